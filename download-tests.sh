@@ -40,4 +40,93 @@ NES_TEST_DOC_URL="https://www.qmtpro.com/~nes/misc/nestest.txt"
 NES_TEST_DOC_HASH="8291241ba9a0885b9a604a4685101a1473e22b3aa070bc828e3b8c342d7f71fb  ./tests/nestest/nestest.txt"
 download_test_file "$NES_TEST_DOC_PATH" "$NES_TEST_DOC_URL" "$NES_TEST_DOC_HASH"
 
+# blargg's test ROMs, which report their results at $6000, from
+# https://github.com/christopherpow/nes-test-roms. Each ROM goes in
+# ./tests/<suite>/.
+#
+# An empty hash hasn't been pinned yet. The download is removed rather than
+# kept unchecked, and its hash is printed so that it can be checked against
+# the upstream repository and pinned here.
+TEST_ROMS_URL="https://raw.githubusercontent.com/christopherpow/nes-test-roms/master"
+
+function download_test_rom() {
+    suite="$1"
+    url_path="$2"
+    hash="$3"
+
+    path="./tests/$suite/$(basename "$url_path")"
+    download_test_file "$path" "$TEST_ROMS_URL/$url_path" "$hash  $path"
+}
+
+download_test_rom cpu_instrs "cpu_instrs/individual/01-basics.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/02-implied.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/03-immediate.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/04-zero_page.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/05-zp_xy.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/06-absolute.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/07-abs_xy.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/08-ind_x.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/09-ind_y.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/10-branches.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/11-stack.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/12-jmp_jsr.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/13-rts.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/14-rti.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/15-brk.nes" ""
+download_test_rom cpu_instrs "cpu_instrs/individual/16-special.nes" ""
+
+download_test_rom instr_timing "instr_timing/rom_singles/1-instr_timing.nes" ""
+download_test_rom instr_timing "instr_timing/rom_singles/2-branch_timing.nes" ""
+
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/01-vbl_basics.nes" ""
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/02-vbl_set_time.nes" ""
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/03-vbl_clear_time.nes" ""
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/04-nmi_control.nes" ""
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/05-nmi_timing.nes" ""
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/06-suppression.nes" ""
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/07-nmi_on_timing.nes" ""
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/08-nmi_off_timing.nes" ""
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/09-even_odd_frames.nes" ""
+download_test_rom ppu_vbl_nmi "ppu_vbl_nmi/rom_singles/10-even_odd_timing.nes" ""
+
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/01-basics.nes" ""
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/02-alignment.nes" ""
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/03-corners.nes" ""
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/04-flip.nes" ""
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/05-left_clip.nes" ""
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/06-right_edge.nes" ""
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/07-screen_bottom.nes" ""
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/08-double_height.nes" ""
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/09-timing.nes" ""
+download_test_rom ppu_sprite_hit "ppu_sprite_hit/rom_singles/10-timing_order.nes" ""
+
+download_test_rom apu_test "apu_test/rom_singles/1-len_ctr.nes" ""
+download_test_rom apu_test "apu_test/rom_singles/2-len_table.nes" ""
+download_test_rom apu_test "apu_test/rom_singles/3-irq_flag.nes" ""
+download_test_rom apu_test "apu_test/rom_singles/4-jitter.nes" ""
+download_test_rom apu_test "apu_test/rom_singles/5-len_timing.nes" ""
+download_test_rom apu_test "apu_test/rom_singles/6-irq_flag_timing.nes" ""
+download_test_rom apu_test "apu_test/rom_singles/7-dmc_basics.nes" ""
+download_test_rom apu_test "apu_test/rom_singles/8-dmc_rates.nes" ""
+
+download_test_rom mmc3_test "mmc3_test_2/rom_singles/1-clocking.nes" ""
+download_test_rom mmc3_test "mmc3_test_2/rom_singles/2-details.nes" ""
+download_test_rom mmc3_test "mmc3_test_2/rom_singles/3-A12_clocking.nes" ""
+download_test_rom mmc3_test "mmc3_test_2/rom_singles/4-scanline_timing.nes" ""
+download_test_rom mmc3_test "mmc3_test_2/rom_singles/5-MMC3.nes" ""
+download_test_rom mmc3_test "mmc3_test_2/rom_singles/6-MMC3_alt.nes" ""
+
+# The nes6502 single-step test vectors, a file for each opcode, from
+# https://github.com/SingleStepTests/ProcessorTests. They go in
+# ./tests/single_step/ and, like the ROMs above, a hash is pinned by adding it
+# here keyed by the opcode.
+SINGLE_STEP_URL="https://raw.githubusercontent.com/SingleStepTests/ProcessorTests/main/nes6502/v1"
+declare -A SINGLE_STEP_HASHES=()
+
+for opcode in $(seq 0 255); do
+    name="$(printf "%02x" "$opcode")"
+    path="./tests/single_step/$name.json"
+    download_test_file "$path" "$SINGLE_STEP_URL/$name.json" "${SINGLE_STEP_HASHES[$name]}  $path"
+done
+
 echo ""
@@ -0,0 +1,10 @@
+#![no_main]
+
+use green_nes::emu::cartridge::{ines::create_mapper, parse};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(ines) = parse(data) {
+        let _ = create_mapper(ines);
+    }
+});
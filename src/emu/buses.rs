@@ -1,9 +1,14 @@
 use std::array;
 
+use sdl2::keyboard::Keycode;
+
 use crate::concat_u8;
 use crate::emu::cartridge::Cartridge;
-use crate::emu::io::controller::Controller;
+use crate::emu::cpu::bus::Bus;
+use crate::emu::error::{Error, StateError};
+use crate::emu::io::{InputDevice, InputType, PORT_COUNT, PORT_DATA_MASK, controller::Buttons};
 use crate::emu::ppu::{OAM_SIZE, PPU};
+use crate::emu::state::{StateReader, StateWriter};
 
 // Internal RAM
 
@@ -47,7 +52,8 @@ pub struct Buses {
     irq: bool,
     /// The NMI pin, where `True` means the pin is pulled low.
     nmi: bool,
-    pub controller: Controller,
+    /// The devices plugged into the two controller ports.
+    pub ports: [Box<dyn InputDevice>; PORT_COUNT],
 }
 
 impl Buses {
@@ -60,13 +66,17 @@ impl Buses {
             nmi: false,
             irq: false,
             ppu: PPU::new(cart),
-            controller: Controller::new(),
+            ports: InputType::Standard.create_ports(),
         }
     }
 
     pub fn tick(&mut self) {
         self.ppu.tick();
         self.nmi = self.ppu.get_nmi();
+
+        let mut mapper = self.cart.mapper.borrow_mut();
+        mapper.tick();
+        self.irq = mapper.get_irq();
     }
 
     /// Returns a byte from the given memory address.
@@ -112,12 +122,15 @@ impl Buses {
                 0x13 => 0, // todo: DMC_LEN
                 0x14 => 0, // todo: OAM_DMA
                 0x15 => 0, // todo: SND_CHN
-                0x16 => self.controller.read(),
-                0x17 => 0, // todo: JOY2
+                0x16 => self.read_port(0),
+                0x17 => self.read_port(1),
                 _ => unreachable!("mod 0x18 is no greater than 0x17"),
             },
             TEST_MODE_START_ADDR..TEST_MODE_END_ADDR => 0, // TODO
-            CARTRIDGE_ROM_MAPPER_START_ADDR.. => self.cart.mapper.borrow().prg_read(addr),
+            CARTRIDGE_ROM_MAPPER_START_ADDR.. => {
+                let data = self.cart.mapper.borrow_mut().prg_read(addr);
+                data.unwrap_or(self.data)
+            }
         }
     }
 
@@ -142,9 +155,22 @@ impl Buses {
                     _ => unreachable!("mod 8 is no greater than 7"),
                 }
             }
-            IO_START_ADDR..IO_END_ADDR => 0,               // TODO
+            IO_START_ADDR..IO_END_ADDR => match (addr - IO_START_ADDR) % 0x18 {
+                0x16 => {
+                    (self.data & !PORT_DATA_MASK) | (self.ports[0].peek(&self.ppu) & PORT_DATA_MASK)
+                }
+                0x17 => {
+                    (self.data & !PORT_DATA_MASK) | (self.ports[1].peek(&self.ppu) & PORT_DATA_MASK)
+                }
+                _ => 0, // TODO
+            },
             TEST_MODE_START_ADDR..TEST_MODE_END_ADDR => 0, // TODO
-            CARTRIDGE_ROM_MAPPER_START_ADDR.. => self.cart.mapper.borrow().prg_read(addr),
+            CARTRIDGE_ROM_MAPPER_START_ADDR.. => self
+                .cart
+                .mapper
+                .borrow()
+                .prg_peek(addr)
+                .unwrap_or(self.data),
         }
     }
 
@@ -209,8 +235,13 @@ impl Buses {
                     self.ppu.write_oam_dma(&oam_data);
                 }
                 0x15 => (), // todo: SND_CHN
-                0x16 => self.controller.write(data),
-                0x17 => (), // todo: JOY2
+                0x16 => {
+                    // The outputs are shared by both ports.
+                    for device in self.ports.iter_mut() {
+                        device.write(data);
+                    }
+                }
+                0x17 => (), // todo: APU frame counter
                 _ => unreachable!("mod 0x18 is no greater than 0x17"),
             },
             TEST_MODE_START_ADDR..TEST_MODE_END_ADDR => (), // TODO
@@ -220,6 +251,62 @@ impl Buses {
         }
     }
 
+    /// Reads the device in the given controller port. Only D0–D4 are driven by
+    /// the device; the upper bits are left over from the last byte on the data
+    /// bus, which is usually the high byte of the address ($40).
+    fn read_port(&mut self, port: usize) -> u8 {
+        let data = self.ports[port].read(&self.ppu) & PORT_DATA_MASK;
+        (self.data & !PORT_DATA_MASK) | data
+    }
+
+    /// Presses or releases a button on the given player's controller. Players 1
+    /// and 2 use the first controller on each port, and players 3 and 4 the
+    /// second controller on each port through a four player adapter.
+    pub fn set_button_pressed(&mut self, player: usize, button: Buttons, pressed: bool) {
+        let port = player % PORT_COUNT;
+        let controller = player / PORT_COUNT;
+        self.ports[port].set_button_pressed(controller, button, pressed);
+    }
+
+    /// Sets which buttons are pressed on the given player's controller,
+    /// releasing the rest.
+    pub fn set_buttons(&mut self, player: usize, buttons: Buttons) {
+        let pressed = u8::from(buttons);
+        self.set_button_pressed(player, Buttons::from(pressed), true);
+        self.set_button_pressed(player, Buttons::from(!pressed), false);
+    }
+
+    /// Moves the mouse pointer for the devices in both ports.
+    pub fn set_pointer_position(&mut self, position: Option<(i32, i32)>) {
+        for device in self.ports.iter_mut() {
+            device.set_pointer_position(position);
+        }
+    }
+
+    /// Presses or releases the mouse button for the devices in both ports.
+    pub fn set_pointer_pressed(&mut self, pressed: bool) {
+        for device in self.ports.iter_mut() {
+            device.set_pointer_pressed(pressed);
+        }
+    }
+
+    /// Presses or releases a host keyboard key for the devices in both ports.
+    pub fn set_key_pressed(&mut self, key: Keycode, pressed: bool) {
+        for device in self.ports.iter_mut() {
+            device.set_key_pressed(key, pressed);
+        }
+    }
+
+    /// Returns the contents of the CPU's RAM.
+    pub fn get_ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Returns `true` if a keyboard is plugged into either port.
+    pub fn has_keyboard(&self) -> bool {
+        self.ports.iter().any(|device| device.is_keyboard())
+    }
+
     /// Returns `true` if the IRQ pin is pulled low.
     pub fn get_irq(&self) -> bool {
         self.irq
@@ -233,4 +320,134 @@ impl Buses {
     pub fn get_ppu(&self) -> &PPU {
         &self.ppu
     }
+
+    pub fn get_cartridge(&self) -> &Cartridge {
+        &self.cart
+    }
+
+    /// Writes the state of RAM, the bus latches, the devices in the controller
+    /// ports, the PPU and the cartridge to a save state.
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.ram);
+        state.write_bytes(&[self.addr.0, self.addr.1, self.data]);
+        state.write_bool(self.irq);
+        state.write_bool(self.nmi);
+        for device in self.ports.iter() {
+            state.write_section(|state| device.save_state(state));
+        }
+
+        // The cartridge goes first, as the PPU renders from its CHR memory.
+        state.write_section(|state| self.cart.mapper.borrow().save_state(state));
+        self.ppu.save_state(state);
+    }
+
+    /// Restores the state written by [`Buses::save_state`]. The devices in the
+    /// controller ports must be the same as when the state was saved.
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        state.read_bytes(&mut self.ram)?;
+        let mut latches = [0; 3];
+        state.read_bytes(&mut latches)?;
+        let [addr_high, addr_low, data] = latches;
+        self.addr = (addr_high, addr_low);
+        self.data = data;
+        self.irq = state.read_bool()?;
+        self.nmi = state.read_bool()?;
+        for (port, device) in self.ports.iter_mut().enumerate() {
+            state
+                .read_section(|state| device.load_state(state))
+                .map_err(|_| StateError::InvalidState {
+                    message: format!(
+                        "controller port {} held a different device when the state was saved",
+                        port + 1
+                    ),
+                })?;
+        }
+
+        state.read_section(|state| self.cart.mapper.borrow_mut().load_state(state))?;
+        self.ppu.load_state(state)
+    }
+}
+
+impl Bus for Buses {
+    fn set_addr(&mut self, addr: (u8, u8)) {
+        self.addr = addr;
+    }
+
+    fn get_data(&self) -> u8 {
+        self.data
+    }
+
+    fn set_data(&mut self, data: u8) {
+        self.data = data;
+    }
+
+    fn read(&mut self) -> u8 {
+        Buses::read(self)
+    }
+
+    fn write(&mut self, data: u8) {
+        Buses::write(self, data);
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        Buses::peek(self, addr)
+    }
+
+    fn get_irq(&self) -> bool {
+        self.irq
+    }
+
+    fn get_nmi(&self) -> bool {
+        self.nmi
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        buses::Buses,
+        cartridge::{LoadOptions, ines::INES_TAG, read_cartridge},
+        io::controller::Buttons,
+    };
+
+    fn create_buses() -> Buses {
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 0]);
+        rom.resize(16 + 0x4000, 0);
+        Buses::new(read_cartridge(&rom, &LoadOptions::default()).expect("ROM should load"))
+    }
+
+    /// Strobes the controllers, leaving $40 on the data bus as the last byte
+    /// written, then reads a port eight times and twice more.
+    fn read_port(buses: &mut Buses, addr_low: u8) -> Vec<u8> {
+        buses.addr = (0x40, 0x16);
+        buses.write(1);
+        buses.write(0x40);
+
+        buses.addr = (0x40, addr_low);
+        (0..10)
+            .map(|_| {
+                let peeked = buses.peek(0x4000 | addr_low as u16);
+                let data = buses.read();
+                assert_eq!(peeked, data);
+                data
+            })
+            .collect()
+    }
+
+    #[test]
+    fn controllers_are_read_with_open_bus() {
+        let mut buses = create_buses();
+        buses.set_buttons(0, Buttons::from(Buttons::A | Buttons::START));
+        buses.set_buttons(1, Buttons::from(Buttons::B | Buttons::RIGHT));
+
+        assert_eq!(
+            read_port(&mut buses, 0x16),
+            [0x41, 0x40, 0x40, 0x41, 0x40, 0x40, 0x40, 0x40, 0x41, 0x41]
+        );
+        assert_eq!(
+            read_port(&mut buses, 0x17),
+            [0x40, 0x41, 0x40, 0x40, 0x40, 0x40, 0x40, 0x41, 0x41, 0x41]
+        );
+    }
 }
@@ -0,0 +1,136 @@
+use std::io::{Cursor, Read};
+
+use crate::emu::error::{Error, FileError};
+
+/// The magic bytes at the start of a zip archive.
+pub const ZIP_TAG: [u8; 4] = *b"PK\x03\x04";
+
+/// The file extensions of ROMs that are picked from an archive when no entry is
+/// given.
+pub const ROM_EXTENSIONS: [&str; 4] = ["nes", "unf", "unif", "fds"];
+
+/// The largest entry that's read from an archive, which is well beyond any
+/// real cartridge or disk image. Anything bigger is rejected rather than
+/// decompressed into memory.
+pub const MAX_ROM_SIZE: usize = 32 * 1024 * 1024;
+
+/// The most memory reserved up front for a decompressed entry. The size in the
+/// archive's header can't be trusted, so anything larger is grown into as it's
+/// read.
+const MAX_PREALLOCATED_SIZE: usize = 4 * 1024 * 1024;
+
+/// Returns `true` if the data is a zip archive.
+pub fn is_zip(data: &[u8]) -> bool {
+    data.starts_with(&ZIP_TAG)
+}
+
+/// Decompresses a ROM from a zip archive. If an entry name is given, then that
+/// entry is read; otherwise the first entry with a ROM file extension is.
+pub fn read_zip_entry(data: &[u8], entry: Option<&str>) -> Result<Vec<u8>, Error> {
+    let archive_failed = |message: String| Error::from(FileError::ArchiveFailed { message });
+
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(data)).map_err(|e| archive_failed(e.to_string()))?;
+
+    let name = match entry {
+        Some(name) => name.to_string(),
+        None => archive
+            .file_names()
+            .filter(|name| has_rom_extension(name))
+            .min_by_key(|name| archive.index_for_name(name))
+            .map(|name| name.to_string())
+            .ok_or_else(|| archive_failed("archive contains no ROM files".to_string()))?,
+    };
+
+    let file = archive
+        .by_name(&name)
+        .map_err(|e| archive_failed(format!("{name}: {e}")))?;
+
+    let capacity = file.size().min(MAX_PREALLOCATED_SIZE as u64) as usize;
+    let mut rom = Vec::with_capacity(capacity);
+    file.take(MAX_ROM_SIZE as u64 + 1)
+        .read_to_end(&mut rom)
+        .map_err(|e| archive_failed(format!("{name}: {e}")))?;
+
+    if rom.len() > MAX_ROM_SIZE {
+        return Err(archive_failed(format!(
+            "{name}: entry is larger than {MAX_ROM_SIZE} bytes"
+        )));
+    }
+
+    Ok(rom)
+}
+
+fn has_rom_extension(name: &str) -> bool {
+    let Some((_, extension)) = name.rsplit_once('.') else {
+        return false;
+    };
+
+    ROM_EXTENSIONS
+        .iter()
+        .any(|rom_extension| extension.eq_ignore_ascii_case(rom_extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+    use crate::emu::cartridge::archive::{MAX_ROM_SIZE, is_zip, read_zip_entry};
+
+    #[test]
+    fn first_rom_entry_is_read() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in [
+            ("readme.txt", b"readme".as_slice()),
+            ("game.NES", b"game"),
+            ("other.nes", b"other"),
+        ] {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .expect("entry should be created");
+            writer.write_all(contents).expect("entry should be written");
+        }
+        let data = writer.finish().expect("archive should finish").into_inner();
+
+        assert!(is_zip(&data));
+        assert_eq!(
+            read_zip_entry(&data, None).expect("ROM should be read"),
+            b"game"
+        );
+        assert_eq!(
+            read_zip_entry(&data, Some("other.nes")).expect("entry should be read"),
+            b"other"
+        );
+        assert!(read_zip_entry(&data, Some("missing.nes")).is_err());
+    }
+
+    #[test]
+    fn oversized_entries_are_rejected() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("music.nsf", SimpleFileOptions::default())
+            .expect("entry should be created");
+
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(true);
+        for (name, size) in [("max.nes", MAX_ROM_SIZE), ("huge.nes", MAX_ROM_SIZE + 1)] {
+            writer
+                .start_file(name, options)
+                .expect("entry should be created");
+            writer
+                .write_all(&vec![0; size])
+                .expect("entry should be written");
+        }
+        let data = writer.finish().expect("archive should finish").into_inner();
+
+        // NSF files can't be played, so they're skipped over.
+        assert_eq!(
+            read_zip_entry(&data, None).map(|rom| rom.len()).ok(),
+            Some(MAX_ROM_SIZE)
+        );
+        assert!(read_zip_entry(&data, Some("huge.nes")).is_err());
+    }
+}
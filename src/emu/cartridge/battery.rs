@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+
+use crate::emu::{
+    cartridge::Cartridge,
+    error::{Error, FileError},
+};
+
+const SAVE_FILE_EXTENSION: &str = "sav";
+const TEMP_FILE_EXTENSION: &str = "sav.tmp";
+
+/// A `.sav` file holding the contents of a cartridge's battery-backed PRG RAM,
+/// or other memory the cartridge persists between sessions.
+pub struct SaveFile {
+    path: PathBuf,
+    /// The save data as of the last load or write, used to skip writes
+    /// when nothing has changed.
+    last_saved: Vec<u8>,
+}
+
+impl SaveFile {
+    /// Creates a save file for the ROM at the given path. The save file is
+    /// named after the ROM and placed in `save_dir` if given, or next to the
+    /// ROM otherwise.
+    pub fn new(rom_path: &str, save_dir: Option<&str>) -> Self {
+        let rom_path = Path::new(rom_path);
+        let file_name = rom_path
+            .with_extension(SAVE_FILE_EXTENSION)
+            .file_name()
+            .map(|name| name.to_owned())
+            .unwrap_or_default();
+
+        let path = match save_dir {
+            Some(dir) => Path::new(dir).join(file_name),
+            None => rom_path.with_extension(SAVE_FILE_EXTENSION),
+        };
+
+        Self {
+            path,
+            last_saved: Vec::new(),
+        }
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the save file into the cartridge's save data. A missing save file
+    /// is not an error; the save data is left as-is.
+    pub fn load(&mut self, cart: &Cartridge) -> Result<(), Error> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+
+        let data = std::fs::read(&self.path).map_err(|e| {
+            Error::from(FileError::FileOpenFailed {
+                message: format!("{}: {e}", self.path.display()),
+            })
+        })?;
+
+        let mut mapper = cart.mapper.borrow_mut();
+        mapper.set_save_data(&data);
+        self.last_saved = mapper.get_save_data();
+
+        Ok(())
+    }
+
+    /// Writes the cartridge's save data to the save file if it has changed
+    /// since the last load or write.
+    ///
+    /// The data is first written to a temporary file which then replaces the
+    /// save file, so an interrupted write never leaves a truncated save behind.
+    pub fn write(&mut self, cart: &Cartridge) -> Result<(), Error> {
+        let data = cart.mapper.borrow().get_save_data();
+        if data == self.last_saved {
+            return Ok(());
+        }
+
+        let write_failed = |e: std::io::Error| {
+            Error::from(FileError::FileWriteFailed {
+                message: format!("{}: {e}", self.path.display()),
+            })
+        };
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(write_failed)?;
+        }
+
+        let temp_path = self.path.with_extension(TEMP_FILE_EXTENSION);
+        std::fs::write(&temp_path, &data).map_err(write_failed)?;
+        std::fs::rename(&temp_path, &self.path).map_err(write_failed)?;
+
+        self.last_saved = data;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::cartridge::{
+        Cartridge, LoadOptions, battery::SaveFile, ines::INES_TAG, read_cartridge,
+    };
+
+    /// Creates an NROM cartridge with 8 KiB of battery-backed PRG RAM.
+    fn create_cartridge() -> Cartridge {
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 1, 0b_0000_0010, 0b_0000_1000, 0, 0, 0x70, 0]);
+        rom.resize(16 + 0x4000 + 0x2000, 0);
+        read_cartridge(&rom, &LoadOptions::default()).expect("ROM should load")
+    }
+
+    #[test]
+    fn save_files_round_trip() {
+        let dir = std::env::temp_dir().join(format!("green_nes_battery_{}", std::process::id()));
+        let rom_path = dir.join("roms/game.nes");
+        let save_dir = dir.join("saves");
+
+        let next_to_rom = SaveFile::new(rom_path.to_str().unwrap(), None);
+        assert_eq!(next_to_rom.get_path(), dir.join("roms/game.sav"));
+
+        let mut save_file = SaveFile::new(rom_path.to_str().unwrap(), save_dir.to_str());
+        let save_path = save_dir.join("game.sav");
+        assert_eq!(save_file.get_path(), save_path);
+
+        // A missing save file leaves the save data as it is.
+        let cart = create_cartridge();
+        save_file.load(&cart).unwrap();
+        cart.mapper.borrow_mut().prg_write(0x6000, 0x12);
+        cart.mapper.borrow_mut().prg_write(0x7FFF, 0x34);
+        save_file.write(&cart).unwrap();
+
+        let data = std::fs::read(&save_path).unwrap();
+        assert_eq!(data.len(), 0x2000);
+        assert_eq!((data[0], data[0x1FFF]), (0x12, 0x34));
+        assert!(!save_dir.join("game.sav.tmp").exists());
+
+        let cart = create_cartridge();
+        SaveFile::new(rom_path.to_str().unwrap(), save_dir.to_str())
+            .load(&cart)
+            .unwrap();
+        assert_eq!(cart.mapper.borrow().get_save_data(), data);
+
+        // A short save file only fills the start of the save data.
+        std::fs::write(&save_path, [0xAA, 0xBB]).unwrap();
+        let cart = create_cartridge();
+        cart.mapper.borrow_mut().prg_write(0x6002, 0xCC);
+        SaveFile::new(rom_path.to_str().unwrap(), save_dir.to_str())
+            .load(&cart)
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let save_data = cart.mapper.borrow().get_save_data();
+        assert_eq!(save_data.len(), 0x2000);
+        assert_eq!(save_data[..3], [0xAA, 0xBB, 0xCC]);
+    }
+}
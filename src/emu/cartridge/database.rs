@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+
+use crate::emu::{
+    cartridge::{Header, Timing},
+    error::{CartridgeError, Error},
+};
+
+/// The bundled game database. See the file itself for a description of the
+/// format.
+const BUNDLED_DATABASE: &str = include_str!("database.txt");
+
+const FIELD_SEPARATOR: char = '|';
+const FIELD_COUNT: usize = 12;
+const UNSPECIFIED_FIELD: &str = "-";
+const COMMENT_PREFIX: char = '#';
+
+/// The nametable arrangement of a cartridge in the game database.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+    FourScreen,
+}
+
+/// Corrections to a ROM's header for a known dump. Fields that are `None` keep
+/// the value from the header.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub sha1: Option<String>,
+    pub mapper_index: Option<u16>,
+    pub submapper_index: Option<u8>,
+    pub mirroring: Option<Mirroring>,
+    pub prg_ram_size: Option<usize>,
+    pub prg_nvram_size: Option<usize>,
+    pub chr_ram_size: Option<usize>,
+    pub chr_nvram_size: Option<usize>,
+    pub timing: Option<Timing>,
+    pub default_expansion_device: Option<u8>,
+    pub title: String,
+}
+
+impl Entry {
+    /// Overrides the given header with the fields specified by this entry.
+    pub fn apply(&self, header: &mut Header) {
+        if let Some(mapper_index) = self.mapper_index {
+            header.mapper_index = mapper_index;
+        }
+        if let Some(submapper_index) = self.submapper_index {
+            header.submapper_index = submapper_index;
+        }
+        if let Some(mirroring) = self.mirroring {
+            // Vertical mirroring is a horizontal nametable arrangement, which
+            // the iNES flags store as `true`.
+            header.nametable_arrangement = mirroring == Mirroring::Vertical;
+            header.alternative_nametable_arrangement = mirroring == Mirroring::FourScreen;
+        }
+        if let Some(prg_ram_size) = self.prg_ram_size {
+            header.prg_ram_size = prg_ram_size;
+        }
+        if let Some(prg_nvram_size) = self.prg_nvram_size {
+            header.prg_nvram_size = prg_nvram_size;
+        }
+        if let Some(chr_ram_size) = self.chr_ram_size {
+            header.chr_ram_size = chr_ram_size;
+        }
+        if let Some(chr_nvram_size) = self.chr_nvram_size {
+            header.chr_nvram_size = chr_nvram_size;
+        }
+        if self.prg_nvram_size.is_some() || self.chr_nvram_size.is_some() {
+            header.has_battery = header.prg_nvram_size != 0 || header.chr_nvram_size != 0;
+        }
+        if let Some(timing) = self.timing {
+            header.timing = timing;
+        }
+        if let Some(default_expansion_device) = self.default_expansion_device {
+            header.default_expansion_device = default_expansion_device;
+        }
+
+        header.title = Some(self.title.clone());
+    }
+}
+
+/// A database of known ROM dumps, keyed by the CRC32 of their PRG and CHR
+/// ROM.
+#[derive(Default)]
+pub struct Database {
+    entries: HashMap<u32, Vec<Entry>>,
+}
+
+impl Database {
+    /// Returns the database bundled with the emulator.
+    pub fn bundled() -> Result<Self, Error> {
+        Self::parse(BUNDLED_DATABASE)
+    }
+
+    /// Parses a database from its text representation.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut database = Database::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(COMMENT_PREFIX) {
+                continue;
+            }
+
+            let (crc32, entry) = parse_entry(line).map_err(|message| {
+                Error::from(CartridgeError::InvalidDatabaseEntry {
+                    line: index + 1,
+                    message,
+                })
+            })?;
+
+            database.entries.entry(crc32).or_default().push(entry);
+        }
+
+        Ok(database)
+    }
+
+    /// Returns the entry for the cartridge with the given PRG and CHR ROM.
+    pub fn lookup(&self, prg_data: &[u8], chr_data: &[u8]) -> Option<&Entry> {
+        let mut crc32 = crc32fast::Hasher::new();
+        crc32.update(prg_data);
+        crc32.update(chr_data);
+
+        let entries = self.entries.get(&crc32.finalize())?;
+
+        let mut sha1 = sha1_smol::Sha1::new();
+        sha1.update(prg_data);
+        sha1.update(chr_data);
+        let sha1 = sha1.digest().to_string();
+
+        entries.iter().find(|entry| match &entry.sha1 {
+            Some(expected) => expected.eq_ignore_ascii_case(&sha1),
+            None => true,
+        })
+    }
+}
+
+/// Converts the NES 2.0 XML database (`nes20db.xml`) into entries for the
+/// game database, one per line. Each game's title is taken from the file name
+/// in the comment that precedes its ROM hashes.
+///
+/// https://forums.nesdev.org/viewtopic.php?t=19940
+pub fn import_nes20db(xml: &str) -> Result<String, Error> {
+    let document = roxmltree::Document::parse(xml).map_err(|e| {
+        Error::from(CartridgeError::InvalidDatabaseImport {
+            message: e.to_string(),
+        })
+    })?;
+
+    let mut text = String::new();
+    for game in document
+        .descendants()
+        .filter(|node| node.has_tag_name("game"))
+    {
+        if let Some(line) = import_game(game) {
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+
+    Ok(text)
+}
+
+/// Returns the database entry for a `<game>` element, or `None` if it lacks
+/// the ROM hashes or board.
+fn import_game(game: roxmltree::Node) -> Option<String> {
+    let child = |name: &str| game.children().find(|node| node.has_tag_name(name));
+    let size = |name: &str| {
+        child(name)
+            .and_then(|node| node.attribute("size"))
+            .unwrap_or("0")
+    };
+
+    let rom = child("rom")?;
+    let pcb = child("pcb")?;
+
+    let title = game
+        .children()
+        .find(|node| node.is_comment())
+        .and_then(|node| node.text())
+        .map(|path| {
+            let name = path.trim().rsplit(['\\', '/']).next().unwrap_or_default();
+            name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+        })
+        .unwrap_or_default();
+
+    let mirroring = match pcb.attribute("mirroring") {
+        Some("H") => "h",
+        Some("V") => "v",
+        Some("4") => "4",
+        _ => UNSPECIFIED_FIELD,
+    };
+    let timing = match child("console").and_then(|node| node.attribute("region")) {
+        Some("0") => "ntsc",
+        Some("1") => "pal",
+        Some("2") => "multi",
+        Some("3") => "dendy",
+        _ => UNSPECIFIED_FIELD,
+    };
+    let input = child("expansion")
+        .and_then(|node| node.attribute("type"))
+        .unwrap_or(UNSPECIFIED_FIELD);
+
+    let fields = [
+        rom.attribute("crc32")?,
+        rom.attribute("sha1").unwrap_or(UNSPECIFIED_FIELD),
+        pcb.attribute("mapper")?,
+        pcb.attribute("submapper").unwrap_or("0"),
+        mirroring,
+        size("prgram"),
+        size("prgnvram"),
+        size("chrram"),
+        size("chrnvram"),
+        timing,
+        input,
+        title,
+    ];
+
+    Some(fields.join("|"))
+}
+
+fn parse_entry(line: &str) -> Result<(u32, Entry), String> {
+    let fields: Vec<&str> = line.splitn(FIELD_COUNT, FIELD_SEPARATOR).collect();
+    let [
+        crc32,
+        sha1,
+        mapper,
+        submapper,
+        mirroring,
+        prg_ram,
+        prg_nvram,
+        chr_ram,
+        chr_nvram,
+        timing,
+        input,
+        title,
+    ] = fields.as_slice()
+    else {
+        return Err(format!(
+            "expected {FIELD_COUNT} fields, found {}",
+            fields.len()
+        ));
+    };
+
+    let crc32 = u32::from_str_radix(crc32.trim(), 16).map_err(|e| format!("invalid CRC32: {e}"))?;
+
+    let sha1 = match sha1.trim() {
+        UNSPECIFIED_FIELD => None,
+        sha1 if sha1.len() == 40 && sha1.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Some(sha1.to_string())
+        }
+        sha1 => return Err(format!("invalid SHA-1: {sha1}")),
+    };
+
+    let mirroring = parse_field(mirroring, |field| match field {
+        "h" => Ok(Mirroring::Horizontal),
+        "v" => Ok(Mirroring::Vertical),
+        "4" => Ok(Mirroring::FourScreen),
+        _ => Err(format!("invalid mirroring: {field}")),
+    })?;
+
+    let timing = parse_field(timing, |field| match field {
+        "ntsc" => Ok(Timing::Ntsc),
+        "pal" => Ok(Timing::Pal),
+        "multi" => Ok(Timing::MultipleRegion),
+        "dendy" => Ok(Timing::Dendy),
+        _ => Err(format!("invalid timing: {field}")),
+    })?;
+
+    let entry = Entry {
+        sha1,
+        mapper_index: parse_number(mapper, "mapper")?,
+        submapper_index: parse_number(submapper, "submapper")?,
+        mirroring,
+        prg_ram_size: parse_number(prg_ram, "PRG RAM size")?,
+        prg_nvram_size: parse_number(prg_nvram, "PRG NVRAM size")?,
+        chr_ram_size: parse_number(chr_ram, "CHR RAM size")?,
+        chr_nvram_size: parse_number(chr_nvram, "CHR NVRAM size")?,
+        timing,
+        default_expansion_device: parse_number(input, "input device")?,
+        title: title.trim().to_string(),
+    };
+
+    Ok((crc32, entry))
+}
+
+fn parse_field<T>(
+    field: &str,
+    parse: impl FnOnce(&str) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    match field.trim() {
+        UNSPECIFIED_FIELD => Ok(None),
+        field => parse(field).map(Some),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(field: &str, name: &str) -> Result<Option<T>, String> {
+    parse_field(field, |field| {
+        field
+            .parse()
+            .map_err(|_| format!("invalid {name}: {field}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::cartridge::{
+        Header, LoadOptions, Timing,
+        database::{Database, Mirroring, import_nes20db},
+        ines::INES_TAG,
+        read_cartridge,
+    };
+
+    #[test]
+    fn bundled_database_parses() {
+        Database::bundled().expect("bundled database should parse");
+    }
+
+    #[test]
+    fn bundled_database_corrects_known_dumps() {
+        let database = Database::bundled().expect("bundled database should parse");
+        let entry = &database.entries[&0x3337EC46][0];
+        assert_eq!(entry.title, "Super Mario Bros.");
+
+        // A "DiskDude!" header reads as mapper 64 with junk in bytes 7-15.
+        let mut header = Header {
+            mapper_index: 64,
+            timing: Timing::Pal,
+            ..Header::default()
+        };
+        entry.apply(&mut header);
+        assert_eq!(header.mapper_index, 0);
+        assert!(header.nametable_arrangement);
+        assert_eq!(header.timing, Timing::Ntsc);
+    }
+
+    #[test]
+    fn nes20db_is_imported() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<nes20db>
+  <game>
+    <!-- Nintendo\Super Mario Bros. (World).nes -->
+    <prgrom size="32768" crc32="5CF548D3"/>
+    <chrrom size="8192" crc32="867B51AD"/>
+    <rom size="40960" crc32="3337EC46" sha1="EA343F4E445A9050D4B4FBAC2C77D0693B1D0922"/>
+    <console type="0" region="0"/>
+    <expansion type="1"/>
+    <pcb mapper="0" submapper="0" mirroring="V" battery="0"/>
+  </game>
+  <game>
+    <!-- Nintendo/Legend of Zelda, The (Europe).nes -->
+    <prgrom size="131072" crc32="ED7F5555"/>
+    <rom size="131072" crc32="ED7F5555"/>
+    <prgnvram size="8192"/>
+    <chrram size="8192"/>
+    <console type="0" region="1"/>
+    <pcb mapper="1" submapper="0" mirroring="H" battery="1"/>
+  </game>
+  <game>
+    <!-- Missing the board -->
+    <rom size="16" crc32="00000000"/>
+  </game>
+</nes20db>"#;
+
+        let text = import_nes20db(xml).expect("XML should be imported");
+        assert_eq!(
+            text,
+            "3337EC46|EA343F4E445A9050D4B4FBAC2C77D0693B1D0922|0|0|v|0|0|0|0|ntsc|1|Super Mario Bros. (World)\n\
+             ED7F5555|-|1|0|h|0|8192|8192|0|pal|-|Legend of Zelda, The (Europe)\n"
+        );
+
+        let database = Database::parse(&text).expect("imported entries should parse");
+        assert_eq!(database.entries[&0xED7F5555][0].prg_nvram_size, Some(8192));
+        assert!(import_nes20db("<nes20db>").is_err());
+    }
+
+    #[test]
+    fn headers_are_kept_without_a_database() {
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 0]);
+        rom.resize(16 + 0x4000, 0);
+
+        let text = format!(
+            "{:08x}|-|0|-|v|-|-|-|-|pal|-|Test Game",
+            crc32fast::hash(&rom[16..])
+        );
+        let options = LoadOptions {
+            database: Some(Database::parse(&text).expect("database should parse")),
+            ..LoadOptions::default()
+        };
+        let cart = read_cartridge(&rom, &options).expect("ROM should load");
+        assert_eq!(cart.header.timing, Timing::Pal);
+        assert_eq!(cart.header.title.as_deref(), Some("Test Game"));
+
+        // --no-db loads the ROM without a database.
+        let options = LoadOptions {
+            database: None,
+            ..LoadOptions::default()
+        };
+        let cart = read_cartridge(&rom, &options).expect("ROM should load");
+        assert_eq!(cart.header.timing, Timing::Ntsc);
+        assert!(!cart.header.nametable_arrangement);
+        assert_eq!(cart.header.title, None);
+    }
+
+    #[test]
+    fn lookup_overrides_header() {
+        let prg_data = [0xEA; 16];
+        let chr_data = [0x00; 8];
+        let mut crc32 = crc32fast::Hasher::new();
+        crc32.update(&prg_data);
+        crc32.update(&chr_data);
+
+        let text = format!(
+            "{:08x}|-|4|-|v|-|8192|-|-|pal|-|Test Game",
+            crc32.finalize()
+        );
+        let database = Database::parse(&text).expect("database should parse");
+
+        let entry = database
+            .lookup(&prg_data, &chr_data)
+            .expect("entry should be found");
+        assert_eq!(entry.mirroring, Some(Mirroring::Vertical));
+
+        let mut header = Header::default();
+        entry.apply(&mut header);
+        assert_eq!(header.mapper_index, 4);
+        assert!(header.nametable_arrangement);
+        assert_eq!(header.prg_nvram_size, 8192);
+        assert!(header.has_battery);
+        assert_eq!(header.timing, Timing::Pal);
+        assert_eq!(header.title.as_deref(), Some("Test Game"));
+
+        assert!(database.lookup(&prg_data, &[0xFF; 8]).is_none());
+    }
+}
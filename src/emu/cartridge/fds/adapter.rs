@@ -0,0 +1,866 @@
+// https://www.nesdev.org/wiki/Family_Computer_Disk_System
+
+use std::ops::Range;
+
+use crate::emu::{
+    cartridge::{
+        NametableMirroring,
+        fds::{
+            BIOS_SIZE, CHR_RAM_SIZE, DISK_SIDE_SIZE, DiskImage, PRG_RAM_SIZE, add_gaps,
+            audio::{AUDIO_END_ADDR, AUDIO_START_ADDR, Audio},
+            remove_gaps,
+        },
+        mappers::Mapper,
+        memory::{ChrMemory, PrgRam},
+    },
+    error::{Error, StateError},
+    state::{StateReader, StateWriter},
+};
+
+const IRQ_RELOAD_LOW_ADDR: u16 = 0x4020;
+const IRQ_RELOAD_HIGH_ADDR: u16 = 0x4021;
+const IRQ_CONTROL_ADDR: u16 = 0x4022;
+const MASTER_IO_ENABLE_ADDR: u16 = 0x4023;
+const WRITE_DATA_ADDR: u16 = 0x4024;
+const FDS_CONTROL_ADDR: u16 = 0x4025;
+const EXTERNAL_CONNECTOR_OUTPUT_ADDR: u16 = 0x4026;
+
+const DISK_STATUS_ADDR: u16 = 0x4030;
+const READ_DATA_ADDR: u16 = 0x4031;
+const DRIVE_STATUS_ADDR: u16 = 0x4032;
+const EXTERNAL_CONNECTOR_INPUT_ADDR: u16 = 0x4033;
+
+const PRG_RAM_START_ADDR: u16 = 0x6000;
+const PRG_RAM_END_ADDR: u16 = PRG_RAM_START_ADDR + PRG_RAM_SIZE as u16;
+const BIOS_START_ADDR: u16 = 0xE000;
+
+const CHR_MIN_ADDR: u16 = 0x0000;
+const CHR_MAX_ADDR: u16 = 0x1FFF;
+
+const IRQ_CONTROL_REPEAT_MASK: u8 = 0b_0000_0001;
+const IRQ_CONTROL_ENABLED_MASK: u8 = 0b_0000_0010;
+
+const MASTER_IO_DISK_ENABLED_MASK: u8 = 0b_0000_0001;
+const MASTER_IO_SOUND_ENABLED_MASK: u8 = 0b_0000_0010;
+
+const FDS_CONTROL_MOTOR_ON_MASK: u8 = 0b_0000_0001;
+const FDS_CONTROL_RESET_TRANSFER_MASK: u8 = 0b_0000_0010;
+const FDS_CONTROL_READ_MODE_MASK: u8 = 0b_0000_0100;
+const FDS_CONTROL_MIRRORING_MASK: u8 = 0b_0000_1000;
+const FDS_CONTROL_CRC_CONTROL_MASK: u8 = 0b_0001_0000;
+const FDS_CONTROL_DISK_READY_MASK: u8 = 0b_0100_0000;
+const FDS_CONTROL_DISK_IRQ_ENABLED_MASK: u8 = 0b_1000_0000;
+
+const DISK_STATUS_TIMER_IRQ_MASK: u8 = 0b_0000_0001;
+const DISK_STATUS_TRANSFER_COMPLETE_MASK: u8 = 0b_0000_0010;
+const DISK_STATUS_END_OF_HEAD_MASK: u8 = 0b_0100_0000;
+
+const DRIVE_STATUS_DISK_MISSING_MASK: u8 = 0b_0000_0001;
+const DRIVE_STATUS_NOT_READY_MASK: u8 = 0b_0000_0010;
+const DRIVE_STATUS_WRITE_PROTECTED_MASK: u8 = 0b_0000_0100;
+
+/// Bit 7 of `$4033` is set while the drive's batteries are good.
+const BATTERY_GOOD_MASK: u8 = 0b_1000_0000;
+
+/// The number of CPU cycles the drive takes to move the head back to the start
+/// of the disk before it begins reading.
+const SEEK_DELAY_CYCLES: u32 = 50000;
+/// The number of CPU cycles the drive takes to read or write a byte.
+const BYTE_TRANSFER_CYCLES: u32 = 150;
+/// The number of CPU cycles a disk is left ejected for when switching sides,
+/// which is long enough for the BIOS to notice (about one second).
+const DISK_SWITCH_DELAY_CYCLES: u32 = 1_789_773;
+
+/// The polynomial of the CRC-16/KERMIT checksum the drive appends to blocks.
+const CRC_POLYNOMIAL: u16 = 0x8408;
+
+/// The Famicom Disk System's RAM adapter, which sits in the cartridge slot and
+/// connects the disk drive, 32 KiB of PRG RAM, 8 KiB of CHR RAM, the BIOS and
+/// expansion audio. The audio unit's registers and wave RAM are emulated, but
+/// its output is muted until there's an APU to mix it into.
+pub struct FDS {
+    bios: [u8; BIOS_SIZE],
+    prg_ram: PrgRam,
+    chr: ChrMemory,
+    audio: Audio,
+
+    /// All disk sides, stored end to end so they can be saved as a whole.
+    disk_data: Vec<u8>,
+    /// The range of `disk_data` covered by each disk side.
+    disk_sides: Vec<Range<usize>>,
+    /// The inserted disk side, if any.
+    disk_side: Option<usize>,
+    /// The side that will be inserted once `insert_delay` runs out.
+    next_disk_side: usize,
+    insert_delay: u32,
+
+    irq_reload_value: u16,
+    irq_counter: u16,
+    irq_repeat: bool,
+    irq_enabled: bool,
+    timer_irq: bool,
+    disk_irq: bool,
+
+    disk_registers_enabled: bool,
+    sound_registers_enabled: bool,
+
+    motor_on: bool,
+    reset_transfer: bool,
+    read_mode: bool,
+    crc_control: bool,
+    disk_ready: bool,
+    disk_irq_enabled: bool,
+    nametable_arrangement: NametableMirroring,
+
+    write_data: u8,
+    read_data: u8,
+    transfer_complete: bool,
+    external_connector: u8,
+
+    /// The position of the head on the disk side, in bytes.
+    disk_position: usize,
+    /// CPU cycles left until the next byte is transferred.
+    delay: u32,
+    scanning_disk: bool,
+    end_of_head: bool,
+    /// `true` once the gap before a block has been read past.
+    gap_ended: bool,
+    previous_crc_control: bool,
+    crc_accumulator: u16,
+}
+
+impl FDS {
+    pub fn new(disk: DiskImage, bios: [u8; BIOS_SIZE]) -> Self {
+        let (disk_data, disk_sides) = join_disk_sides(disk.sides);
+
+        FDS {
+            bios,
+            prg_ram: PrgRam::new(PRG_RAM_SIZE),
+            chr: ChrMemory::new(Vec::new(), CHR_RAM_SIZE),
+            audio: Audio::new(),
+
+            disk_data,
+            disk_sides,
+            disk_side: Some(0),
+            next_disk_side: 0,
+            insert_delay: 0,
+
+            irq_reload_value: 0,
+            irq_counter: 0,
+            irq_repeat: false,
+            irq_enabled: false,
+            timer_irq: false,
+            disk_irq: false,
+
+            disk_registers_enabled: false,
+            sound_registers_enabled: false,
+
+            motor_on: false,
+            reset_transfer: false,
+            read_mode: false,
+            crc_control: false,
+            disk_ready: false,
+            disk_irq_enabled: false,
+            nametable_arrangement: NametableMirroring::Vertical,
+
+            write_data: 0,
+            read_data: 0,
+            transfer_complete: false,
+            external_connector: 0,
+
+            disk_position: 0,
+            delay: 0,
+            scanning_disk: false,
+            end_of_head: true,
+            gap_ended: false,
+            previous_crc_control: false,
+            crc_accumulator: 0,
+        }
+    }
+
+    /// Returns the current expansion audio output level, from 0 to 63. Nothing
+    /// mixes it in yet, so the audio stays muted until there's an APU.
+    pub fn get_audio_output(&self) -> u8 {
+        self.audio.get_output()
+    }
+
+    /// Returns each disk side as the drive sees it.
+    fn get_disk_side_data(&self) -> impl Iterator<Item = &[u8]> {
+        self.disk_sides
+            .iter()
+            .map(|range| &self.disk_data[range.clone()])
+    }
+
+    /// Returns the inserted disk side, if any.
+    pub fn get_disk_side(&self) -> Option<usize> {
+        self.disk_side
+    }
+
+    fn is_disk_inserted(&self) -> bool {
+        self.disk_side.is_some()
+    }
+
+    fn get_disk_side_range(&self) -> Option<Range<usize>> {
+        self.disk_side.map(|side| self.disk_sides[side].clone())
+    }
+
+    fn read_disk(&self) -> u8 {
+        match self.get_disk_side_range() {
+            Some(range) => self.disk_data[range.start + self.disk_position],
+            None => 0,
+        }
+    }
+
+    fn write_disk(&mut self, data: u8) {
+        if let Some(range) = self.get_disk_side_range() {
+            self.disk_data[range.start + self.disk_position] = data;
+        }
+    }
+
+    fn update_crc(&mut self, data: u8) {
+        for bit in 0..8 {
+            let carry = self.crc_accumulator & 1 != 0;
+            self.crc_accumulator >>= 1;
+            if carry {
+                self.crc_accumulator ^= CRC_POLYNOMIAL;
+            }
+            if data & (1 << bit) != 0 {
+                self.crc_accumulator ^= 0x8000;
+            }
+        }
+    }
+
+    fn tick_timer(&mut self) {
+        if !self.irq_enabled {
+            return;
+        }
+
+        if self.irq_counter == 0 {
+            self.timer_irq = true;
+            self.irq_counter = self.irq_reload_value;
+            if !self.irq_repeat {
+                self.irq_enabled = false;
+            }
+        } else {
+            self.irq_counter -= 1;
+        }
+    }
+
+    /// Advances the disk drive by a single CPU cycle, transferring a byte
+    /// between the disk and the data registers every `BYTE_TRANSFER_CYCLES`.
+    ///
+    /// https://www.nesdev.org/wiki/FDS_disk_format#Disk_drive
+    fn tick_drive(&mut self) {
+        if self.insert_delay > 0 {
+            self.insert_delay -= 1;
+            if self.insert_delay == 0 {
+                self.disk_side = Some(self.next_disk_side);
+            }
+        }
+
+        if !self.is_disk_inserted() || !self.motor_on {
+            self.end_of_head = true;
+            self.scanning_disk = false;
+            return;
+        }
+
+        if self.reset_transfer && !self.scanning_disk {
+            return;
+        }
+
+        if self.end_of_head {
+            self.delay = SEEK_DELAY_CYCLES;
+            self.end_of_head = false;
+            self.disk_position = 0;
+            self.gap_ended = false;
+            return;
+        }
+
+        if self.delay > 0 {
+            self.delay -= 1;
+            return;
+        }
+
+        self.scanning_disk = true;
+
+        if self.read_mode {
+            let data = self.read_disk();
+            if !self.previous_crc_control {
+                self.update_crc(data);
+            }
+
+            let mut needs_irq = self.disk_irq_enabled;
+            if !self.disk_ready {
+                self.gap_ended = false;
+                self.crc_accumulator = 0;
+            } else if data != 0 && !self.gap_ended {
+                // The start mark ending a gap isn't passed on to the CPU.
+                self.gap_ended = true;
+                needs_irq = false;
+            }
+
+            if self.gap_ended {
+                self.transfer_complete = true;
+                self.read_data = data;
+                if needs_irq {
+                    self.disk_irq = true;
+                }
+            }
+        } else {
+            let mut data = 0;
+            if !self.crc_control {
+                self.transfer_complete = true;
+                data = self.write_data;
+                if self.disk_irq_enabled {
+                    self.disk_irq = true;
+                }
+            }
+
+            if !self.disk_ready {
+                data = 0;
+            }
+
+            if !self.crc_control {
+                self.update_crc(data);
+            } else {
+                if !self.previous_crc_control {
+                    // Flush the CRC before writing it out.
+                    self.update_crc(0);
+                    self.update_crc(0);
+                }
+
+                data = self.crc_accumulator as u8;
+                self.crc_accumulator >>= 8;
+            }
+
+            self.write_disk(data);
+            self.gap_ended = false;
+        }
+
+        self.previous_crc_control = self.crc_control;
+
+        self.disk_position += 1;
+        let side_size = self.get_disk_side_range().map_or(0, |range| range.len());
+        if self.disk_position >= side_size {
+            // The head is back at the start of the side once the motor stops,
+            // so the position never points past its end.
+            self.motor_on = false;
+            self.end_of_head = true;
+            self.disk_position = 0;
+        } else {
+            self.delay = BYTE_TRANSFER_CYCLES;
+        }
+    }
+
+    fn peek_register(&self, addr: u16) -> u8 {
+        match addr {
+            DISK_STATUS_ADDR => {
+                let mut data = 0;
+                if self.timer_irq {
+                    data |= DISK_STATUS_TIMER_IRQ_MASK;
+                }
+                if self.transfer_complete {
+                    data |= DISK_STATUS_TRANSFER_COMPLETE_MASK;
+                }
+                if self.end_of_head {
+                    data |= DISK_STATUS_END_OF_HEAD_MASK;
+                }
+                data
+            }
+            READ_DATA_ADDR => self.read_data,
+            DRIVE_STATUS_ADDR => {
+                // The upper bits are open bus, which holds the high byte of
+                // the address.
+                let mut data = 0x40;
+                if !self.is_disk_inserted() {
+                    data |= DRIVE_STATUS_DISK_MISSING_MASK | DRIVE_STATUS_WRITE_PROTECTED_MASK;
+                }
+                if !self.is_disk_inserted() || !self.scanning_disk {
+                    data |= DRIVE_STATUS_NOT_READY_MASK;
+                }
+                data
+            }
+            EXTERNAL_CONNECTOR_INPUT_ADDR => BATTERY_GOOD_MASK,
+            _ => 0, // ignore; unmapped
+        }
+    }
+
+    fn write_register(&mut self, addr: u16, data: u8) {
+        if !self.disk_registers_enabled && addr != MASTER_IO_ENABLE_ADDR {
+            return;
+        }
+
+        match addr {
+            IRQ_RELOAD_LOW_ADDR => {
+                self.irq_reload_value = (self.irq_reload_value & 0xFF00) | data as u16;
+            }
+            IRQ_RELOAD_HIGH_ADDR => {
+                self.irq_reload_value = (self.irq_reload_value & 0x00FF) | ((data as u16) << 8);
+            }
+            IRQ_CONTROL_ADDR => {
+                self.irq_repeat = data & IRQ_CONTROL_REPEAT_MASK != 0;
+                self.irq_enabled = data & IRQ_CONTROL_ENABLED_MASK != 0;
+                if self.irq_enabled {
+                    self.irq_counter = self.irq_reload_value;
+                } else {
+                    self.timer_irq = false;
+                }
+            }
+            MASTER_IO_ENABLE_ADDR => {
+                self.disk_registers_enabled = data & MASTER_IO_DISK_ENABLED_MASK != 0;
+                self.sound_registers_enabled = data & MASTER_IO_SOUND_ENABLED_MASK != 0;
+                if !self.disk_registers_enabled {
+                    self.irq_enabled = false;
+                    self.timer_irq = false;
+                    self.disk_irq = false;
+                }
+            }
+            WRITE_DATA_ADDR => {
+                self.write_data = data;
+                self.transfer_complete = false;
+                self.disk_irq = false;
+            }
+            FDS_CONTROL_ADDR => {
+                self.motor_on = data & FDS_CONTROL_MOTOR_ON_MASK != 0;
+                self.reset_transfer = data & FDS_CONTROL_RESET_TRANSFER_MASK != 0;
+                self.read_mode = data & FDS_CONTROL_READ_MODE_MASK != 0;
+                self.nametable_arrangement = match data & FDS_CONTROL_MIRRORING_MASK != 0 {
+                    true => NametableMirroring::Horizontal,
+                    false => NametableMirroring::Vertical,
+                };
+                self.crc_control = data & FDS_CONTROL_CRC_CONTROL_MASK != 0;
+                self.disk_ready = data & FDS_CONTROL_DISK_READY_MASK != 0;
+                self.disk_irq_enabled = data & FDS_CONTROL_DISK_IRQ_ENABLED_MASK != 0;
+                self.disk_irq = false;
+            }
+            EXTERNAL_CONNECTOR_OUTPUT_ADDR => self.external_connector = data,
+            _ => (), // ignore; unmapped
+        }
+    }
+}
+
+impl Mapper for FDS {
+    fn prg_read(&mut self, addr: u16) -> Option<u8> {
+        let data = self.prg_peek(addr);
+
+        match addr {
+            DISK_STATUS_ADDR => {
+                self.transfer_complete = false;
+                self.timer_irq = false;
+                self.disk_irq = false;
+            }
+            READ_DATA_ADDR => {
+                self.transfer_complete = false;
+                self.disk_irq = false;
+            }
+            _ => (),
+        }
+
+        data
+    }
+
+    fn prg_peek(&self, addr: u16) -> Option<u8> {
+        match addr {
+            DISK_STATUS_ADDR..=EXTERNAL_CONNECTOR_INPUT_ADDR if self.disk_registers_enabled => {
+                Some(self.peek_register(addr))
+            }
+            AUDIO_START_ADDR..AUDIO_END_ADDR if self.sound_registers_enabled => {
+                Some(self.audio.peek(addr))
+            }
+            PRG_RAM_START_ADDR..PRG_RAM_END_ADDR => self.prg_ram.read(addr),
+            BIOS_START_ADDR..=0xFFFF => Some(self.bios[(addr - BIOS_START_ADDR) as usize]),
+            _ => None, // ignore; unmapped
+        }
+    }
+
+    fn prg_write(&mut self, addr: u16, data: u8) {
+        match addr {
+            IRQ_RELOAD_LOW_ADDR..=EXTERNAL_CONNECTOR_OUTPUT_ADDR => self.write_register(addr, data),
+            AUDIO_START_ADDR..AUDIO_END_ADDR if self.sound_registers_enabled => {
+                self.audio.write(addr, data)
+            }
+            PRG_RAM_START_ADDR..PRG_RAM_END_ADDR => self.prg_ram.write(addr, data),
+            _ => (), // ignore; BIOS ROM or unmapped
+        }
+    }
+
+    fn chr_read(&self, addr: u16) -> u8 {
+        match addr {
+            CHR_MIN_ADDR..=CHR_MAX_ADDR => self.chr.read(addr as usize),
+            _ => 0, // ignore; unmapped
+        }
+    }
+
+    fn chr_write(&mut self, addr: u16, data: u8) {
+        if (CHR_MIN_ADDR..=CHR_MAX_ADDR).contains(&addr) {
+            self.chr.write(addr as usize, data);
+        }
+    }
+
+    fn get_nametable_arrangement(&self) -> NametableMirroring {
+        self.nametable_arrangement
+    }
+
+    fn get_prg_ram(&self) -> &PrgRam {
+        &self.prg_ram
+    }
+
+    fn get_prg_ram_mut(&mut self) -> &mut PrgRam {
+        &mut self.prg_ram
+    }
+
+    fn tick(&mut self) {
+        self.tick_timer();
+        self.tick_drive();
+        self.audio.tick();
+    }
+
+    fn get_irq(&self) -> bool {
+        self.timer_irq || self.disk_irq
+    }
+
+    /// The disk sides, including anything the game has written to them, in
+    /// the layout of an `.fds` disk image.
+    fn get_save_data(&self) -> Vec<u8> {
+        self.get_disk_side_data().flat_map(remove_gaps).collect()
+    }
+
+    fn set_save_data(&mut self, data: &[u8]) {
+        let saved_sides = data.chunks_exact(DISK_SIDE_SIZE).map(add_gaps);
+        let sides: Vec<Vec<u8>> = saved_sides
+            .chain(self.get_disk_side_data().map(<[u8]>::to_vec))
+            .take(self.disk_sides.len())
+            .collect();
+
+        (self.disk_data, self.disk_sides) = join_disk_sides(sides);
+        self.disk_position = 0;
+        self.end_of_head = true;
+    }
+
+    fn switch_disk_side(&mut self) {
+        let current = self.disk_side.unwrap_or(self.next_disk_side);
+        self.next_disk_side = (current + 1) % self.disk_sides.len();
+        self.disk_side = None;
+        self.insert_delay = DISK_SWITCH_DELAY_CYCLES;
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        self.prg_ram.save_state(state);
+        self.chr.save_state(state);
+        self.audio.save_state(state);
+
+        // Disk sides can change size when save data is loaded.
+        state.write_u32(self.disk_sides.len() as u32);
+        for range in self.disk_sides.iter() {
+            state.write_u32(range.len() as u32);
+        }
+        state.write_block(&self.disk_data);
+        state.write_bool(self.disk_side.is_some());
+        state.write_u32(self.disk_side.unwrap_or(0) as u32);
+        state.write_u32(self.next_disk_side as u32);
+        state.write_u32(self.insert_delay);
+
+        state.write_u16(self.irq_reload_value);
+        state.write_u16(self.irq_counter);
+        state.write_bool(self.irq_repeat);
+        state.write_bool(self.irq_enabled);
+        state.write_bool(self.timer_irq);
+        state.write_bool(self.disk_irq);
+
+        state.write_bool(self.disk_registers_enabled);
+        state.write_bool(self.sound_registers_enabled);
+
+        state.write_bool(self.motor_on);
+        state.write_bool(self.reset_transfer);
+        state.write_bool(self.read_mode);
+        state.write_bool(self.crc_control);
+        state.write_bool(self.disk_ready);
+        state.write_bool(self.disk_irq_enabled);
+        state.write_bool(matches!(
+            self.nametable_arrangement,
+            NametableMirroring::Horizontal
+        ));
+
+        state.write_u8(self.write_data);
+        state.write_u8(self.read_data);
+        state.write_bool(self.transfer_complete);
+        state.write_u8(self.external_connector);
+
+        state.write_u32(self.disk_position as u32);
+        state.write_u32(self.delay);
+        state.write_bool(self.scanning_disk);
+        state.write_bool(self.end_of_head);
+        state.write_bool(self.gap_ended);
+        state.write_bool(self.previous_crc_control);
+        state.write_u16(self.crc_accumulator);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.prg_ram.load_state(state)?;
+        self.chr.load_state(state)?;
+        self.audio.load_state(state)?;
+
+        let side_count = state.read_u32()? as usize;
+        if side_count != self.disk_sides.len() {
+            return Err(StateError::InvalidState {
+                message: format!(
+                    "expected {} disk sides, found {side_count}",
+                    self.disk_sides.len()
+                ),
+            }
+            .into());
+        }
+        let mut side_sizes = Vec::with_capacity(side_count);
+        for _ in 0..side_count {
+            side_sizes.push(state.read_u32()? as usize);
+        }
+        let disk_data = state.read_block_data()?;
+        if side_sizes.iter().sum::<usize>() != disk_data.len() {
+            return Err(StateError::InvalidState {
+                message: "disk side sizes don't match the disk data".to_string(),
+            }
+            .into());
+        }
+        let mut start = 0;
+        for (range, size) in self.disk_sides.iter_mut().zip(side_sizes) {
+            *range = start..start + size;
+            start += size;
+        }
+        self.disk_data = disk_data.to_vec();
+        let is_disk_inserted = state.read_bool()?;
+        let disk_side = state.read_u32()? as usize;
+        let next_disk_side = state.read_u32()? as usize;
+        if disk_side >= self.disk_sides.len() || next_disk_side >= self.disk_sides.len() {
+            return Err(StateError::InvalidState {
+                message: format!("disk side {disk_side} doesn't exist"),
+            }
+            .into());
+        }
+        self.disk_side = is_disk_inserted.then_some(disk_side);
+        self.next_disk_side = next_disk_side;
+        self.insert_delay = state.read_u32()?;
+
+        self.irq_reload_value = state.read_u16()?;
+        self.irq_counter = state.read_u16()?;
+        self.irq_repeat = state.read_bool()?;
+        self.irq_enabled = state.read_bool()?;
+        self.timer_irq = state.read_bool()?;
+        self.disk_irq = state.read_bool()?;
+
+        self.disk_registers_enabled = state.read_bool()?;
+        self.sound_registers_enabled = state.read_bool()?;
+
+        self.motor_on = state.read_bool()?;
+        self.reset_transfer = state.read_bool()?;
+        self.read_mode = state.read_bool()?;
+        self.crc_control = state.read_bool()?;
+        self.disk_ready = state.read_bool()?;
+        self.disk_irq_enabled = state.read_bool()?;
+        self.nametable_arrangement = match state.read_bool()? {
+            true => NametableMirroring::Horizontal,
+            false => NametableMirroring::Vertical,
+        };
+
+        self.write_data = state.read_u8()?;
+        self.read_data = state.read_u8()?;
+        self.transfer_complete = state.read_bool()?;
+        self.external_connector = state.read_u8()?;
+
+        self.disk_position = state.read_u32()? as usize;
+        if self.disk_position >= self.disk_sides[disk_side].len() {
+            return Err(StateError::InvalidState {
+                message: format!(
+                    "disk position {} is past the end of the side",
+                    self.disk_position
+                ),
+            }
+            .into());
+        }
+        self.delay = state.read_u32()?;
+        self.scanning_disk = state.read_bool()?;
+        self.end_of_head = state.read_bool()?;
+        self.gap_ended = state.read_bool()?;
+        self.previous_crc_control = state.read_bool()?;
+        self.crc_accumulator = state.read_u16()?;
+
+        Ok(())
+    }
+}
+
+/// Stores disk sides end to end, returning the data and the range of each
+/// side within it.
+fn join_disk_sides(sides: Vec<Vec<u8>>) -> (Vec<u8>, Vec<Range<usize>>) {
+    let mut disk_data = Vec::new();
+    let mut disk_sides = Vec::new();
+    for side in sides {
+        let start = disk_data.len();
+        disk_data.extend(side);
+        disk_sides.push(start..disk_data.len());
+    }
+
+    (disk_data, disk_sides)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        cartridge::{
+            fds::{BIOS_SIZE, DISK_INFO_TAG, DISK_SIDE_SIZE, FDS_TAG, adapter::FDS, parse},
+            mappers::Mapper,
+        },
+        state::{StateReader, StateWriter},
+    };
+
+    fn create_fds() -> FDS {
+        let mut data = FDS_TAG.to_vec();
+        data.resize(16, 0);
+        data[4] = 1;
+
+        let mut side = vec![0; DISK_SIDE_SIZE];
+        side[..DISK_INFO_TAG.len()].copy_from_slice(DISK_INFO_TAG);
+        data.extend(side);
+
+        let image = parse(&data).expect("disk image should parse");
+        FDS::new(image, [0; BIOS_SIZE])
+    }
+
+    /// Runs the drive until it has transferred a byte, then acknowledges it
+    /// and returns the byte read.
+    fn transfer(fds: &mut FDS) -> u8 {
+        for _ in 0..1_000_000 {
+            if fds.prg_peek(0x4030).unwrap_or(0) & 0b_0000_0010 != 0 {
+                return fds.prg_read(0x4031).unwrap_or(0);
+            }
+            fds.tick();
+        }
+        panic!("drive should transfer a byte");
+    }
+
+    #[test]
+    fn timer_irq_reloads_and_repeats() {
+        let mut fds = create_fds();
+        fds.prg_write(0x4023, 0b_0000_0001);
+        fds.prg_write(0x4020, 3);
+        fds.prg_write(0x4021, 0);
+        fds.prg_write(0x4022, 0b_0000_0011);
+
+        for _ in 0..2 {
+            for _ in 0..3 {
+                fds.tick();
+                assert!(!fds.get_irq());
+            }
+            fds.tick();
+            assert!(fds.get_irq());
+
+            // Reading $4030 acknowledges the IRQ.
+            assert_eq!(fds.prg_read(0x4030).unwrap_or(0) & 0b_0000_0001, 1);
+            assert!(!fds.get_irq());
+        }
+
+        // Without repeat, the timer stops after firing once.
+        fds.prg_write(0x4022, 0b_0000_0010);
+        for _ in 0..4 {
+            fds.tick();
+        }
+        assert!(fds.get_irq());
+        fds.prg_read(0x4030);
+        for _ in 0..100 {
+            fds.tick();
+        }
+        assert!(!fds.get_irq());
+    }
+
+    #[test]
+    fn drive_reads_first_block() {
+        let mut fds = create_fds();
+        fds.prg_write(0x4023, 0b_0000_0001);
+        assert_eq!(fds.prg_peek(0x4032), Some(0b_0100_0010));
+
+        // Motor on, read mode, disk ready. The start mark ending the gap comes
+        // first.
+        fds.prg_write(0x4025, 0b_0100_0101);
+        assert_eq!(transfer(&mut fds), 0x80);
+
+        let block: Vec<u8> = (0..DISK_INFO_TAG.len())
+            .map(|_| transfer(&mut fds))
+            .collect();
+        assert_eq!(block, DISK_INFO_TAG);
+        assert_eq!(fds.prg_peek(0x4032), Some(0b_0100_0000));
+    }
+
+    #[test]
+    fn disk_writes_are_saved() {
+        let mut fds = create_fds();
+        fds.prg_write(0x4023, 0b_0000_0001);
+
+        // Read past the start mark, the disk info block and its CRC.
+        fds.prg_write(0x4025, 0b_0100_0101);
+        for _ in 0..59 {
+            transfer(&mut fds);
+        }
+
+        // Write a short gap, then a file amount block.
+        fds.prg_write(0x4024, 0);
+        fds.prg_write(0x4025, 0b_0000_0001);
+        for _ in 0..8 {
+            transfer(&mut fds);
+        }
+        fds.prg_write(0x4025, 0b_0100_0001);
+        for data in [0x80, 2, 1] {
+            fds.prg_write(0x4024, data);
+            transfer(&mut fds);
+        }
+
+        // The CRC is written without signalling transfers.
+        fds.prg_write(0x4025, 0b_0101_0001);
+        for _ in 0..300 {
+            fds.tick();
+        }
+        fds.prg_write(0x4025, 0);
+
+        let save = fds.get_save_data();
+        assert_eq!(save.len(), DISK_SIDE_SIZE);
+        assert_eq!(&save[..DISK_INFO_TAG.len()], DISK_INFO_TAG);
+        assert_eq!(save[56..59], [2, 1, 0]);
+
+        let mut loaded = create_fds();
+        loaded.set_save_data(&save);
+        assert_eq!(loaded.get_save_data(), save);
+    }
+
+    #[test]
+    fn sound_registers_hold_wave_ram() {
+        let mut fds = create_fds();
+        assert_eq!(fds.prg_peek(0x4040), None);
+
+        fds.prg_write(0x4023, 0b_0000_0011);
+        fds.prg_write(0x4089, 0b_1000_0000);
+        fds.prg_write(0x4040, 0x3F);
+        fds.prg_write(0x407F, 0xC1);
+        fds.prg_write(0x4089, 0);
+        fds.prg_write(0x4041, 0x3F);
+        fds.prg_write(0x4080, 0b_1001_0100);
+
+        // The upper two bits are open bus.
+        assert_eq!(fds.prg_peek(0x4040), Some(0x7F));
+        assert_eq!(fds.prg_peek(0x4041), Some(0x40));
+        assert_eq!(fds.prg_peek(0x407F), Some(0x41));
+        assert_eq!(fds.prg_peek(0x4090), Some(0x54));
+        assert_eq!(fds.get_audio_output(), 0);
+
+        let mut state = StateWriter::new(0);
+        fds.save_state(&mut state);
+        let data = state.into_data();
+
+        let mut loaded = create_fds();
+        loaded.prg_write(0x4023, 0b_0000_0011);
+        let mut state = StateReader::new(&data, 0).expect("state header should be valid");
+        loaded.load_state(&mut state).expect("state should load");
+        assert_eq!(loaded.prg_peek(0x4040), Some(0x7F));
+        assert_eq!(loaded.prg_peek(0x407F), Some(0x41));
+        assert_eq!(loaded.prg_peek(0x4090), Some(0x54));
+
+        fds.prg_write(0x4023, 0b_0000_0001);
+        assert_eq!(fds.prg_peek(0x4040), None);
+    }
+}
@@ -0,0 +1,399 @@
+// https://www.nesdev.org/wiki/FDS_audio
+
+use crate::emu::{
+    error::Error,
+    state::{StateReader, StateWriter},
+};
+
+pub const AUDIO_START_ADDR: u16 = 0x4040;
+pub const AUDIO_END_ADDR: u16 = 0x4098;
+
+const WAVE_TABLE_START_ADDR: u16 = 0x4040;
+const WAVE_TABLE_END_ADDR: u16 = 0x4080;
+const WAVE_TABLE_SIZE: usize = 64;
+const MOD_TABLE_SIZE: usize = 64;
+
+const VOLUME_ENVELOPE_ADDR: u16 = 0x4080;
+const FREQUENCY_LOW_ADDR: u16 = 0x4082;
+const FREQUENCY_HIGH_ADDR: u16 = 0x4083;
+const MOD_ENVELOPE_ADDR: u16 = 0x4084;
+const MOD_COUNTER_ADDR: u16 = 0x4085;
+const MOD_FREQUENCY_LOW_ADDR: u16 = 0x4086;
+const MOD_FREQUENCY_HIGH_ADDR: u16 = 0x4087;
+const MOD_TABLE_WRITE_ADDR: u16 = 0x4088;
+const WAVE_WRITE_ADDR: u16 = 0x4089;
+const ENVELOPE_SPEED_ADDR: u16 = 0x408A;
+const VOLUME_GAIN_ADDR: u16 = 0x4090;
+const MOD_GAIN_ADDR: u16 = 0x4092;
+
+const MAX_GAIN: u8 = 32;
+/// Master volume multipliers, where the output is divided by 1152 to give a
+/// 6-bit level.
+const MASTER_VOLUMES: [u32; 4] = [36, 24, 17, 14];
+const MASTER_VOLUME_DIVISOR: u32 = 1152;
+
+/// A modulation table entry that resets the mod counter.
+const MOD_RESET: i8 = i8::MIN;
+const MOD_ADJUSTMENTS: [i8; 8] = [0, 1, 2, 4, MOD_RESET, -4, -2, -1];
+
+/// The envelope and frequency registers shared by the volume and modulation
+/// units.
+#[derive(Clone, Default)]
+struct Envelope {
+    speed: u8,
+    gain: u8,
+    increase: bool,
+    disabled: bool,
+    frequency: u16,
+    timer: u32,
+}
+
+impl Envelope {
+    fn write_control(&mut self, data: u8, master_speed: u8) {
+        self.speed = data & 0b_0011_1111;
+        self.increase = data & 0b_0100_0000 != 0;
+        self.disabled = data & 0b_1000_0000 != 0;
+        self.reset_timer(master_speed);
+
+        if self.disabled {
+            self.gain = self.speed;
+        }
+    }
+
+    fn write_frequency_low(&mut self, data: u8) {
+        self.frequency = (self.frequency & 0x0F00) | data as u16;
+    }
+
+    fn write_frequency_high(&mut self, data: u8) {
+        self.frequency = (self.frequency & 0x00FF) | (((data & 0x0F) as u16) << 8);
+    }
+
+    fn reset_timer(&mut self, master_speed: u8) {
+        self.timer = 8 * (self.speed as u32 + 1) * master_speed as u32;
+    }
+
+    /// Clocks the envelope, returning `true` if the gain was updated.
+    fn tick(&mut self, master_speed: u8) -> bool {
+        if self.disabled || master_speed == 0 {
+            return false;
+        }
+
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer != 0 {
+            return false;
+        }
+
+        self.reset_timer(master_speed);
+        if self.increase && self.gain < MAX_GAIN {
+            self.gain += 1;
+        } else if !self.increase && self.gain > 0 {
+            self.gain -= 1;
+        }
+
+        true
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.speed);
+        state.write_u8(self.gain);
+        state.write_bool(self.increase);
+        state.write_bool(self.disabled);
+        state.write_u16(self.frequency);
+        state.write_u32(self.timer);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.speed = state.read_u8()?;
+        self.gain = state.read_u8()?;
+        self.increase = state.read_bool()?;
+        self.disabled = state.read_bool()?;
+        self.frequency = state.read_u16()?;
+        self.timer = state.read_u32()?;
+        Ok(())
+    }
+}
+
+/// The FDS's expansion audio: a single wavetable channel with a volume
+/// envelope, and a modulation unit that bends its pitch.
+#[derive(Clone)]
+pub struct Audio {
+    wave_table: [u8; WAVE_TABLE_SIZE],
+    wave_write_enabled: bool,
+    wave_position: u8,
+    wave_accumulator: u16,
+    halt_waveform: bool,
+    envelopes_disabled: bool,
+    master_volume: u8,
+    master_speed: u8,
+    volume: Envelope,
+
+    mod_envelope: Envelope,
+    mod_table: [u8; MOD_TABLE_SIZE],
+    mod_position: u8,
+    mod_accumulator: u16,
+    mod_counter: i8,
+    mod_disabled: bool,
+    /// The pitch adjustment from the modulation unit.
+    mod_output: i32,
+
+    /// The current 6-bit output level.
+    output: u8,
+}
+
+impl Audio {
+    pub fn new() -> Self {
+        Self {
+            wave_table: [0; WAVE_TABLE_SIZE],
+            wave_write_enabled: false,
+            wave_position: 0,
+            wave_accumulator: 0,
+            halt_waveform: false,
+            envelopes_disabled: false,
+            master_volume: 0,
+            master_speed: 0xFF,
+            volume: Envelope::default(),
+            mod_envelope: Envelope::default(),
+            mod_table: [0; MOD_TABLE_SIZE],
+            mod_position: 0,
+            mod_accumulator: 0,
+            mod_counter: 0,
+            mod_disabled: true,
+            mod_output: 0,
+            output: 0,
+        }
+    }
+
+    /// Returns the current output level, from 0 to 63.
+    pub fn get_output(&self) -> u8 {
+        self.output
+    }
+
+    /// Advances the state of the audio unit ahead by a single CPU cycle.
+    pub fn tick(&mut self) {
+        let frequency = self.volume.frequency;
+
+        if !self.halt_waveform && !self.envelopes_disabled {
+            self.volume.tick(self.master_speed);
+            if self.mod_envelope.tick(self.master_speed) {
+                self.update_mod_output(frequency);
+            }
+        }
+
+        if self.tick_modulator() {
+            self.update_mod_output(frequency);
+        }
+
+        if self.halt_waveform {
+            self.wave_position = 0;
+            self.update_output();
+            return;
+        }
+
+        self.update_output();
+
+        let pitch = frequency as i32 + self.mod_output;
+        if 0 < pitch && !self.wave_write_enabled {
+            let pitch = pitch.min(u16::MAX as i32) as u16;
+            let (accumulator, overflow) = self.wave_accumulator.overflowing_add(pitch);
+            self.wave_accumulator = accumulator;
+            if overflow {
+                self.wave_position = (self.wave_position + 1) % WAVE_TABLE_SIZE as u8;
+            }
+        }
+    }
+
+    /// Clocks the modulation unit, returning `true` if the mod counter was
+    /// updated.
+    fn tick_modulator(&mut self) -> bool {
+        let frequency = self.mod_envelope.frequency;
+        if self.mod_disabled || frequency == 0 {
+            return false;
+        }
+
+        let (accumulator, overflow) = self.mod_accumulator.overflowing_add(frequency);
+        self.mod_accumulator = accumulator;
+        if !overflow {
+            return false;
+        }
+
+        let adjustment = MOD_ADJUSTMENTS[self.mod_table[self.mod_position as usize] as usize];
+        let counter = match adjustment {
+            MOD_RESET => 0,
+            adjustment => self.mod_counter as i32 + adjustment as i32,
+        };
+        self.set_mod_counter(counter);
+        self.mod_position = (self.mod_position + 1) % MOD_TABLE_SIZE as u8;
+
+        true
+    }
+
+    /// Sets the 7-bit signed mod counter.
+    fn set_mod_counter(&mut self, value: i32) {
+        let value = value & 0x7F;
+        self.mod_counter = if 64 <= value { value - 128 } else { value } as i8;
+    }
+
+    /// Calculates the pitch adjustment from the mod counter and gain.
+    ///
+    /// https://www.nesdev.org/wiki/FDS_audio#Frequency_calculation
+    fn update_mod_output(&mut self, pitch: u16) {
+        let counter = self.mod_counter as i32;
+
+        // Multiply the counter by the gain, dropping the lowest 4 bits but
+        // rounding in an unusual way.
+        let mut temp = counter * self.mod_envelope.gain as i32;
+        let remainder = temp & 0x0F;
+        temp >>= 4;
+        if 0 < remainder && (temp & 0x80) == 0 {
+            temp += if counter < 0 { -1 } else { 2 };
+        }
+
+        // Wrap if a certain range is exceeded.
+        if 192 <= temp {
+            temp -= 256;
+        } else if temp < -64 {
+            temp += 256;
+        }
+
+        // Multiply by the pitch, then round to the nearest while dropping 6
+        // bits.
+        temp *= pitch as i32;
+        let remainder = temp & 0x3F;
+        temp >>= 6;
+        if 32 <= remainder {
+            temp += 1;
+        }
+
+        self.mod_output = temp;
+    }
+
+    fn update_output(&mut self) {
+        let gain = self.volume.gain.min(MAX_GAIN) as u32;
+        let level = gain * MASTER_VOLUMES[self.master_volume as usize];
+        let sample = self.wave_table[self.wave_position as usize] as u32;
+
+        self.output = (sample * level / MASTER_VOLUME_DIVISOR) as u8;
+    }
+
+    /// Returns a byte from an audio register without side effects. The upper
+    /// two bits are open bus, which holds the high byte of the address.
+    pub fn peek(&self, addr: u16) -> u8 {
+        let open_bus = (addr >> 8) as u8;
+        match addr {
+            WAVE_TABLE_START_ADDR..WAVE_TABLE_END_ADDR => {
+                let index = (addr - WAVE_TABLE_START_ADDR) as usize;
+                (open_bus & 0b_1100_0000) | self.wave_table[index]
+            }
+            VOLUME_GAIN_ADDR => (open_bus & 0b_1100_0000) | self.volume.gain,
+            MOD_GAIN_ADDR => (open_bus & 0b_1100_0000) | self.mod_envelope.gain,
+            _ => open_bus, // ignore; write-only
+        }
+    }
+
+    /// Writes a byte to an audio register.
+    pub fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            // The wave table can only be written while writes are enabled.
+            WAVE_TABLE_START_ADDR..WAVE_TABLE_END_ADDR if self.wave_write_enabled => {
+                let index = (addr - WAVE_TABLE_START_ADDR) as usize;
+                self.wave_table[index] = data & 0b_0011_1111;
+            }
+            VOLUME_ENVELOPE_ADDR => self.volume.write_control(data, self.master_speed),
+            FREQUENCY_LOW_ADDR => self.volume.write_frequency_low(data),
+            FREQUENCY_HIGH_ADDR => {
+                self.volume.write_frequency_high(data);
+                self.envelopes_disabled = data & 0b_0100_0000 != 0;
+                self.halt_waveform = data & 0b_1000_0000 != 0;
+                if self.halt_waveform {
+                    self.wave_accumulator = 0;
+                }
+                if self.envelopes_disabled {
+                    self.volume.reset_timer(self.master_speed);
+                    self.mod_envelope.reset_timer(self.master_speed);
+                }
+            }
+            MOD_ENVELOPE_ADDR => self.mod_envelope.write_control(data, self.master_speed),
+            MOD_COUNTER_ADDR => self.set_mod_counter(data as i32),
+            MOD_FREQUENCY_LOW_ADDR => self.mod_envelope.write_frequency_low(data),
+            MOD_FREQUENCY_HIGH_ADDR => {
+                self.mod_envelope.write_frequency_high(data);
+                self.mod_disabled = data & 0b_1000_0000 != 0;
+                if self.mod_disabled {
+                    self.mod_accumulator = 0;
+                }
+            }
+            // The mod table can only be written while the mod unit is
+            // disabled. Each write fills two consecutive entries.
+            MOD_TABLE_WRITE_ADDR if self.mod_disabled => {
+                let entry = data & 0b_0000_0111;
+                let position = self.mod_position as usize;
+                self.mod_table[position] = entry;
+                self.mod_table[(position + 1) % MOD_TABLE_SIZE] = entry;
+                self.mod_position = ((position + 2) % MOD_TABLE_SIZE) as u8;
+            }
+            WAVE_WRITE_ADDR => {
+                self.master_volume = data & 0b_0000_0011;
+                self.wave_write_enabled = data & 0b_1000_0000 != 0;
+            }
+            ENVELOPE_SPEED_ADDR => self.master_speed = data,
+            _ => (), // ignore; read-only, unused, or write-protected
+        }
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.wave_table);
+        state.write_bool(self.wave_write_enabled);
+        state.write_u8(self.wave_position);
+        state.write_u16(self.wave_accumulator);
+        state.write_bool(self.halt_waveform);
+        state.write_bool(self.envelopes_disabled);
+        state.write_u8(self.master_volume);
+        state.write_u8(self.master_speed);
+        self.volume.save_state(state);
+
+        self.mod_envelope.save_state(state);
+        state.write_bytes(&self.mod_table);
+        state.write_u8(self.mod_position);
+        state.write_u16(self.mod_accumulator);
+        state.write_u8(self.mod_counter as u8);
+        state.write_bool(self.mod_disabled);
+        state.write_u32(self.mod_output as u32);
+
+        state.write_u8(self.output);
+    }
+
+    /// Restores the state written by [`Audio::save_state`]. Values used as
+    /// table indices are masked the same way as when they're written, so a
+    /// corrupt state can't index out of bounds.
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        state.read_bytes(&mut self.wave_table)?;
+        self.wave_write_enabled = state.read_bool()?;
+        self.wave_position = state.read_u8()? % WAVE_TABLE_SIZE as u8;
+        self.wave_accumulator = state.read_u16()?;
+        self.halt_waveform = state.read_bool()?;
+        self.envelopes_disabled = state.read_bool()?;
+        self.master_volume = state.read_u8()? & 0b_0000_0011;
+        self.master_speed = state.read_u8()?;
+        self.volume.load_state(state)?;
+
+        self.mod_envelope.load_state(state)?;
+        state.read_bytes(&mut self.mod_table)?;
+        for entry in self.mod_table.iter_mut() {
+            *entry &= 0b_0000_0111;
+        }
+        self.mod_position = state.read_u8()? % MOD_TABLE_SIZE as u8;
+        self.mod_accumulator = state.read_u16()?;
+        self.mod_counter = state.read_u8()? as i8;
+        self.mod_disabled = state.read_bool()?;
+        self.mod_output = state.read_u32()? as i32;
+
+        self.output = state.read_u8()?;
+        Ok(())
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
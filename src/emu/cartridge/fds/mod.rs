@@ -0,0 +1,264 @@
+// https://www.nesdev.org/wiki/FDS_file_format
+// https://www.nesdev.org/wiki/FDS_disk_format
+
+use crate::emu::{
+    cartridge::Header,
+    error::{CartridgeError, Error},
+};
+
+pub mod adapter;
+pub mod audio;
+
+/// The magic bytes at the start of a disk image with an fwNES header.
+pub const FDS_TAG: [u8; 4] = *b"FDS\x1A";
+const FDS_HEADER_SIZE: usize = 16;
+const FDS_HEADER_SIDE_COUNT_INDEX: usize = 4;
+
+/// The first block on every disk side, which also starts disk images without
+/// an fwNES header.
+pub const DISK_INFO_TAG: &[u8] = b"\x01*NINTENDO-HVC*";
+
+/// The size of a disk side in a disk image, which excludes the gaps and CRCs
+/// between blocks.
+pub const DISK_SIDE_SIZE: usize = 65500;
+
+/// The size of the BIOS ROM, `disksys.rom`.
+pub const BIOS_SIZE: usize = 0x2000;
+/// The default file name of the BIOS ROM, which is looked for next to the disk
+/// image.
+pub const BIOS_FILE_NAME: &str = "disksys.rom";
+
+/// The NES 2.0 mapper number reserved for the FDS.
+pub const FDS_MAPPER_INDEX: u16 = 20;
+const PRG_RAM_SIZE: usize = 0x8000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+const DISK_INFO_BLOCK_TYPE: u8 = 1;
+const FILE_AMOUNT_BLOCK_TYPE: u8 = 2;
+const FILE_HEADER_BLOCK_TYPE: u8 = 3;
+const FILE_DATA_BLOCK_TYPE: u8 = 4;
+
+const DISK_INFO_BLOCK_SIZE: usize = 56;
+const FILE_AMOUNT_BLOCK_SIZE: usize = 2;
+const FILE_HEADER_BLOCK_SIZE: usize = 16;
+/// The offset of the file size within a file header block.
+const FILE_HEADER_FILE_SIZE_INDEX: usize = 13;
+
+/// The gap before the first block on a side, in bytes (28300 bits).
+const LEADING_GAP_SIZE: usize = 28300 / 8;
+/// The gap after each block, in bytes (976 bits).
+const BLOCK_GAP_SIZE: usize = 976 / 8;
+/// The mark that ends each gap and starts a block.
+const BLOCK_START_MARK: u8 = 0x80;
+/// Disk images don't store block CRCs; the drive never checks them when
+/// reading, so any value will do.
+const FAKE_CRC: [u8; 2] = [0x4D, 0x62];
+
+/// The disk sides of a Famicom Disk System disk image.
+pub struct DiskImage {
+    /// Each side as it appears on disk, with gaps and CRCs between blocks.
+    pub sides: Vec<Vec<u8>>,
+    pub header: Header,
+}
+
+/// Returns `true` if the data looks like an FDS disk image, with or without an
+/// fwNES header.
+pub fn is_fds(data: &[u8]) -> bool {
+    data.starts_with(&FDS_TAG) || data.starts_with(DISK_INFO_TAG)
+}
+
+/// Parses the contents of an `.fds` file.
+pub fn parse(data: &[u8]) -> Result<DiskImage, Error> {
+    let (data, side_count) = if data.starts_with(&FDS_TAG) {
+        if data.len() < FDS_HEADER_SIZE {
+            return Err(CartridgeError::MissingHeader.into());
+        }
+
+        let side_count = data[FDS_HEADER_SIDE_COUNT_INDEX] as usize;
+        (&data[FDS_HEADER_SIZE..], side_count)
+    } else {
+        (data, data.len() / DISK_SIDE_SIZE)
+    };
+
+    if side_count == 0 {
+        return Err(CartridgeError::InvalidHeader {
+            message: "disk image has no disk sides".to_string(),
+        }
+        .into());
+    }
+
+    let expected = side_count * DISK_SIDE_SIZE;
+    if data.len() < expected {
+        return Err(CartridgeError::TruncatedDisk {
+            expected,
+            actual: data.len(),
+        }
+        .into());
+    }
+
+    let sides = data[..expected]
+        .chunks_exact(DISK_SIDE_SIZE)
+        .map(add_gaps)
+        .collect();
+
+    let header = Header {
+        mapper_index: FDS_MAPPER_INDEX,
+        prg_ram_size: PRG_RAM_SIZE,
+        chr_ram_size: CHR_RAM_SIZE,
+        // Disk writes are persisted like battery-backed RAM.
+        has_battery: true,
+        ..Header::default()
+    };
+
+    Ok(DiskImage { sides, header })
+}
+
+/// Rebuilds a disk side as the drive sees it, inserting the gaps, start marks
+/// and CRCs that disk images leave out.
+fn add_gaps(side: &[u8]) -> Vec<u8> {
+    let mut disk = vec![0; LEADING_GAP_SIZE];
+
+    let mut index = 0;
+    while index < side.len() {
+        // The rest of the side is unused.
+        let Some(size) = get_block_size(side, index) else {
+            break;
+        };
+
+        let end = (index + size).min(side.len());
+        disk.push(BLOCK_START_MARK);
+        disk.extend_from_slice(&side[index..end]);
+        disk.extend_from_slice(&FAKE_CRC);
+        disk.extend(std::iter::repeat_n(0, BLOCK_GAP_SIZE));
+
+        index = end;
+    }
+
+    // Pad the side out so the drive takes as long to reach the end of it as it
+    // would on a real disk.
+    if disk.len() < DISK_SIDE_SIZE {
+        disk.resize(DISK_SIDE_SIZE, 0);
+    }
+
+    disk
+}
+
+/// Turns a disk side as the drive sees it back into the layout of a disk
+/// image, dropping the gaps, start marks and CRCs. This undoes `add_gaps`, and
+/// also picks up blocks the BIOS has written.
+fn remove_gaps(disk: &[u8]) -> Vec<u8> {
+    let mut side = Vec::with_capacity(DISK_SIDE_SIZE);
+
+    let mut index = 0;
+    while side.len() < DISK_SIDE_SIZE {
+        let Some(gap) = disk[index..].iter().position(|&byte| byte != 0) else {
+            break;
+        };
+        index += gap;
+        if disk[index] != BLOCK_START_MARK {
+            break;
+        }
+        index += 1;
+
+        // The block size of file data is read from the file header that was
+        // just copied, so the block type has to be copied first.
+        let Some(&block_type) = disk.get(index) else {
+            break;
+        };
+        side.push(block_type);
+        let Some(size) = get_block_size(&side, side.len() - 1) else {
+            side.pop();
+            break;
+        };
+
+        let end = (index + size).min(disk.len());
+        side.extend_from_slice(&disk[index + 1..end]);
+        index = (end + FAKE_CRC.len()).min(disk.len());
+    }
+
+    side.resize(DISK_SIDE_SIZE, 0);
+    side
+}
+
+/// Returns the size of the block starting at `index` of a disk side in the
+/// layout of a disk image, or `None` if there's no block there.
+fn get_block_size(side: &[u8], index: usize) -> Option<usize> {
+    match side[index] {
+        DISK_INFO_BLOCK_TYPE => Some(DISK_INFO_BLOCK_SIZE),
+        FILE_AMOUNT_BLOCK_TYPE => Some(FILE_AMOUNT_BLOCK_SIZE),
+        FILE_HEADER_BLOCK_TYPE => Some(FILE_HEADER_BLOCK_SIZE),
+        FILE_DATA_BLOCK_TYPE if index >= FILE_HEADER_BLOCK_SIZE => {
+            // The file size is stored in the preceding file header.
+            let file_header = index - FILE_HEADER_BLOCK_SIZE;
+            let size_index = file_header + FILE_HEADER_FILE_SIZE_INDEX;
+            Some(1 + u16::from_le_bytes([side[size_index], side[size_index + 1]]) as usize)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        cartridge::fds::{
+            DISK_INFO_TAG, DISK_SIDE_SIZE, FDS_TAG, LEADING_GAP_SIZE, parse, remove_gaps,
+        },
+        error::{CartridgeError, Error},
+    };
+
+    #[test]
+    fn disk_blocks_are_gapped() {
+        let mut side = vec![0; DISK_SIDE_SIZE];
+        side[..DISK_INFO_TAG.len()].copy_from_slice(DISK_INFO_TAG);
+        side[56] = 2; // file amount block
+        side[57] = 1;
+        side[58] = 3; // file header block
+        side[58 + 13] = 4; // file size
+        side[74] = 4; // file data block
+        side[75..79].copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
+
+        let mut data = FDS_TAG.to_vec();
+        data.resize(16, 0);
+        data[4] = 1;
+        data.extend(side);
+
+        let image = parse(&data).expect("disk image should parse");
+        assert_eq!(image.sides.len(), 1);
+
+        let disk = &image.sides[0];
+        assert_eq!(disk.len(), DISK_SIDE_SIZE);
+        assert!(disk[..LEADING_GAP_SIZE].iter().all(|&byte| byte == 0));
+        assert_eq!(disk[LEADING_GAP_SIZE], 0x80);
+        assert_eq!(
+            &disk[LEADING_GAP_SIZE + 1..][..DISK_INFO_TAG.len()],
+            DISK_INFO_TAG
+        );
+
+        let file_data = disk
+            .windows(5)
+            .position(|window| window == [4, 0xAA, 0xBB, 0xCC, 0xDD])
+            .expect("file data should be on disk");
+        assert_eq!(disk[file_data - 1], 0x80);
+
+        assert_eq!(remove_gaps(disk), &data[16..]);
+    }
+
+    #[test]
+    fn truncated_disk() {
+        let mut data = FDS_TAG.to_vec();
+        data.resize(16, 0);
+        data[4] = 2;
+        data.extend(vec![0; DISK_SIDE_SIZE]);
+
+        let Err(Error::CartridgeError { err }) = parse(&data) else {
+            panic!("truncated disk image should fail to parse");
+        };
+        assert!(matches!(
+            err,
+            CartridgeError::TruncatedDisk {
+                expected: 131000,
+                actual: 65500
+            }
+        ));
+    }
+}
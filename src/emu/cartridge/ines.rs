@@ -1,18 +1,19 @@
-use std::{cell::RefCell, rc::Rc};
-
 use crate::emu::{
     cartridge::{
-        Cartridge,
+        ConsoleType, Header, Timing,
         mappers::{Mapper, nrom::NROM},
     },
-    error::{CartridgeError, Error, FileError},
+    error::{CartridgeError, Error},
 };
 
+// https://www.nesdev.org/wiki/INES
+// https://www.nesdev.org/wiki/NES_2.0
+
 const INES_HEADER_SIZE: usize = 16;
 const TRAINER_SIZE: usize = 512;
 
 const INES_TAG_INDEX: usize = 0;
-const INES_TAG: [u8; 4] = [0x4E, 0x45, 0x53, 0x1A]; // ASCII "NES" followed by MS-DOS EoF
+pub const INES_TAG: [u8; 4] = [0x4E, 0x45, 0x53, 0x1A]; // ASCII "NES" followed by MS-DOS EoF
 
 const PRG_ROM_SIZE_INDEX: usize = 4;
 const INES_PRG_ROM_SIZE_UNITS: usize = 16384;
@@ -22,90 +23,324 @@ const INES_CHR_ROM_SIZE_UNITS: usize = 8192;
 
 const FLAGS_6_INDEX: usize = 6;
 const FLAGS_6_NAMETABLE_ARRANGEMENT_MASK: u8 = 0b_0000_0001;
+const FLAGS_6_BATTERY_MASK: u8 = 0b_0000_0010;
 const FLAGS_6_TRAINER_MASK: u8 = 0b_0000_0100;
 const FLAGS_6_ALTERNATIVE_NAMETABLE_ARRANGEMENT_MASK: u8 = 0b_0000_1000;
 const FLAGS_6_MAPPER_LOWER_NIBBLE_MASK: u8 = 0b_1111_0000;
 
 const FLAGS_7_INDEX: usize = 7;
+const FLAGS_7_CONSOLE_TYPE_MASK: u8 = 0b_0000_0011;
 const FLAGS_7_INES2_FORMAT_MASK: u8 = 0b_0000_1100;
+const FLAGS_7_INES2_FORMAT_ID: u8 = 0b_0000_1000;
 const FLAGS_7_MAPPER_UPPER_NIBBLE_MASK: u8 = 0b_1111_0000;
 
+const PRG_RAM_SIZE_INDEX: usize = 8;
+const INES_PRG_RAM_SIZE_UNITS: usize = 8192;
+
+const FLAGS_9_INDEX: usize = 9;
+const FLAGS_9_TV_SYSTEM_MASK: u8 = 0b_0000_0001;
+
+/// The size of the CHR RAM allocated for cartridges without CHR ROM.
+const DEFAULT_CHR_RAM_SIZE: usize = 8192;
+
+// NES 2.0
+
+const MAPPER_MSB_SUBMAPPER_INDEX: usize = 8;
+const MAPPER_MSB_MASK: u8 = 0b_0000_1111;
+const SUBMAPPER_MASK: u8 = 0b_1111_0000;
+
+const ROM_SIZE_MSB_INDEX: usize = 9;
+const PRG_ROM_SIZE_MSB_MASK: u8 = 0b_0000_1111;
+const CHR_ROM_SIZE_MSB_MASK: u8 = 0b_1111_0000;
+const ROM_SIZE_EXPONENT_MSB: u16 = 0x0F;
+
+const PRG_RAM_SHIFT_INDEX: usize = 10;
+const CHR_RAM_SHIFT_INDEX: usize = 11;
+const VOLATILE_SHIFT_MASK: u8 = 0b_0000_1111;
+const NON_VOLATILE_SHIFT_MASK: u8 = 0b_1111_0000;
+const RAM_SHIFT_BASE: usize = 64;
+
+const TIMING_INDEX: usize = 12;
+const TIMING_MASK: u8 = 0b_0000_0011;
+
+const SYSTEM_TYPE_INDEX: usize = 13;
+const VS_PPU_TYPE_MASK: u8 = 0b_0000_1111;
+const VS_HARDWARE_TYPE_MASK: u8 = 0b_1111_0000;
+const EXTENDED_CONSOLE_TYPE_MASK: u8 = 0b_0000_1111;
+
+const MISC_ROMS_INDEX: usize = 14;
+const MISC_ROMS_MASK: u8 = 0b_0000_0011;
+
+const DEFAULT_EXPANSION_DEVICE_INDEX: usize = 15;
+const DEFAULT_EXPANSION_DEVICE_MASK: u8 = 0b_0011_1111;
+
+const JUNK_HEADER_START_INDEX: usize = 7;
+const JUNK_HEADER_CHECK_INDEX: usize = 12;
+const FLAGS_7_ARCHAIC_FORMAT_ID: u8 = 0b_0000_0100;
+
 pub struct INes {
     pub prg_data: Vec<u8>,
     pub chr_data: Vec<u8>,
-    pub mapper_index: u8,
-    pub nametable_arrangement: bool,
-    pub alternative_nametable_arrangement: bool,
+    pub header: Header,
+    /// Problems with the file that were worked around while parsing it.
+    pub warnings: Vec<CartridgeError>,
 }
 
-pub fn read_cartridge(path_to_ines_file: &str) -> Result<Cartridge, Error> {
-    let data = read_data(path_to_ines_file)?;
+/// Parses the contents of an iNES or NES 2.0 file.
+///
+/// Every section that the header declares is checked against the length of
+/// the data, so malformed files produce an error rather than a panic. Headers
+/// with junk in bytes 7–15 (such as "DiskDude!") are repaired, and the repair
+/// is reported in the returned warnings.
+pub fn parse(data: &[u8]) -> Result<INes, Error> {
+    if data.len() < INES_HEADER_SIZE {
+        return Err(CartridgeError::MissingHeader.into());
+    }
+
+    if data[INES_TAG_INDEX..(INES_TAG_INDEX + INES_TAG.len())] != INES_TAG {
+        return Err(CartridgeError::InvalidHeader {
+            message: "file is not formatted as iNES".to_string(),
+        }
+        .into());
+    }
 
-    let prg_rom_size = data[PRG_ROM_SIZE_INDEX] as usize * INES_PRG_ROM_SIZE_UNITS;
-    let chr_rom_size = data[CHR_ROM_SIZE_INDEX] as usize * INES_CHR_ROM_SIZE_UNITS;
+    let mut warnings = Vec::new();
+    let mut header_data = [0; INES_HEADER_SIZE];
+    header_data.copy_from_slice(&data[..INES_HEADER_SIZE]);
+
+    if let Some(junk) = get_junk_header_bytes(&header_data) {
+        header_data[JUNK_HEADER_START_INDEX..].fill(0);
+        warnings.push(CartridgeError::JunkHeader { junk });
+    }
 
-    let trainer_exists = (data[FLAGS_6_INDEX] & FLAGS_6_TRAINER_MASK) != 0;
-    let prg_rom_start = INES_HEADER_SIZE + if trainer_exists { TRAINER_SIZE } else { 0 };
-    let prg_rom_end = prg_rom_start + prg_rom_size;
+    let header = read_header(&header_data);
+
+    let trainer_size = if header.has_trainer { TRAINER_SIZE } else { 0 };
+    let trainer_end = INES_HEADER_SIZE + trainer_size;
+    if data.len() < trainer_end {
+        return Err(CartridgeError::TruncatedTrainer {
+            expected: trainer_size,
+            actual: data.len() - INES_HEADER_SIZE,
+        }
+        .into());
+    }
+
+    let prg_rom_start = trainer_end;
+    let prg_rom_end = prg_rom_start.saturating_add(header.prg_rom_size);
+    if data.len() < prg_rom_end {
+        return Err(CartridgeError::TruncatedPrgRom {
+            expected: header.prg_rom_size,
+            actual: data.len() - prg_rom_start,
+        }
+        .into());
+    }
     let prg_data = data[prg_rom_start..prg_rom_end].to_vec();
 
     let chr_rom_start = prg_rom_end;
-    let chr_rom_end = chr_rom_start + chr_rom_size;
+    let chr_rom_end = chr_rom_start.saturating_add(header.chr_rom_size);
+    if data.len() < chr_rom_end {
+        return Err(CartridgeError::TruncatedChrRom {
+            expected: header.chr_rom_size,
+            actual: data.len() - chr_rom_start,
+        }
+        .into());
+    }
     let chr_data = data[chr_rom_start..chr_rom_end].to_vec();
 
-    let mapper_index = get_mapper_index(&data);
-    let nametable_arrangement = get_nametable_arrangement(&data);
-    let alternative_nametable_arrangement = get_alternative_nametable_arrangement(&data);
+    // NES 2.0 files may store miscellaneous ROMs after CHR ROM.
+    let trailing_size = data.len() - chr_rom_end;
+    if trailing_size != 0 && header.misc_rom_count == 0 {
+        warnings.push(CartridgeError::TrailingData {
+            size: trailing_size,
+        });
+    }
 
-    let ines = INes {
+    Ok(INes {
         prg_data,
         chr_data,
-        mapper_index,
-        nametable_arrangement,
-        alternative_nametable_arrangement,
+        header,
+        warnings,
+    })
+}
+
+/// Returns bytes 7–15 of an iNES header if they appear to contain junk, such
+/// as the "DiskDude!" signature left by some ROM tools, which would otherwise
+/// corrupt the mapper number.
+///
+/// https://www.nesdev.org/wiki/INES#Variant_comparison
+fn get_junk_header_bytes(data: &[u8; INES_HEADER_SIZE]) -> Option<Vec<u8>> {
+    let format = data[FLAGS_7_INDEX] & FLAGS_7_INES2_FORMAT_MASK;
+    let is_archaic = format == FLAGS_7_ARCHAIC_FORMAT_ID;
+    let is_ines_with_junk = format == 0
+        && data[JUNK_HEADER_CHECK_INDEX..]
+            .iter()
+            .any(|byte| *byte != 0);
+
+    if is_archaic || is_ines_with_junk {
+        Some(data[JUNK_HEADER_START_INDEX..].to_vec())
+    } else {
+        None
+    }
+}
+
+/// Reads the iNES or NES 2.0 header at the start of the given data.
+pub fn read_header(data: &[u8]) -> Header {
+    let is_nes2 = is_nes2(data);
+
+    let has_battery = get_has_battery(data);
+    let (prg_ram_size, prg_nvram_size, chr_ram_size, chr_nvram_size) = if is_nes2 {
+        get_nes2_ram_sizes(data)
+    } else {
+        get_ines_ram_sizes(data, has_battery)
     };
 
-    let mapper = create_mapper(ines)?;
+    Header {
+        is_nes2,
+        mapper_index: get_mapper_index(data, is_nes2),
+        submapper_index: if is_nes2 {
+            get_submapper_index(data)
+        } else {
+            0
+        },
+        prg_rom_size: get_prg_rom_size(data, is_nes2),
+        chr_rom_size: get_chr_rom_size(data, is_nes2),
+        prg_ram_size,
+        prg_nvram_size,
+        chr_ram_size,
+        chr_nvram_size,
+        has_trainer: get_has_trainer(data),
+        has_battery,
+        nametable_arrangement: get_nametable_arrangement(data),
+        alternative_nametable_arrangement: get_alternative_nametable_arrangement(data),
+        timing: get_timing(data, is_nes2),
+        console_type: get_console_type(data, is_nes2),
+        misc_rom_count: if is_nes2 { get_misc_rom_count(data) } else { 0 },
+        default_expansion_device: if is_nes2 {
+            get_default_expansion_device(data)
+        } else {
+            0
+        },
+        title: None,
+    }
+}
 
-    Ok(Cartridge {
-        mapper: Rc::new(RefCell::new(mapper)),
-    })
+fn is_nes2(data: &[u8]) -> bool {
+    (data[FLAGS_7_INDEX] & FLAGS_7_INES2_FORMAT_MASK) == FLAGS_7_INES2_FORMAT_ID
 }
 
-fn read_data(path_to_ines_file: &str) -> Result<Vec<u8>, Error> {
-    let data = std::fs::read(std::path::Path::new(path_to_ines_file)).map_err(|e| {
-        Error::from(FileError::FileOpenFailed {
-            message: e.to_string(),
-        })
-    })?;
+fn get_mapper_index(data: &[u8], is_nes2: bool) -> u16 {
+    let upper_nibble = data[FLAGS_7_INDEX] & FLAGS_7_MAPPER_UPPER_NIBBLE_MASK;
+    let lower_nibble = (data[FLAGS_6_INDEX] & FLAGS_6_MAPPER_LOWER_NIBBLE_MASK) >> 4;
+    let mapper_index = (upper_nibble | lower_nibble) as u16;
 
-    if data.len() < INES_HEADER_SIZE {
-        return Err(CartridgeError::MissingHeader.into());
+    if is_nes2 {
+        let msb = (data[MAPPER_MSB_SUBMAPPER_INDEX] & MAPPER_MSB_MASK) as u16;
+        (msb << 8) | mapper_index
+    } else {
+        mapper_index
     }
+}
 
-    if data[INES_TAG_INDEX..(INES_TAG_INDEX + INES_TAG.len())] != INES_TAG {
-        return Err(CartridgeError::InvalidHeader {
-            message: "file is not formatted as iNES".to_string(),
-        }
-        .into());
+fn get_submapper_index(data: &[u8]) -> u8 {
+    (data[MAPPER_MSB_SUBMAPPER_INDEX] & SUBMAPPER_MASK) >> 4
+}
+
+fn get_prg_rom_size(data: &[u8], is_nes2: bool) -> usize {
+    let lsb = data[PRG_ROM_SIZE_INDEX];
+    if !is_nes2 {
+        return lsb as usize * INES_PRG_ROM_SIZE_UNITS;
     }
 
-    let is_ines_v2 = (data[FLAGS_7_INDEX] & FLAGS_7_INES2_FORMAT_MASK) == FLAGS_7_INES2_FORMAT_MASK;
-    if is_ines_v2 {
-        return Err(CartridgeError::NotSupported {
-            message: "iNES format version 2 not supported".to_string(),
-        }
-        .into());
+    let msb = data[ROM_SIZE_MSB_INDEX] & PRG_ROM_SIZE_MSB_MASK;
+    get_nes2_rom_size(lsb, msb, INES_PRG_ROM_SIZE_UNITS)
+}
+
+fn get_chr_rom_size(data: &[u8], is_nes2: bool) -> usize {
+    let lsb = data[CHR_ROM_SIZE_INDEX];
+    if !is_nes2 {
+        return lsb as usize * INES_CHR_ROM_SIZE_UNITS;
     }
 
-    Ok(data)
+    let msb = (data[ROM_SIZE_MSB_INDEX] & CHR_ROM_SIZE_MSB_MASK) >> 4;
+    get_nes2_rom_size(lsb, msb, INES_CHR_ROM_SIZE_UNITS)
 }
 
-fn get_mapper_index(data: &[u8]) -> u8 {
-    let upper_nibble = data[FLAGS_7_INDEX] & FLAGS_7_MAPPER_UPPER_NIBBLE_MASK;
-    let lower_nibble = data[FLAGS_6_INDEX] & FLAGS_6_MAPPER_LOWER_NIBBLE_MASK;
+/// Returns a NES 2.0 ROM size in bytes. If the most significant nibble is
+/// `$F`, then the least significant byte is an exponent-multiplier pair in the
+/// form `EEEEEEMM`, and the size is `2^E * (MM*2 + 1)`.
+///
+/// https://www.nesdev.org/wiki/NES_2.0#PRG-ROM_Area
+fn get_nes2_rom_size(lsb: u8, msb: u8, units: usize) -> usize {
+    if msb as u16 == ROM_SIZE_EXPONENT_MSB {
+        let exponent = (lsb >> 2) as u32;
+        let multiplier = ((lsb & 0b_0000_0011) as usize) * 2 + 1;
+
+        // Exponents this large describe ROMs that could never be loaded.
+        1usize
+            .checked_shl(exponent)
+            .and_then(|size| size.checked_mul(multiplier))
+            .unwrap_or(usize::MAX)
+    } else {
+        (((msb as usize) << 8) | lsb as usize) * units
+    }
+}
+
+/// Returns the PRG RAM, PRG NVRAM, CHR RAM, and CHR NVRAM sizes of an iNES
+/// header. The iNES format only specifies the PRG RAM size, and whether or not
+/// it is battery-backed.
+fn get_ines_ram_sizes(data: &[u8], has_battery: bool) -> (usize, usize, usize, usize) {
+    // A value of 0 infers 8 KiB of PRG RAM for compatibility.
+    // https://www.nesdev.org/wiki/INES#Flags_8
+    let prg_ram_size = match data[PRG_RAM_SIZE_INDEX] as usize {
+        0 => INES_PRG_RAM_SIZE_UNITS,
+        units => units * INES_PRG_RAM_SIZE_UNITS,
+    };
+
+    let chr_ram_size = if data[CHR_ROM_SIZE_INDEX] == 0 {
+        DEFAULT_CHR_RAM_SIZE
+    } else {
+        0
+    };
+
+    if has_battery {
+        (0, prg_ram_size, chr_ram_size, 0)
+    } else {
+        (prg_ram_size, 0, chr_ram_size, 0)
+    }
+}
+
+/// Returns the PRG RAM, PRG NVRAM, CHR RAM, and CHR NVRAM sizes of a NES 2.0
+/// header. Each size is stored as a shift count, where the size is `64 << n`
+/// bytes, or zero if the shift count is zero.
+///
+/// https://www.nesdev.org/wiki/NES_2.0#PRG-(NV)RAM/EEPROM
+fn get_nes2_ram_sizes(data: &[u8]) -> (usize, usize, usize, usize) {
+    let prg_ram = data[PRG_RAM_SHIFT_INDEX] & VOLATILE_SHIFT_MASK;
+    let prg_nvram = (data[PRG_RAM_SHIFT_INDEX] & NON_VOLATILE_SHIFT_MASK) >> 4;
+    let chr_ram = data[CHR_RAM_SHIFT_INDEX] & VOLATILE_SHIFT_MASK;
+    let chr_nvram = (data[CHR_RAM_SHIFT_INDEX] & NON_VOLATILE_SHIFT_MASK) >> 4;
 
-    upper_nibble | lower_nibble
+    (
+        get_nes2_ram_size(prg_ram),
+        get_nes2_ram_size(prg_nvram),
+        get_nes2_ram_size(chr_ram),
+        get_nes2_ram_size(chr_nvram),
+    )
+}
+
+fn get_nes2_ram_size(shift_count: u8) -> usize {
+    match shift_count {
+        0 => 0,
+        n => RAM_SHIFT_BASE << n,
+    }
+}
+
+fn get_has_trainer(data: &[u8]) -> bool {
+    data[FLAGS_6_INDEX] & FLAGS_6_TRAINER_MASK != 0
+}
+
+fn get_has_battery(data: &[u8]) -> bool {
+    data[FLAGS_6_INDEX] & FLAGS_6_BATTERY_MASK != 0
 }
 
 fn get_nametable_arrangement(data: &[u8]) -> bool {
@@ -116,8 +351,55 @@ fn get_alternative_nametable_arrangement(data: &[u8]) -> bool {
     data[FLAGS_6_INDEX] & FLAGS_6_ALTERNATIVE_NAMETABLE_ARRANGEMENT_MASK != 0
 }
 
+fn get_timing(data: &[u8], is_nes2: bool) -> Timing {
+    if !is_nes2 {
+        return match data[FLAGS_9_INDEX] & FLAGS_9_TV_SYSTEM_MASK {
+            0 => Timing::Ntsc,
+            _ => Timing::Pal,
+        };
+    }
+
+    match data[TIMING_INDEX] & TIMING_MASK {
+        0 => Timing::Ntsc,
+        1 => Timing::Pal,
+        2 => Timing::MultipleRegion,
+        3 => Timing::Dendy,
+        _ => unreachable!("2-bit value is no greater than 3"),
+    }
+}
+
+fn get_console_type(data: &[u8], is_nes2: bool) -> ConsoleType {
+    match data[FLAGS_7_INDEX] & FLAGS_7_CONSOLE_TYPE_MASK {
+        0 => ConsoleType::Nes,
+        1 if is_nes2 => ConsoleType::VsSystem {
+            ppu_type: data[SYSTEM_TYPE_INDEX] & VS_PPU_TYPE_MASK,
+            hardware_type: (data[SYSTEM_TYPE_INDEX] & VS_HARDWARE_TYPE_MASK) >> 4,
+        },
+        1 => ConsoleType::VsSystem {
+            ppu_type: 0,
+            hardware_type: 0,
+        },
+        2 => ConsoleType::Playchoice10,
+        3 if is_nes2 => ConsoleType::Extended {
+            console_type: data[SYSTEM_TYPE_INDEX] & EXTENDED_CONSOLE_TYPE_MASK,
+        },
+        // iNES only defines the Vs. System and PlayChoice-10 bits; both being
+        // set is meaningless.
+        3 => ConsoleType::Nes,
+        _ => unreachable!("2-bit value is no greater than 3"),
+    }
+}
+
+fn get_misc_rom_count(data: &[u8]) -> u8 {
+    data[MISC_ROMS_INDEX] & MISC_ROMS_MASK
+}
+
+fn get_default_expansion_device(data: &[u8]) -> u8 {
+    data[DEFAULT_EXPANSION_DEVICE_INDEX] & DEFAULT_EXPANSION_DEVICE_MASK
+}
+
 pub fn create_mapper(ines: INes) -> Result<impl Mapper, Error> {
-    match ines.mapper_index {
+    match ines.header.mapper_index {
         0 => NROM::new(ines),
         i => Err(CartridgeError::NotSupported {
             message: format!("mapper {i} is not supported"),
@@ -125,3 +407,204 @@ pub fn create_mapper(ines: INes) -> Result<impl Mapper, Error> {
         .into()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        cartridge::{
+            ConsoleType, Timing,
+            ines::{INES_HEADER_SIZE, INES_TAG, parse, read_header},
+        },
+        error::{CartridgeError, Error},
+    };
+
+    fn create_header(prg_banks: u8, chr_banks: u8) -> Vec<u8> {
+        let mut data = vec![0; INES_HEADER_SIZE];
+        data[..INES_TAG.len()].copy_from_slice(&INES_TAG);
+        data[4] = prg_banks;
+        data[5] = chr_banks;
+        data
+    }
+
+    fn create_nes2_header(prg_banks: u8, chr_banks: u8) -> Vec<u8> {
+        let mut data = create_header(prg_banks, chr_banks);
+        data[7] = 0b_0000_1000;
+        data
+    }
+
+    #[test]
+    fn nes2_rom_sizes() {
+        let mut data = create_nes2_header(0x02, 0x01);
+        data[9] = 0x21; // PRG MSB 1, CHR MSB 2
+        let header = read_header(&data);
+        assert!(header.is_nes2);
+        assert_eq!(header.prg_rom_size, 0x102 * 16384);
+        assert_eq!(header.chr_rom_size, 0x201 * 8192);
+
+        // Exponent-multiplier form: 2^E * (MM*2 + 1).
+        data[4] = (10 << 2) | 0b01; // 2^10 * 3
+        data[5] = (13 << 2) | 0b11; // 2^13 * 7
+        data[9] = 0xFF;
+        let header = read_header(&data);
+        assert_eq!(header.prg_rom_size, 3072);
+        assert_eq!(header.chr_rom_size, 57344);
+
+        // Exponents too large for a usize don't overflow.
+        data[4] = 0xFF;
+        assert_eq!(read_header(&data).prg_rom_size, usize::MAX);
+    }
+
+    #[test]
+    fn nes2_mapper_and_submapper() {
+        let mut data = create_nes2_header(1, 1);
+        data[6] = 0x40;
+        data[7] |= 0x10;
+        data[8] = 0x52;
+        let header = read_header(&data);
+        assert_eq!(header.mapper_index, 0x214);
+        assert_eq!(header.submapper_index, 5);
+
+        // The same bytes in an iNES header have no mapper MSB or submapper.
+        data[7] &= !0b_0000_1100;
+        let header = read_header(&data);
+        assert_eq!(header.mapper_index, 0x14);
+        assert_eq!(header.submapper_index, 0);
+    }
+
+    #[test]
+    fn nes2_ram_sizes() {
+        let mut data = create_nes2_header(1, 0);
+        data[10] = 0x97; // PRG NVRAM 64 << 9, PRG RAM 64 << 7
+        data[11] = 0x07; // CHR RAM 64 << 7
+        let header = read_header(&data);
+        assert_eq!(header.prg_ram_size, 8192);
+        assert_eq!(header.prg_nvram_size, 32768);
+        assert_eq!(header.chr_ram_size, 8192);
+        assert_eq!(header.chr_nvram_size, 0);
+
+        // A shift count of zero means there's no RAM, unlike in iNES headers.
+        data[10] = 0;
+        assert_eq!(read_header(&data).prg_ram_size, 0);
+    }
+
+    #[test]
+    fn nes2_timing_and_console_type() {
+        let mut data = create_nes2_header(1, 1);
+        data[12] = 3;
+        data[14] = 2;
+        data[15] = 0x48; // reserved bits are ignored
+        let header = read_header(&data);
+        assert_eq!(header.timing, Timing::Dendy);
+        assert_eq!(header.console_type, ConsoleType::Nes);
+        assert_eq!(header.misc_rom_count, 2);
+        assert_eq!(header.default_expansion_device, 0x08);
+
+        data[7] |= 1;
+        data[13] = 0x35;
+        assert_eq!(
+            read_header(&data).console_type,
+            ConsoleType::VsSystem {
+                ppu_type: 5,
+                hardware_type: 3
+            }
+        );
+
+        data[7] |= 3;
+        assert_eq!(
+            read_header(&data).console_type,
+            ConsoleType::Extended { console_type: 5 }
+        );
+
+        // iNES headers only have a PAL bit, and no misc ROMs or devices.
+        let mut data = create_header(1, 1);
+        data[9] = 1;
+        data[12] = 3;
+        data[14] = 2;
+        let header = read_header(&data);
+        assert_eq!(header.timing, Timing::Pal);
+        assert_eq!(header.misc_rom_count, 0);
+    }
+
+    #[test]
+    fn truncated_prg_rom() {
+        let mut data = create_header(2, 1);
+        data.extend([0; 16384]);
+
+        let Err(Error::CartridgeError { err }) = parse(&data) else {
+            panic!("truncated PRG ROM should fail to parse");
+        };
+        assert!(matches!(
+            err,
+            CartridgeError::TruncatedPrgRom {
+                expected: 32768,
+                actual: 16384
+            }
+        ));
+    }
+
+    #[test]
+    fn truncated_chr_rom() {
+        let mut data = create_header(1, 1);
+        data.extend([0; 16384 + 100]);
+
+        let Err(Error::CartridgeError { err }) = parse(&data) else {
+            panic!("truncated CHR ROM should fail to parse");
+        };
+        assert!(matches!(
+            err,
+            CartridgeError::TruncatedChrRom {
+                expected: 8192,
+                actual: 100
+            }
+        ));
+    }
+
+    #[test]
+    fn junk_header_is_repaired() {
+        let mut data = create_header(1, 1);
+        data[6] = 0x10; // mapper 1 lower nibble
+        data[7..16].copy_from_slice(b"DiskDude!");
+        data.extend([0; 16384 + 8192]);
+
+        let ines = parse(&data).expect("junk header should be repaired");
+        assert_eq!(ines.header.mapper_index, 1);
+        assert!(matches!(
+            ines.warnings.as_slice(),
+            [CartridgeError::JunkHeader { .. }]
+        ));
+    }
+
+    #[test]
+    fn trailing_data_is_reported() {
+        let mut data = create_header(1, 0);
+        data.extend([0; 16384 + 128]);
+
+        let ines = parse(&data).expect("trailing data should not fail to parse");
+        assert!(matches!(
+            ines.warnings.as_slice(),
+            [CartridgeError::TrailingData { size: 128 }]
+        ));
+    }
+
+    #[test]
+    fn random_headers_do_not_panic() {
+        // A small xorshift generator keeps the inputs deterministic.
+        let mut state: u32 = 0x1234_5678;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+
+        for _ in 0..2_000 {
+            let mut data = create_header(0, 0);
+            for byte in data[4..].iter_mut() {
+                *byte = next() as u8;
+            }
+            data.extend((0..(next() % 0x8000)).map(|_| 0));
+
+            let _ = parse(&data);
+        }
+    }
+}
@@ -1,11 +1,56 @@
-use crate::emu::cartridge::NametableMirroring;
+use crate::emu::{
+    cartridge::{NametableMirroring, memory::PrgRam},
+    error::Error,
+    state::{StateReader, StateWriter},
+};
 
 pub mod nrom;
 
 pub trait Mapper {
-    fn prg_read(&self, addr: u16) -> u8;
+    /// Returns a byte from the CPU's address space, applying any side effects
+    /// of the read. Returns `None` if nothing on the cartridge drives the data
+    /// bus, leaving open bus.
+    fn prg_read(&mut self, addr: u16) -> Option<u8> {
+        self.prg_peek(addr)
+    }
+    /// Returns a byte from the CPU's address space without side effects.
+    fn prg_peek(&self, addr: u16) -> Option<u8>;
     fn prg_write(&mut self, addr: u16, data: u8);
     fn chr_read(&self, addr: u16) -> u8;
     fn chr_write(&mut self, addr: u16, data: u8);
     fn get_nametable_arrangement(&self) -> NametableMirroring;
+    fn get_prg_ram(&self) -> &PrgRam;
+    fn get_prg_ram_mut(&mut self) -> &mut PrgRam;
+
+    /// Advances the state of the mapper ahead by a single CPU cycle.
+    fn tick(&mut self) {}
+
+    /// Returns `true` if the mapper is pulling the IRQ pin low.
+    fn get_irq(&self) -> bool {
+        false
+    }
+
+    /// Returns the memory that is persisted between sessions, such as
+    /// battery-backed PRG RAM.
+    fn get_save_data(&self) -> Vec<u8> {
+        self.get_prg_ram().data().to_vec()
+    }
+
+    /// Restores data returned by `get_save_data`. Data that is too short only
+    /// overwrites the start of the save data.
+    fn set_save_data(&mut self, data: &[u8]) {
+        let save_data = self.get_prg_ram_mut().data_mut();
+        let len = save_data.len().min(data.len());
+        save_data[..len].copy_from_slice(&data[..len]);
+    }
+
+    /// Ejects the current disk side and inserts the next one, for mappers
+    /// with a disk drive.
+    fn switch_disk_side(&mut self) {}
+
+    /// Writes everything about the mapper that can change while running, such
+    /// as RAM and bank registers, to a save state. ROM isn't included.
+    fn save_state(&self, state: &mut StateWriter);
+    /// Restores the state written by [`Mapper::save_state`].
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error>;
 }
@@ -1,6 +1,12 @@
 use crate::emu::{
-    cartridge::{NametableMirroring, ines::INes, mappers::Mapper},
+    cartridge::{
+        NametableMirroring,
+        ines::INes,
+        mappers::Mapper,
+        memory::{ChrMemory, PRG_RAM_END_ADDR, PRG_RAM_START_ADDR, PrgRam},
+    },
     error::{CartridgeError, Error},
+    state::{StateReader, StateWriter},
 };
 
 const PRG_BANKS_MIN_ADDR: u16 = 0x8000;
@@ -10,27 +16,31 @@ const PRG_ROM_BANK_SIZE: usize = PRG_ROM_SIZE / 2;
 
 const CHR_MIN_ADDR: u16 = 0x0000;
 const CHR_MAX_ADDR: u16 = 0x1FFF;
-const CHR_ROM_SIZE: usize = (CHR_MAX_ADDR - CHR_MIN_ADDR + 1) as usize;
+const CHR_SIZE: usize = (CHR_MAX_ADDR - CHR_MIN_ADDR + 1) as usize;
 
 pub struct NROM {
     prg_rom: [u8; PRG_ROM_SIZE],
-    chr_rom: [u8; CHR_ROM_SIZE],
+    prg_ram: PrgRam,
+    chr: ChrMemory,
 
     nametable_arrangement: NametableMirroring,
 }
 
 impl NROM {
     pub fn new(ines: INes) -> Result<Self, Error> {
+        let header = ines.header;
         let prg_rom = create_prg_rom(ines.prg_data)?;
-        let chr_rom = create_chr_rom(ines.chr_data)?;
-        let nametable_arrangement = match ines.nametable_arrangement {
+        let prg_ram = PrgRam::new(header.prg_ram_size + header.prg_nvram_size);
+        let chr = create_chr(ines.chr_data, header.chr_ram_size + header.chr_nvram_size)?;
+        let nametable_arrangement = match header.nametable_arrangement {
             true => NametableMirroring::Horizontal,
             false => NametableMirroring::Vertical,
         };
 
         Ok(NROM {
             prg_rom,
-            chr_rom,
+            prg_ram,
+            chr,
             nametable_arrangement,
         })
     }
@@ -61,52 +71,132 @@ fn create_prg_rom(prg_data: Vec<u8>) -> Result<[u8; PRG_ROM_SIZE], Error> {
     Ok(data)
 }
 
-fn create_chr_rom(chr_data: Vec<u8>) -> Result<[u8; CHR_ROM_SIZE], Error> {
-    if chr_data.len() > CHR_ROM_SIZE {
+fn create_chr(chr_data: Vec<u8>, chr_ram_size: usize) -> Result<ChrMemory, Error> {
+    if chr_data.len() > CHR_SIZE {
         return Err(CartridgeError::NotSupported {
             message: "NROM mapper failed: CHR ROM too large".to_string(),
         }
         .into());
     }
 
-    let mut data = [0; CHR_ROM_SIZE];
+    if chr_data.is_empty() {
+        // NROM boards only ever address 8 KiB of CHR.
+        return Ok(ChrMemory::new(chr_data, chr_ram_size.min(CHR_SIZE)));
+    }
+
+    let mut data = vec![0; CHR_SIZE];
     for (i, byte) in chr_data.iter().enumerate() {
         data[i] = *byte;
     }
 
-    Ok(data)
+    Ok(ChrMemory::new(data, 0))
 }
 
 impl Mapper for NROM {
-    fn prg_read(&self, addr: u16) -> u8 {
+    fn prg_peek(&self, addr: u16) -> Option<u8> {
         match addr {
+            PRG_RAM_START_ADDR..PRG_RAM_END_ADDR => self.prg_ram.read(addr),
             PRG_BANKS_MIN_ADDR..=PRG_BANKS_MAX_ADDR => {
                 let mapped_addr = addr - PRG_BANKS_MIN_ADDR;
-                self.prg_rom[mapped_addr as usize]
+                Some(self.prg_rom[mapped_addr as usize])
             }
-            _ => 0, // ignore; unmapped
+            _ => None, // ignore; unmapped
         }
     }
 
-    fn prg_write(&mut self, _: u16, _: u8) {
-        // ignore; PRG ROM is fixed
+    fn prg_write(&mut self, addr: u16, data: u8) {
+        // Writes outside of PRG RAM are ignored; PRG ROM is fixed.
+        if (PRG_RAM_START_ADDR..PRG_RAM_END_ADDR).contains(&addr) {
+            self.prg_ram.write(addr, data);
+        }
     }
 
     fn chr_read(&self, addr: u16) -> u8 {
         match addr {
-            CHR_MIN_ADDR..=CHR_MAX_ADDR => {
-                let mapped_addr = addr;
-                self.chr_rom[mapped_addr as usize]
-            }
+            CHR_MIN_ADDR..=CHR_MAX_ADDR => self.chr.read(addr as usize),
             _ => 0, // ignore; unmapped
         }
     }
 
-    fn chr_write(&mut self, _: u16, _: u8) {
-        // ignore; CHR ROM is fixed
+    fn chr_write(&mut self, addr: u16, data: u8) {
+        // Writes to CHR ROM are ignored by the CHR memory itself.
+        if (CHR_MIN_ADDR..=CHR_MAX_ADDR).contains(&addr) {
+            self.chr.write(addr as usize, data);
+        }
     }
 
     fn get_nametable_arrangement(&self) -> NametableMirroring {
         self.nametable_arrangement
     }
+
+    fn get_prg_ram(&self) -> &PrgRam {
+        &self.prg_ram
+    }
+
+    fn get_prg_ram_mut(&mut self) -> &mut PrgRam {
+        &mut self.prg_ram
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        self.prg_ram.save_state(state);
+        self.chr.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.prg_ram.load_state(state)?;
+        self.chr.load_state(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        buses::Buses,
+        cartridge::{Cartridge, LoadOptions, ines::INES_TAG, read_cartridge},
+        ppu,
+    };
+
+    /// Creates an NROM cartridge with 16 KiB of PRG ROM, no CHR ROM and the
+    /// given NES 2.0 PRG RAM shift count.
+    fn create_cartridge(prg_ram_shift: u8) -> Cartridge {
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 0, 0, 0b_0000_1000, 0, 0, prg_ram_shift, 7]);
+        rom.resize(16 + 0x4000, 0);
+        read_cartridge(&rom, &LoadOptions::default()).expect("ROM should load")
+    }
+
+    #[test]
+    fn chr_ram_is_writable_without_chr_rom() {
+        let mut buses = ppu::buses::Buses::new(create_cartridge(0));
+        buses.write(0x0000, 0x12);
+        buses.write(0x1FFF, 0x34);
+        assert_eq!(buses.read(0x0000), 0x12);
+        assert_eq!(buses.read(0x1FFF), 0x34);
+    }
+
+    #[test]
+    fn prg_ram_is_sized_from_the_header() {
+        // 64 << 6 bytes, which is mirrored across $6000-$7FFF.
+        let cart = create_cartridge(6);
+        assert_eq!(cart.mapper.borrow().get_prg_ram().data().len(), 4096);
+
+        let mut buses = Buses::new(cart);
+        buses.addr = (0x60, 0x10);
+        buses.write(0xAB);
+        buses.addr = (0x70, 0x10);
+        assert_eq!(buses.read(), 0xAB);
+    }
+
+    #[test]
+    fn missing_prg_ram_is_open_bus() {
+        let cart = create_cartridge(0);
+        assert!(cart.mapper.borrow().get_prg_ram().data().is_empty());
+
+        // The high byte of the address is usually left on the bus.
+        let mut buses = Buses::new(cart);
+        buses.data = 0x60;
+        buses.addr = (0x60, 0x00);
+        assert_eq!(buses.read(), 0x60);
+        assert_eq!(buses.peek(0x6000), 0x60);
+    }
 }
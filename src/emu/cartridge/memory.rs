@@ -0,0 +1,131 @@
+// https://www.nesdev.org/wiki/CHR_ROM_vs._CHR_RAM
+// https://www.nesdev.org/wiki/PRG_RAM_circuit
+
+use crate::emu::{
+    error::Error,
+    state::{StateReader, StateWriter},
+};
+
+pub const PRG_RAM_START_ADDR: u16 = 0x6000;
+pub const PRG_RAM_END_ADDR: u16 = 0x8000;
+pub const PRG_RAM_WINDOW_SIZE: usize = (PRG_RAM_END_ADDR - PRG_RAM_START_ADDR) as usize;
+
+/// The pattern table memory on a cartridge, which is either fixed ROM or
+/// writable RAM.
+#[derive(Clone)]
+pub enum ChrMemory {
+    Rom(Vec<u8>),
+    Ram(Vec<u8>),
+}
+
+impl ChrMemory {
+    /// Creates CHR memory from the cartridge's CHR data. If the cartridge has
+    /// no CHR data, then CHR RAM of the given size is allocated instead.
+    pub fn new(chr_data: Vec<u8>, chr_ram_size: usize) -> Self {
+        if chr_data.is_empty() {
+            ChrMemory::Ram(vec![0; chr_ram_size])
+        } else {
+            ChrMemory::Rom(chr_data)
+        }
+    }
+
+    /// Returns the byte at the given offset. Offsets past the end of the
+    /// memory are mirrored.
+    pub fn read(&self, offset: usize) -> u8 {
+        let data = self.data();
+        if data.is_empty() {
+            return 0;
+        }
+
+        data[offset % data.len()]
+    }
+
+    /// Writes a byte to the given offset. Writes to CHR ROM are ignored.
+    pub fn write(&mut self, offset: usize, data: u8) {
+        if let ChrMemory::Ram(ram) = self {
+            if ram.is_empty() {
+                return;
+            }
+
+            let len = ram.len();
+            ram[offset % len] = data;
+        }
+    }
+
+    pub fn is_ram(&self) -> bool {
+        matches!(self, ChrMemory::Ram(_))
+    }
+
+    pub fn data(&self) -> &[u8] {
+        match self {
+            ChrMemory::Rom(data) | ChrMemory::Ram(data) => data,
+        }
+    }
+
+    /// Writes the contents of CHR RAM. CHR ROM never changes, so nothing is
+    /// written for it.
+    pub fn save_state(&self, state: &mut StateWriter) {
+        if let ChrMemory::Ram(ram) = self {
+            state.write_block(ram);
+        }
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        match self {
+            ChrMemory::Ram(ram) => state.read_block(ram),
+            ChrMemory::Rom(_) => Ok(()),
+        }
+    }
+}
+
+/// Work RAM mapped into the CPU's address space at `$6000–$7FFF`.
+#[derive(Clone)]
+pub struct PrgRam {
+    data: Vec<u8>,
+}
+
+impl PrgRam {
+    pub fn new(size: usize) -> Self {
+        Self {
+            data: vec![0; size],
+        }
+    }
+
+    /// Returns the byte at the given CPU address. RAM smaller than the 8 KiB
+    /// window is mirrored across it, and `None` is returned if there is no RAM,
+    /// which leaves the data bus open.
+    pub fn read(&self, addr: u16) -> Option<u8> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let offset = (addr - PRG_RAM_START_ADDR) as usize % self.data.len();
+        Some(self.data[offset])
+    }
+
+    /// Writes a byte to the given CPU address.
+    pub fn write(&mut self, addr: u16, data: u8) {
+        if self.data.is_empty() {
+            return;
+        }
+
+        let offset = (addr - PRG_RAM_START_ADDR) as usize % self.data.len();
+        self.data[offset] = data;
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.write_block(&self.data);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        state.read_block(&mut self.data)
+    }
+}
@@ -1,8 +1,28 @@
-use crate::emu::cartridge::mappers::Mapper;
-use std::{cell::RefCell, rc::Rc};
+use crate::emu::{
+    cartridge::{
+        database::Database,
+        fds::{BIOS_FILE_NAME, BIOS_SIZE, adapter::FDS},
+        ines::{INES_TAG, INes, create_mapper},
+        mappers::Mapper,
+        unif::UNIF_TAG,
+    },
+    error::{CartridgeError, Error, FileError},
+};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
+pub mod archive;
+pub mod battery;
+pub mod database;
+pub mod fds;
 pub mod ines;
 pub mod mappers;
+pub mod memory;
+pub mod patch;
+pub mod unif;
 
 #[derive(Copy, Clone)]
 pub enum NametableMirroring {
@@ -10,9 +30,240 @@ pub enum NametableMirroring {
     Vertical,
 }
 
+/// The CPU/PPU timing the cartridge was made for.
+///
+/// https://www.nesdev.org/wiki/NES_2.0#CPU/PPU_Timing
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Timing {
+    #[default]
+    Ntsc,
+    Pal,
+    /// Runs on both NTSC and PAL consoles.
+    MultipleRegion,
+    Dendy,
+}
+
+/// The console the cartridge was made for.
+///
+/// https://www.nesdev.org/wiki/NES_2.0#Console_Type
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ConsoleType {
+    #[default]
+    Nes,
+    VsSystem {
+        ppu_type: u8,
+        hardware_type: u8,
+    },
+    Playchoice10,
+    Extended {
+        console_type: u8,
+    },
+}
+
+/// NES 2.0 default expansion device values.
+///
+/// https://www.nesdev.org/wiki/NES_2.0#Default_Expansion_Device
+pub mod expansion_device {
+    pub const UNSPECIFIED: u8 = 0x00;
+    pub const STANDARD_CONTROLLERS: u8 = 0x01;
+    pub const FOUR_SCORE: u8 = 0x02;
+    pub const FAMICOM_FOUR_PLAYERS_ADAPTER: u8 = 0x03;
+    pub const ZAPPER: u8 = 0x08;
+    pub const POWER_PAD_SIDE_A: u8 = 0x0B;
+    pub const POWER_PAD_SIDE_B: u8 = 0x0C;
+    pub const FAMILY_TRAINER_SIDE_A: u8 = 0x0D;
+    pub const FAMILY_TRAINER_SIDE_B: u8 = 0x0E;
+    pub const ARKANOID_NES: u8 = 0x0F;
+    pub const ARKANOID_FAMICOM: u8 = 0x10;
+    pub const ROB: u8 = 0x1F;
+    pub const FAMILY_BASIC_KEYBOARD: u8 = 0x23;
+}
+
+/// Metadata describing the hardware on a cartridge, typically read from the
+/// header of a ROM file.
+#[derive(Clone, Debug, Default)]
+pub struct Header {
+    /// `true` if the header is in the NES 2.0 format.
+    pub is_nes2: bool,
+    pub mapper_index: u16,
+    pub submapper_index: u8,
+    pub prg_rom_size: usize,
+    pub chr_rom_size: usize,
+    /// Volatile PRG RAM size in bytes.
+    pub prg_ram_size: usize,
+    /// Battery-backed PRG RAM size in bytes.
+    pub prg_nvram_size: usize,
+    /// Volatile CHR RAM size in bytes.
+    pub chr_ram_size: usize,
+    /// Battery-backed CHR RAM size in bytes.
+    pub chr_nvram_size: usize,
+    pub has_trainer: bool,
+    /// Whether or not the cartridge has battery-backed memory.
+    pub has_battery: bool,
+    pub nametable_arrangement: bool,
+    pub alternative_nametable_arrangement: bool,
+    pub timing: Timing,
+    pub console_type: ConsoleType,
+    pub misc_rom_count: u8,
+    /// One of the values in [`expansion_device`].
+    pub default_expansion_device: u8,
+    /// The game's title, if known.
+    pub title: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct Cartridge {
     // Use Rc<RefCell<T>> for interior mutability and derived Clone trait.
     // https://stackoverflow.com/a/52994358
     pub mapper: Rc<RefCell<dyn Mapper>>,
+    pub header: Header,
+    /// The CRC32 of the ROM file's contents, which identifies the game that a
+    /// save state belongs to.
+    pub rom_hash: u32,
+    /// Problems with the cartridge's file that were worked around while
+    /// loading it.
+    pub warnings: Vec<CartridgeError>,
+}
+
+/// Options for reading a ROM file from disk.
+#[derive(Default)]
+pub struct FileOptions {
+    /// The name of the ROM to read from a zip archive. Defaults to the first
+    /// ROM in the archive.
+    pub entry: Option<String>,
+    /// IPS, BPS, or UPS patches to apply to the ROM, in order. If empty, then
+    /// any patches next to the ROM that share its name are applied instead.
+    pub patch_paths: Vec<String>,
+    /// The path to the FDS BIOS. Defaults to `disksys.rom` in the directory of
+    /// the disk image.
+    pub fds_bios_path: Option<String>,
+}
+
+/// Options for loading a cartridge.
+#[derive(Default)]
+pub struct LoadOptions {
+    /// If given, then the header is corrected by the database's entry for the
+    /// ROM.
+    pub database: Option<Database>,
+    /// The contents of the FDS BIOS, which is required to load FDS disk
+    /// images.
+    pub fds_bios: Option<Vec<u8>>,
+}
+
+/// Reads a cartridge from a ROM file on disk, which may be inside a zip
+/// archive. If the ROM is an FDS disk image and no BIOS is given in the
+/// options, then the BIOS is read from disk as well.
+pub fn load_cartridge(
+    path: &str,
+    file_options: &FileOptions,
+    mut options: LoadOptions,
+) -> Result<Cartridge, Error> {
+    let data = read_rom_file(path, file_options)?;
+
+    if fds::is_fds(&data) && options.fds_bios.is_none() {
+        let bios_path = match &file_options.fds_bios_path {
+            Some(bios_path) => PathBuf::from(bios_path),
+            None => Path::new(path).with_file_name(BIOS_FILE_NAME),
+        };
+
+        let bios = std::fs::read(&bios_path).map_err(|_| {
+            Error::from(CartridgeError::MissingFdsBios {
+                path: Some(bios_path.display().to_string()),
+            })
+        })?;
+        options.fds_bios = Some(bios);
+    }
+
+    read_cartridge(&data, &options)
+}
+
+/// Reads the contents of a ROM file, decompressing it if it is a zip archive.
+/// Patches are applied to the contents before they are returned.
+pub fn read_rom_file(path: &str, options: &FileOptions) -> Result<Vec<u8>, Error> {
+    let mut data = std::fs::read(Path::new(path)).map_err(|e| {
+        Error::from(FileError::FileOpenFailed {
+            message: e.to_string(),
+        })
+    })?;
+
+    if archive::is_zip(&data) {
+        data = archive::read_zip_entry(&data, options.entry.as_deref())?;
+    }
+
+    let patch_paths = match options.patch_paths.is_empty() {
+        true => patch::find_patches(path),
+        false => options.patch_paths.iter().map(PathBuf::from).collect(),
+    };
+    for patch_path in patch_paths {
+        data = patch::apply_file(&data, &patch_path)?;
+    }
+
+    Ok(data)
+}
+
+/// Reads a cartridge from the contents of an iNES, NES 2.0, UNIF, or FDS file,
+/// picking the format based on the magic bytes.
+pub fn read_cartridge(data: &[u8], options: &LoadOptions) -> Result<Cartridge, Error> {
+    if fds::is_fds(data) {
+        let bios = options
+            .fds_bios
+            .as_deref()
+            .ok_or(CartridgeError::MissingFdsBios { path: None })?;
+
+        return read_disk(data, bios);
+    }
+
+    let mut rom = parse(data)?;
+
+    if let Some(entry) = options
+        .database
+        .as_ref()
+        .and_then(|db| db.lookup(&rom.prg_data, &rom.chr_data))
+    {
+        entry.apply(&mut rom.header);
+    }
+
+    let header = rom.header.clone();
+    let warnings = rom.warnings.clone();
+    let mapper = create_mapper(rom)?;
+
+    Ok(Cartridge {
+        mapper: Rc::new(RefCell::new(mapper)),
+        header,
+        rom_hash: crc32fast::hash(data),
+        warnings,
+    })
+}
+
+/// Creates a Famicom Disk System cartridge from a disk image and the BIOS.
+fn read_disk(data: &[u8], bios: &[u8]) -> Result<Cartridge, Error> {
+    let disk = fds::parse(data)?;
+
+    let bios: [u8; BIOS_SIZE] = bios
+        .try_into()
+        .map_err(|_| Error::from(CartridgeError::InvalidFdsBios { size: bios.len() }))?;
+
+    let header = disk.header.clone();
+    let mapper = FDS::new(disk, bios);
+
+    Ok(Cartridge {
+        mapper: Rc::new(RefCell::new(mapper)),
+        header,
+        rom_hash: crc32fast::hash(data),
+        warnings: Vec::new(),
+    })
+}
+
+/// Parses the contents of a ROM file in any supported format.
+pub fn parse(data: &[u8]) -> Result<INes, Error> {
+    if data.starts_with(&INES_TAG) {
+        ines::parse(data)
+    } else if data.starts_with(&UNIF_TAG) {
+        unif::parse(data)
+    } else {
+        Err(CartridgeError::InvalidHeader {
+            message: "file is not formatted as iNES, NES 2.0, or UNIF".to_string(),
+        }
+        .into())
+    }
 }
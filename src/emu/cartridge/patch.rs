@@ -0,0 +1,514 @@
+// https://zerosoft.zophar.net/ips.php
+// https://github.com/blakesmith/rombp/blob/master/docs/bps_spec.md
+// https://www.romhacking.net/documents/392/
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::emu::error::{Error, FileError, PatchError};
+
+pub const IPS_TAG: &[u8] = b"PATCH";
+pub const BPS_TAG: &[u8] = b"BPS1";
+pub const UPS_TAG: &[u8] = b"UPS1";
+
+/// The file extensions of patches that are applied automatically when they sit
+/// next to the ROM.
+pub const PATCH_EXTENSIONS: [&str; 3] = ["ips", "bps", "ups"];
+
+const IPS_EOF_TAG: &[u8] = b"EOF";
+const IPS_OFFSET_SIZE: usize = 3;
+const IPS_TRUNCATE_SIZE: usize = 3;
+
+/// BPS and UPS patches end with the CRC32s of the source, the target, and the
+/// patch itself.
+const FOOTER_SIZE: usize = 12;
+/// The largest target a BPS or UPS patch may produce. This is far larger than
+/// any NES ROM, and stops a corrupt patch from allocating whatever size it
+/// claims.
+const MAX_TARGET_SIZE: usize = 64 * 1024 * 1024;
+
+const BPS_SOURCE_READ: u64 = 0;
+const BPS_TARGET_READ: u64 = 1;
+const BPS_SOURCE_COPY: u64 = 2;
+const BPS_TARGET_COPY: u64 = 3;
+
+/// Returns the patches next to the ROM at the given path that share its name,
+/// e.g. `game.ips` for `game.nes`.
+pub fn find_patches(rom_path: &str) -> Vec<PathBuf> {
+    PATCH_EXTENSIONS
+        .iter()
+        .map(|extension| Path::new(rom_path).with_extension(extension))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Reads the patch at the given path and applies it to the data.
+pub fn apply_file(data: &[u8], patch_path: &Path) -> Result<Vec<u8>, Error> {
+    let patch = std::fs::read(patch_path).map_err(|e| {
+        Error::from(FileError::FileOpenFailed {
+            message: format!("{}: {e}", patch_path.display()),
+        })
+    })?;
+
+    match apply(data, &patch) {
+        Some(result) => result,
+        None => Err(PatchError::UnknownFormat {
+            path: patch_path.display().to_string(),
+        }
+        .into()),
+    }
+}
+
+/// Applies an IPS, BPS, or UPS patch to the data, picking the format based on
+/// the patch's magic bytes. Returns `None` if the format is not recognized.
+pub fn apply(data: &[u8], patch: &[u8]) -> Option<Result<Vec<u8>, Error>> {
+    let result = if patch.starts_with(IPS_TAG) {
+        apply_ips(data, patch)
+    } else if patch.starts_with(BPS_TAG) {
+        apply_bps(data, patch)
+    } else if patch.starts_with(UPS_TAG) {
+        apply_ups(data, patch)
+    } else {
+        return None;
+    };
+
+    Some(result.map_err(Error::from))
+}
+
+/// Applies an IPS patch, which is a list of records each overwriting bytes at
+/// an offset. IPS patches have no checksums.
+pub fn apply_ips(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let mut reader = PatchReader::new(patch, IPS_TAG.len(), patch.len());
+    let mut target = data.to_vec();
+
+    loop {
+        let offset = reader.read_bytes(IPS_OFFSET_SIZE)?;
+        if offset == IPS_EOF_TAG {
+            break;
+        }
+        let offset = u32::from_be_bytes([0, offset[0], offset[1], offset[2]]) as usize;
+
+        let size = reader.read_u16_be()? as usize;
+        let (size, bytes) = if size == 0 {
+            // Run-length encoded record
+            let size = reader.read_u16_be()? as usize;
+            let value = reader.read_byte()?;
+            (size, vec![value; size])
+        } else {
+            (size, reader.read_bytes(size)?.to_vec())
+        };
+
+        if target.len() < offset + size {
+            target.resize(offset + size, 0);
+        }
+        target[offset..offset + size].copy_from_slice(&bytes);
+    }
+
+    // An extension to the format truncates the target to a given size.
+    if let Ok(size) = reader.read_bytes(IPS_TRUNCATE_SIZE) {
+        let size = u32::from_be_bytes([0, size[0], size[1], size[2]]) as usize;
+        target.truncate(size);
+    }
+
+    Ok(target)
+}
+
+/// Applies a BPS patch, which builds the target from a list of copy commands,
+/// then verifies the checksums of the source, target, and patch.
+pub fn apply_bps(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let footer = verify_footer(data, patch)?;
+    let mut reader = PatchReader::new(patch, BPS_TAG.len(), patch.len() - FOOTER_SIZE);
+
+    let source_size = reader.read_number()? as usize;
+    let target_size = read_target_size(&mut reader)?;
+    let metadata_size = reader.read_number()? as usize;
+    reader.read_bytes(metadata_size)?;
+
+    if source_size != data.len() {
+        return Err(PatchError::InvalidPatch {
+            message: format!(
+                "ROM is {} bytes, but the patch expects {source_size}",
+                data.len()
+            ),
+        });
+    }
+
+    let mut target = Vec::with_capacity(target_size);
+    let mut source_offset = 0usize;
+    let mut target_offset = 0usize;
+
+    while !reader.is_done() {
+        let command = reader.read_number()?;
+        let length = ((command >> 2) + 1) as usize;
+
+        // Checking the length up front also keeps target copies from running
+        // on for as long as the patch says.
+        if get_range(target.len(), length, "command")?.end > target_size {
+            return Err(out_of_bounds("command"));
+        }
+
+        match command & 0b11 {
+            BPS_SOURCE_READ => {
+                let range = get_range(target.len(), length, "source read")?;
+                let bytes = data
+                    .get(range)
+                    .ok_or_else(|| out_of_bounds("source read"))?;
+                target.extend_from_slice(bytes);
+            }
+            BPS_TARGET_READ => target.extend_from_slice(reader.read_bytes(length)?),
+            BPS_SOURCE_COPY => {
+                source_offset = add_signed(source_offset, reader.read_number()?)
+                    .ok_or_else(|| out_of_bounds("source copy"))?;
+                let range = get_range(source_offset, length, "source copy")?;
+                source_offset = range.end;
+                let bytes = data
+                    .get(range)
+                    .ok_or_else(|| out_of_bounds("source copy"))?;
+                target.extend_from_slice(bytes);
+            }
+            BPS_TARGET_COPY => {
+                target_offset = add_signed(target_offset, reader.read_number()?)
+                    .ok_or_else(|| out_of_bounds("target copy"))?;
+                // The copy may overlap the bytes it produces, so it has to go
+                // byte by byte.
+                for _ in 0..length {
+                    let byte = *target
+                        .get(target_offset)
+                        .ok_or_else(|| out_of_bounds("target copy"))?;
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+            _ => unreachable!("command is masked to two bits"),
+        }
+    }
+
+    footer.verify_target(&target)?;
+
+    Ok(target)
+}
+
+/// Applies a UPS patch, which XORs runs of bytes at offsets in the source,
+/// then verifies the checksums of the source, target, and patch.
+pub fn apply_ups(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let footer = verify_footer(data, patch)?;
+    let mut reader = PatchReader::new(patch, UPS_TAG.len(), patch.len() - FOOTER_SIZE);
+
+    let source_size = reader.read_number()? as usize;
+    let target_size = read_target_size(&mut reader)?;
+
+    if source_size != data.len() {
+        return Err(PatchError::InvalidPatch {
+            message: format!(
+                "ROM is {} bytes, but the patch expects {source_size}",
+                data.len()
+            ),
+        });
+    }
+
+    let mut target = data.to_vec();
+    target.resize(target_size, 0);
+
+    let mut offset = 0usize;
+    while !reader.is_done() {
+        offset = offset.saturating_add(reader.read_number()? as usize);
+
+        // XOR bytes until a zero byte, which ends the run.
+        loop {
+            let byte = reader.read_byte()?;
+            if byte == 0 {
+                offset = offset.saturating_add(1);
+                break;
+            }
+
+            let target_byte = target
+                .get_mut(offset)
+                .ok_or_else(|| out_of_bounds("XOR run"))?;
+            *target_byte ^= byte;
+            offset += 1;
+        }
+    }
+
+    footer.verify_target(&target)?;
+
+    Ok(target)
+}
+
+/// The checksums at the end of a BPS or UPS patch.
+struct Footer {
+    target_crc32: u32,
+}
+
+impl Footer {
+    fn verify_target(&self, target: &[u8]) -> Result<(), PatchError> {
+        let actual = crc32fast::hash(target);
+        if actual != self.target_crc32 {
+            return Err(PatchError::TargetChecksumMismatch {
+                expected: self.target_crc32,
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks the patch's own checksum and the checksum of the data it applies to.
+fn verify_footer(data: &[u8], patch: &[u8]) -> Result<Footer, PatchError> {
+    if patch.len() < 4 + FOOTER_SIZE {
+        return Err(PatchError::InvalidPatch {
+            message: "patch is too short".to_string(),
+        });
+    }
+
+    let footer = &patch[patch.len() - FOOTER_SIZE..];
+    let read_crc32 = |index: usize| {
+        u32::from_le_bytes([
+            footer[index],
+            footer[index + 1],
+            footer[index + 2],
+            footer[index + 3],
+        ])
+    };
+    let source_crc32 = read_crc32(0);
+    let target_crc32 = read_crc32(4);
+    let patch_crc32 = read_crc32(8);
+
+    let actual = crc32fast::hash(&patch[..patch.len() - 4]);
+    if actual != patch_crc32 {
+        return Err(PatchError::PatchChecksumMismatch {
+            expected: patch_crc32,
+            actual,
+        });
+    }
+
+    let actual = crc32fast::hash(data);
+    if actual != source_crc32 {
+        return Err(PatchError::SourceChecksumMismatch {
+            expected: source_crc32,
+            actual,
+        });
+    }
+
+    Ok(Footer { target_crc32 })
+}
+
+/// Reads the size of the target of a BPS or UPS patch, rejecting sizes no ROM
+/// could have before anything is allocated for it.
+fn read_target_size(reader: &mut PatchReader) -> Result<usize, PatchError> {
+    let size = reader.read_number()?;
+    if size > MAX_TARGET_SIZE as u64 {
+        return Err(PatchError::InvalidPatch {
+            message: format!("target size of {size} bytes is too large"),
+        });
+    }
+
+    Ok(size as usize)
+}
+
+/// Returns the range of `length` bytes from `start`, failing if its end
+/// overflows.
+fn get_range(start: usize, length: usize, command: &str) -> Result<Range<usize>, PatchError> {
+    let end = start
+        .checked_add(length)
+        .ok_or_else(|| out_of_bounds(command))?;
+    Ok(start..end)
+}
+
+fn add_signed(offset: usize, encoded: u64) -> Option<usize> {
+    // The lowest bit is the sign.
+    let magnitude = (encoded >> 1) as usize;
+    match encoded & 1 != 0 {
+        true => offset.checked_sub(magnitude),
+        false => offset.checked_add(magnitude),
+    }
+}
+
+fn out_of_bounds(command: &str) -> PatchError {
+    PatchError::InvalidPatch {
+        message: format!("{command} is out of bounds"),
+    }
+}
+
+/// Reads the fields of a patch, failing when the end of the patch is reached.
+struct PatchReader<'a> {
+    patch: &'a [u8],
+    position: usize,
+    end: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(patch: &'a [u8], start: usize, end: usize) -> Self {
+        Self {
+            patch,
+            position: start,
+            end,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.end <= self.position
+    }
+
+    fn read_bytes(&mut self, size: usize) -> Result<&'a [u8], PatchError> {
+        if self.end - self.position.min(self.end) < size {
+            return Err(PatchError::InvalidPatch {
+                message: "patch ended unexpectedly".to_string(),
+            });
+        }
+
+        let bytes = &self.patch[self.position..self.position + size];
+        self.position += size;
+        Ok(bytes)
+    }
+
+    fn read_byte(&mut self) -> Result<u8, PatchError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16_be(&mut self) -> Result<u16, PatchError> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Reads a variable-length number as encoded by BPS and UPS patches.
+    fn read_number(&mut self) -> Result<u64, PatchError> {
+        let mut number = 0u64;
+        let mut shift = 1u64;
+
+        loop {
+            let byte = self.read_byte()?;
+            number = (byte as u64 & 0x7F)
+                .checked_mul(shift)
+                .and_then(|value| number.checked_add(value))
+                .ok_or_else(|| PatchError::InvalidPatch {
+                    message: "number is too large".to_string(),
+                })?;
+
+            if byte & 0x80 != 0 {
+                return Ok(number);
+            }
+
+            shift <<= 7;
+            number = number
+                .checked_add(shift)
+                .ok_or_else(|| PatchError::InvalidPatch {
+                    message: "number is too large".to_string(),
+                })?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        cartridge::patch::{apply_bps, apply_ips, apply_ups},
+        error::PatchError,
+    };
+
+    fn encode_number(mut number: u64, out: &mut Vec<u8>) {
+        loop {
+            let byte = (number & 0x7F) as u8;
+            number >>= 7;
+            if number == 0 {
+                out.push(byte | 0x80);
+                return;
+            }
+            out.push(byte);
+            number -= 1;
+        }
+    }
+
+    fn add_footer(source: &[u8], target: &[u8], mut patch: Vec<u8>) -> Vec<u8> {
+        patch.extend(crc32fast::hash(source).to_le_bytes());
+        patch.extend(crc32fast::hash(target).to_le_bytes());
+        patch.extend(crc32fast::hash(&patch).to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn ips_records() {
+        let source = [0u8; 8];
+        let mut patch = b"PATCH".to_vec();
+        patch.extend([0x00, 0x00, 0x01, 0x00, 0x02, 0xAA, 0xBB]);
+        // Run-length encoded record past the end of the source
+        patch.extend([0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0xCC]);
+        patch.extend(b"EOF");
+
+        let target = apply_ips(&source, &patch).expect("patch should apply");
+        assert_eq!(target, [0, 0xAA, 0xBB, 0, 0, 0, 0, 0xCC, 0xCC, 0xCC]);
+    }
+
+    #[test]
+    fn ups_and_bps_patches() {
+        let source = b"GREEN NES".to_vec();
+        let target = b"GREEN FAMICOM".to_vec();
+
+        // UPS: XOR the differing bytes, starting at offset 6.
+        let mut ups = b"UPS1".to_vec();
+        encode_number(source.len() as u64, &mut ups);
+        encode_number(target.len() as u64, &mut ups);
+        encode_number(6, &mut ups);
+        let mut padded = source.clone();
+        padded.resize(target.len(), 0);
+        ups.extend(padded[6..].iter().zip(&target[6..]).map(|(a, b)| a ^ b));
+        ups.push(0);
+        let ups = add_footer(&source, &target, ups);
+
+        assert_eq!(apply_ups(&source, &ups).expect("UPS should apply"), target);
+
+        // BPS: read "GREEN " from the source, then "FAMICOM" from the patch.
+        let mut bps = b"BPS1".to_vec();
+        encode_number(source.len() as u64, &mut bps);
+        encode_number(target.len() as u64, &mut bps);
+        encode_number(0, &mut bps);
+        encode_number((6 - 1) << 2, &mut bps);
+        encode_number(((7 - 1) << 2) | 1, &mut bps);
+        bps.extend(b"FAMICOM");
+        let bps = add_footer(&source, &target, bps);
+
+        assert_eq!(apply_bps(&source, &bps).expect("BPS should apply"), target);
+
+        let wrong_source = b"GREEN SNES";
+        assert!(matches!(
+            apply_bps(wrong_source, &bps),
+            Err(PatchError::SourceChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn oversized_patches_are_rejected() {
+        let source = b"GREEN NES".to_vec();
+
+        // A target far larger than any ROM is rejected before it's allocated.
+        for tag in [b"BPS1", b"UPS1"] {
+            let mut patch = tag.to_vec();
+            encode_number(source.len() as u64, &mut patch);
+            encode_number(u64::MAX >> 8, &mut patch);
+            encode_number(0, &mut patch);
+            let patch = add_footer(&source, &source, patch);
+
+            let result = match tag {
+                b"BPS1" => apply_bps(&source, &patch),
+                _ => apply_ups(&source, &patch),
+            };
+            assert!(matches!(result, Err(PatchError::InvalidPatch { .. })));
+        }
+
+        // A source copy far past the end of the source is out of bounds.
+        let mut bps = b"BPS1".to_vec();
+        encode_number(source.len() as u64, &mut bps);
+        encode_number(source.len() as u64, &mut bps);
+        encode_number(0, &mut bps);
+        encode_number(((4 - 1) << 2) | 2, &mut bps);
+        encode_number(u64::MAX >> 1, &mut bps);
+        let bps = add_footer(&source, &source, bps);
+
+        assert!(matches!(
+            apply_bps(&source, &bps),
+            Err(PatchError::InvalidPatch { .. })
+        ));
+    }
+}
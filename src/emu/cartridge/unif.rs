@@ -0,0 +1,330 @@
+use crate::emu::{
+    cartridge::{Header, Timing, expansion_device, ines::INes},
+    error::{CartridgeError, Error},
+};
+
+// https://www.nesdev.org/wiki/UNIF
+
+const UNIF_HEADER_SIZE: usize = 32;
+
+pub const UNIF_TAG: [u8; 4] = *b"UNIF";
+
+const CHUNK_ID_SIZE: usize = 4;
+const CHUNK_LENGTH_SIZE: usize = 4;
+const CHUNK_HEADER_SIZE: usize = CHUNK_ID_SIZE + CHUNK_LENGTH_SIZE;
+
+const BANK_CHUNK_COUNT: usize = 16;
+
+// MIRR chunk values.
+const MIRR_VERTICAL: u8 = 1;
+const MIRR_FOUR_SCREEN: u8 = 4;
+
+// TVCI chunk values.
+const TVCI_NTSC: u8 = 0;
+const TVCI_PAL: u8 = 1;
+
+// CTRL chunk bits.
+const CTRL_STANDARD: u8 = 1 << 0;
+const CTRL_ZAPPER: u8 = 1 << 1;
+const CTRL_ROB: u8 = 1 << 2;
+const CTRL_ARKANOID: u8 = 1 << 3;
+const CTRL_POWER_PAD: u8 = 1 << 4;
+const CTRL_FOUR_SCORE: u8 = 1 << 5;
+
+/// The size of the PRG RAM assumed for UNIF boards, which do not specify it.
+const DEFAULT_PRG_RAM_SIZE: usize = 8192;
+/// The size of the CHR RAM allocated for boards without CHR ROM.
+const DEFAULT_CHR_RAM_SIZE: usize = 8192;
+
+/// Board name prefixes that do not affect the board's mapper.
+const BOARD_PREFIXES: [&str; 7] = ["NES-", "HVC-", "UNL-", "BTL-", "BMC-", "IREM-", "KONAMI-"];
+
+/// UNIF board names (without their prefix) and the iNES mapper and submapper
+/// they correspond to.
+///
+/// https://www.nesdev.org/wiki/UNIF_to_NES_2.0_Mapping
+const BOARDS: &[(&str, u16, u8)] = &[
+    ("NROM", 0, 0),
+    ("NROM-128", 0, 0),
+    ("NROM-256", 0, 0),
+    ("RROM", 0, 0),
+    ("SAROM", 1, 0),
+    ("SBROM", 1, 0),
+    ("SCROM", 1, 0),
+    ("SEROM", 1, 5),
+    ("SFROM", 1, 0),
+    ("SGROM", 1, 0),
+    ("SHROM", 1, 5),
+    ("SJROM", 1, 0),
+    ("SKROM", 1, 0),
+    ("SLROM", 1, 0),
+    ("SL1ROM", 1, 0),
+    ("SNROM", 1, 0),
+    ("SOROM", 1, 0),
+    ("SUROM", 1, 0),
+    ("SXROM", 1, 0),
+    ("UNROM", 2, 2),
+    ("UOROM", 2, 2),
+    ("CNROM", 3, 2),
+    ("TBROM", 4, 0),
+    ("TEROM", 4, 0),
+    ("TFROM", 4, 0),
+    ("TGROM", 4, 0),
+    ("TKROM", 4, 0),
+    ("TLROM", 4, 0),
+    ("TL1ROM", 4, 0),
+    ("TSROM", 4, 0),
+    ("TR1ROM", 4, 0),
+    ("TVROM", 4, 0),
+    ("HKROM", 4, 1),
+    ("EKROM", 5, 0),
+    ("ELROM", 5, 0),
+    ("ETROM", 5, 0),
+    ("EWROM", 5, 0),
+    ("ANROM", 7, 1),
+    ("AN1ROM", 7, 1),
+    ("AOROM", 7, 0),
+    ("AMROM", 7, 2),
+    ("PNROM", 9, 0),
+    ("PEEOROM", 9, 0),
+    ("FJROM", 10, 0),
+    ("FKROM", 10, 0),
+    ("CPROM", 13, 0),
+    ("GNROM", 66, 0),
+    ("MHROM", 66, 0),
+    ("BNROM", 34, 2),
+];
+
+/// Parses the contents of a UNIF file into the same representation as an iNES
+/// file, with a header describing the board.
+pub fn parse(data: &[u8]) -> Result<INes, Error> {
+    if data.len() < UNIF_HEADER_SIZE {
+        return Err(CartridgeError::MissingHeader.into());
+    }
+
+    if data[..UNIF_TAG.len()] != UNIF_TAG {
+        return Err(CartridgeError::InvalidHeader {
+            message: "file is not formatted as UNIF".to_string(),
+        }
+        .into());
+    }
+
+    let mut board_name = None;
+    let mut title = None;
+    let mut prg_chunks: [Option<&[u8]>; BANK_CHUNK_COUNT] = [None; BANK_CHUNK_COUNT];
+    let mut chr_chunks: [Option<&[u8]>; BANK_CHUNK_COUNT] = [None; BANK_CHUNK_COUNT];
+    let mut mirroring = None;
+    let mut has_battery = false;
+    let mut timing = Timing::Ntsc;
+    let mut controllers = 0;
+
+    let mut offset = UNIF_HEADER_SIZE;
+    while offset < data.len() {
+        let (id, chunk) = read_chunk(data, offset)?;
+        offset += CHUNK_HEADER_SIZE + chunk.len();
+
+        match &id {
+            b"MAPR" => board_name = Some(read_string(chunk)),
+            b"NAME" => title = Some(read_string(chunk)),
+            b"MIRR" => mirroring = chunk.first().copied(),
+            b"BATR" => has_battery = true,
+            b"TVCI" => {
+                timing = match chunk.first() {
+                    Some(&TVCI_NTSC) | None => Timing::Ntsc,
+                    Some(&TVCI_PAL) => Timing::Pal,
+                    Some(_) => Timing::MultipleRegion,
+                }
+            }
+            b"CTRL" => controllers = chunk.first().copied().unwrap_or(0),
+            [b'P', b'R', b'G', bank] => {
+                if let Some(index) = get_bank_index(*bank) {
+                    prg_chunks[index] = Some(chunk);
+                }
+            }
+            [b'C', b'H', b'R', bank] => {
+                if let Some(index) = get_bank_index(*bank) {
+                    chr_chunks[index] = Some(chunk);
+                }
+            }
+            _ => (), // ignore; chunks that do not describe the hardware
+        }
+    }
+
+    let board_name = board_name.ok_or_else(|| {
+        Error::from(CartridgeError::InvalidHeader {
+            message: "UNIF file has no MAPR chunk".to_string(),
+        })
+    })?;
+
+    let (mapper_index, submapper_index) = get_mapper(&board_name).ok_or_else(|| {
+        Error::from(CartridgeError::NotSupported {
+            message: format!("UNIF board {board_name} is not supported"),
+        })
+    })?;
+
+    let prg_data: Vec<u8> = prg_chunks
+        .iter()
+        .flatten()
+        .flat_map(|c| c.iter())
+        .copied()
+        .collect();
+    let chr_data: Vec<u8> = chr_chunks
+        .iter()
+        .flatten()
+        .flat_map(|c| c.iter())
+        .copied()
+        .collect();
+
+    let prg_ram_size = DEFAULT_PRG_RAM_SIZE;
+    let chr_ram_size = if chr_data.is_empty() {
+        DEFAULT_CHR_RAM_SIZE
+    } else {
+        0
+    };
+
+    let header = Header {
+        is_nes2: false,
+        mapper_index,
+        submapper_index,
+        prg_rom_size: prg_data.len(),
+        chr_rom_size: chr_data.len(),
+        prg_ram_size: if has_battery { 0 } else { prg_ram_size },
+        prg_nvram_size: if has_battery { prg_ram_size } else { 0 },
+        chr_ram_size,
+        chr_nvram_size: 0,
+        has_trainer: false,
+        has_battery,
+        // Vertical mirroring is a horizontal nametable arrangement. Boards
+        // with single-screen or mapper-controlled mirroring are left to the
+        // mapper.
+        nametable_arrangement: mirroring == Some(MIRR_VERTICAL),
+        alternative_nametable_arrangement: mirroring == Some(MIRR_FOUR_SCREEN),
+        timing,
+        default_expansion_device: get_expansion_device(controllers),
+        title,
+        ..Header::default()
+    };
+
+    Ok(INes {
+        prg_data,
+        chr_data,
+        header,
+        warnings: Vec::new(),
+    })
+}
+
+/// Returns the ID and data of the chunk at the given offset.
+fn read_chunk(data: &[u8], offset: usize) -> Result<([u8; CHUNK_ID_SIZE], &[u8]), Error> {
+    let truncated = || {
+        Error::from(CartridgeError::InvalidHeader {
+            message: format!("UNIF chunk at offset {offset} is truncated"),
+        })
+    };
+
+    let header = data
+        .get(offset..offset + CHUNK_HEADER_SIZE)
+        .ok_or_else(truncated)?;
+
+    let mut id = [0; CHUNK_ID_SIZE];
+    id.copy_from_slice(&header[..CHUNK_ID_SIZE]);
+
+    let mut length = [0; CHUNK_LENGTH_SIZE];
+    length.copy_from_slice(&header[CHUNK_ID_SIZE..]);
+    let length = u32::from_le_bytes(length) as usize;
+
+    let start = offset + CHUNK_HEADER_SIZE;
+    let chunk = start
+        .checked_add(length)
+        .and_then(|end| data.get(start..end))
+        .ok_or_else(truncated)?;
+
+    Ok((id, chunk))
+}
+
+/// Reads a null-terminated string from a chunk.
+fn read_string(chunk: &[u8]) -> String {
+    let end = chunk.iter().position(|b| *b == 0).unwrap_or(chunk.len());
+    String::from_utf8_lossy(&chunk[..end]).trim().to_string()
+}
+
+/// Returns the index of a PRG or CHR chunk from the hexadecimal digit at the
+/// end of its ID.
+fn get_bank_index(digit: u8) -> Option<usize> {
+    (digit as char).to_digit(16).map(|index| index as usize)
+}
+
+/// Returns the mapper and submapper for the given board name.
+pub fn get_mapper(board_name: &str) -> Option<(u16, u8)> {
+    let name = board_name.to_ascii_uppercase();
+    let name = BOARD_PREFIXES
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(&name);
+
+    BOARDS
+        .iter()
+        .find(|(board, _, _)| *board == name)
+        .map(|(_, mapper, submapper)| (*mapper, *submapper))
+}
+
+/// Returns the NES 2.0 default expansion device for the CTRL chunk's bits.
+fn get_expansion_device(controllers: u8) -> u8 {
+    if controllers & CTRL_FOUR_SCORE != 0 {
+        expansion_device::FOUR_SCORE
+    } else if controllers & CTRL_ZAPPER != 0 {
+        expansion_device::ZAPPER
+    } else if controllers & CTRL_ARKANOID != 0 {
+        expansion_device::ARKANOID_NES
+    } else if controllers & CTRL_POWER_PAD != 0 {
+        expansion_device::POWER_PAD_SIDE_A
+    } else if controllers & CTRL_ROB != 0 {
+        expansion_device::ROB
+    } else if controllers & CTRL_STANDARD != 0 {
+        expansion_device::STANDARD_CONTROLLERS
+    } else {
+        expansion_device::UNSPECIFIED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::cartridge::{
+        parse,
+        unif::{UNIF_HEADER_SIZE, UNIF_TAG},
+    };
+
+    fn push_chunk(data: &mut Vec<u8>, id: &[u8; 4], chunk: &[u8]) {
+        data.extend(id);
+        data.extend((chunk.len() as u32).to_le_bytes());
+        data.extend(chunk);
+    }
+
+    #[test]
+    fn unif_chunks() {
+        let mut data = vec![0; UNIF_HEADER_SIZE];
+        data[..UNIF_TAG.len()].copy_from_slice(&UNIF_TAG);
+        push_chunk(&mut data, b"MAPR", b"NES-SNROM\0");
+        push_chunk(&mut data, b"PRG1", &[0x22; 16]);
+        push_chunk(&mut data, b"PRG0", &[0x11; 16]);
+        push_chunk(&mut data, b"MIRR", &[1]);
+        push_chunk(&mut data, b"BATR", &[0]);
+
+        let rom = parse(&data).expect("UNIF file should parse");
+        assert_eq!(rom.header.mapper_index, 1);
+        assert_eq!(rom.prg_data[0], 0x11);
+        assert_eq!(rom.prg_data[16], 0x22);
+        assert!(rom.chr_data.is_empty());
+        assert!(rom.header.has_battery);
+        assert!(rom.header.nametable_arrangement);
+    }
+
+    #[test]
+    fn truncated_chunk() {
+        let mut data = vec![0; UNIF_HEADER_SIZE];
+        data[..UNIF_TAG.len()].copy_from_slice(&UNIF_TAG);
+        data.extend(b"PRG0");
+        data.extend(1024u32.to_le_bytes());
+
+        assert!(parse(&data).is_err());
+    }
+}
@@ -0,0 +1,29 @@
+/// The external buses that the CPU accesses memory and I/O through, with one
+/// read or write each cycle.
+pub trait Bus {
+    /// Places an address onto the address bus.
+    fn set_addr(&mut self, addr: (u8, u8));
+
+    /// Returns the byte on the data bus.
+    fn get_data(&self) -> u8;
+
+    /// Places a byte onto the data bus.
+    fn set_data(&mut self, data: u8);
+
+    /// Reads the byte at the address on the address bus and places it onto the
+    /// data bus.
+    fn read(&mut self) -> u8;
+
+    /// Places a byte onto the data bus, and then writes it to the address on
+    /// the address bus.
+    fn write(&mut self, data: u8);
+
+    /// Returns the byte at an address without any side effects.
+    fn peek(&self, addr: u16) -> u8;
+
+    /// Returns `true` if the IRQ pin is pulled low.
+    fn get_irq(&self) -> bool;
+
+    /// Returns `true` if the NMI pin is pulled low.
+    fn get_nmi(&self) -> bool;
+}
@@ -1,342 +1,465 @@
-use crate::emu::{
-    buses::Buses as ExternalBuses,
-    cpu::{
-        CPU,
-        half_cycles::{
-            operations::{
-                access::*, arithmetic::*, bitwise::*, branch::*, compare::*, flags::*, jump::*,
-                other::*, shift::*, stack::*, transfer::*, unofficial::*,
-            },
-            *,
-        },
-        instructions::{
-            Instruction, miscellaneous, read, read_modify_write, single_byte, store, unofficial,
-        },
+use crate::emu::cpu::{
+    CPU,
+    bus::Bus,
+    half_cycles::HalfCycle::{self, *},
+    instructions::{
+        Instruction, miscellaneous, read, read_modify_write, single_byte, store, unofficial,
     },
 };
 
 /// A single execution cycle for the CPU.
 pub type Cycle = [HalfCycle; 2];
 
+/// The most cycles that can be queued at once: the longest instruction, plus
+/// an extra cycle for crossing a page.
+pub const CYCLE_QUEUE_CAPACITY: usize = 8;
+
+/// The cycles the CPU has left to execute, stored in a fixed-size ring buffer
+/// so that the queue can be copied without allocating.
+#[derive(Copy, Clone, Debug)]
+pub struct CycleQueue {
+    cycles: [Cycle; CYCLE_QUEUE_CAPACITY],
+    start: usize,
+    len: usize,
+}
+
+impl CycleQueue {
+    pub fn new() -> Self {
+        Self {
+            cycles: [[HalfCycle::Nop; 2]; CYCLE_QUEUE_CAPACITY],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+    }
+
+    /// Adds a cycle to the back of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue is full, which no instruction should cause.
+    pub fn push_back(&mut self, cycle: Cycle) {
+        assert!(
+            self.len < CYCLE_QUEUE_CAPACITY,
+            "cycle queue overflowed: {CYCLE_QUEUE_CAPACITY} cycles are already queued"
+        );
+
+        self.cycles[(self.start + self.len) % CYCLE_QUEUE_CAPACITY] = cycle;
+        self.len += 1;
+    }
+
+    pub fn extend(&mut self, cycles: &[Cycle]) {
+        for &cycle in cycles {
+            self.push_back(cycle);
+        }
+    }
+
+    /// Removes the cycle at the front of the queue and returns it.
+    pub fn pop_front(&mut self) -> Option<Cycle> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let cycle = self.cycles[self.start];
+        self.start = (self.start + 1) % CYCLE_QUEUE_CAPACITY;
+        self.len -= 1;
+        Some(cycle)
+    }
+
+    /// Returns the queued cycles, from front to back.
+    pub fn iter(&self) -> impl Iterator<Item = Cycle> + '_ {
+        (0..self.len).map(|i| self.cycles[(self.start + i) % CYCLE_QUEUE_CAPACITY])
+    }
+}
+
+impl Default for CycleQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Queues are equal if they hold the same cycles, wherever those sit in the
+/// ring buffer.
+impl PartialEq for CycleQueue {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for CycleQueue {}
+
 /// Gets the opcode at the `PC` address and loads it into `IR`.
-pub const GET_OPCODE: Cycle = [get_pc_with_inc, read_opcode];
+pub const GET_OPCODE: Cycle = [GetPcWithInc, ReadOpcode];
 
 /// Gets the byte at the `PC` address and loads it into `ADH`.
-pub const GET_EFFECTIVE_ADDR_HIGH_BYTE: Cycle = [get_pc_with_inc, read_effective_addr_high_byte];
+pub const GET_EFFECTIVE_ADDR_HIGH_BYTE: Cycle = [GetPcWithInc, ReadEffectiveAddrHighByte];
 /// Gets the byte at the `PC` address and loads it into `ADL`.
-pub const GET_EFFECTIVE_ADDR_LOW_BYTE: Cycle = [get_pc_with_inc, read_effective_addr_low_byte];
+pub const GET_EFFECTIVE_ADDR_LOW_BYTE: Cycle = [GetPcWithInc, ReadEffectiveAddrLowByte];
 /// Reads the byte at the effective address and loads it onto the data bus.
-pub const READ_FROM_EFFECTIVE_ADDRESS: Cycle = [get_effective_addr, read_data];
+pub const READ_FROM_EFFECTIVE_ADDRESS: Cycle = [GetEffectiveAddr, ReadData];
 /// Writes the byte on the data bus to the effective address.
-pub const WRITE_TO_EFFECTIVE_ADDRESS: Cycle = [get_effective_addr, write_data];
+pub const WRITE_TO_EFFECTIVE_ADDRESS: Cycle = [GetEffectiveAddr, WriteData];
 
 /// Gets the byte at the `PC` address and loads it into `BAH`.
-pub const GET_BASE_ADDR_HIGH_BYTE: Cycle = [get_pc_with_inc, read_base_addr_high_byte];
+pub const GET_BASE_ADDR_HIGH_BYTE: Cycle = [GetPcWithInc, ReadBaseAddrHighByte];
 /// Gets the byte at the `PC` address and loads it into `BAL`.
-pub const GET_BASE_ADDR_LOW_BYTE: Cycle = [get_pc_with_inc, read_base_addr_low_byte];
+pub const GET_BASE_ADDR_LOW_BYTE: Cycle = [GetPcWithInc, ReadBaseAddrLowByte];
 
 /// Reads the byte at the base zero page address and loads it onto the data bus.
-pub const READ_FROM_BASE_ZERO_PAGE_ADDR: Cycle = [get_base_zero_page_addr, read_data];
+pub const READ_FROM_BASE_ZERO_PAGE_ADDR: Cycle = [GetBaseZeroPageAddr, ReadData];
 
 /// Same functionality as BRK but with different vector.
 pub const HANDLE_NMI: [Cycle; 6] = [
-    [get_pc, read_data],
-    [push_stack, write_pc_high_byte],
-    [push_stack, write_pc_low_byte],
-    [push_stack, write_break_status],
-    [get_nmi_vector_low_byte, read_pc_low_byte],
-    [get_nmi_vector_high_byte, read_pc_high_byte],
+    [GetPc, ReadData],
+    [PushStack, WritePcHighByte],
+    [PushStack, WritePcLowByte],
+    [PushStack, WriteBreakStatus],
+    [GetNmiVectorLowByte, ReadPcLowByte],
+    [GetNmiVectorHighByte, ReadPcHighByte],
 ];
 
 /// Same functionality as BRK.
 pub const HANDLE_IRQ: [Cycle; 6] = [
-    [get_pc, read_data],
-    [push_stack, write_pc_high_byte],
-    [push_stack, write_pc_low_byte],
-    [push_stack, write_break_status],
-    [get_irq_vector_low_byte, read_pc_low_byte],
-    [get_irq_vector_high_byte, read_pc_high_byte],
+    [GetPc, ReadData],
+    [PushStack, WritePcHighByte],
+    [PushStack, WritePcLowByte],
+    [PushStack, WriteBreakStatus],
+    [GetIrqVectorLowByte, ReadPcLowByte],
+    [GetIrqVectorHighByte, ReadPcHighByte],
 ];
 
 impl CPU {
     /// Extends the CPU's cycle queue depending on the opcode at the `PC`
     /// address.
-    pub fn get_cycles(&mut self, buses: &mut ExternalBuses) {
+    pub fn get_cycles(&mut self, buses: &mut impl Bus) {
         self.run_cycle(buses, GET_OPCODE);
         let opcode = self.registers.ir;
         let cycles: &[Cycle] = match opcode {
             0x00 => &miscellaneous::Break {}.get_cycles(),
-            0x01 => &read::IndirectX { op: ora }.get_cycles(),
+            0x01 => &read::IndirectX { op: Ora }.get_cycles(),
             0x02 => &unofficial::Halt {}.get_cycles(),
-            0x03 => &unofficial::IndirectX { op: slo }.get_cycles(),
-            0x04 => &read::ZeroPage { op: nop }.get_cycles(),
-            0x05 => &read::ZeroPage { op: ora }.get_cycles(),
-            0x06 => &read_modify_write::ZeroPage { op: asl_m }.get_cycles(),
-            0x07 => &unofficial::ZeroPage { op: slo }.get_cycles(),
-            0x08 => &miscellaneous::Push { op: php }.get_cycles(),
-            0x09 => &read::Immediate { op: ora }.get_cycles(),
-            0x0A => &single_byte::SingleByte { op: asl_a }.get_cycles(),
+            0x03 => &unofficial::IndirectX { op: Slo }.get_cycles(),
+            0x04 => &read::ZeroPage { op: Nop }.get_cycles(),
+            0x05 => &read::ZeroPage { op: Ora }.get_cycles(),
+            0x06 => &read_modify_write::ZeroPage { op: AslM }.get_cycles(),
+            0x07 => &unofficial::ZeroPage { op: Slo }.get_cycles(),
+            0x08 => &miscellaneous::Push { op: Php }.get_cycles(),
+            0x09 => &read::Immediate { op: Ora }.get_cycles(),
+            0x0A => &single_byte::SingleByte { op: AslA }.get_cycles(),
             0x0B => todo!("opcode not yet implemented: {opcode:02X}"),
-            0x0C => &read::Absolute { op: nop }.get_cycles(),
-            0x0D => &read::Absolute { op: ora }.get_cycles(),
-            0x0E => &read_modify_write::Absolute { op: asl_m }.get_cycles(),
-            0x0F => &unofficial::Absolute { op: slo }.get_cycles(),
+            0x0C => &read::Absolute { op: Nop }.get_cycles(),
+            0x0D => &read::Absolute { op: Ora }.get_cycles(),
+            0x0E => &read_modify_write::Absolute { op: AslM }.get_cycles(),
+            0x0F => &unofficial::Absolute { op: Slo }.get_cycles(),
 
-            0x10 => &miscellaneous::Branch { op: bpl }.get_cycles(),
-            0x11 => &read::IndirectY { op: ora_indirect_y }.get_cycles(),
+            0x10 => &miscellaneous::Branch { op: Bpl }.get_cycles(),
+            0x11 => &read::IndirectY { op: OraIndirectY }.get_cycles(),
             0x12 => &unofficial::Halt {}.get_cycles(),
-            0x13 => &unofficial::IndirectY { op: slo }.get_cycles(),
-            0x14 => &read::ZeroPageX { op: nop }.get_cycles(),
-            0x15 => &read::ZeroPageX { op: ora }.get_cycles(),
-            0x16 => &read_modify_write::ZeroPageX { op: asl_m }.get_cycles(),
-            0x17 => &unofficial::ZeroPageX { op: slo }.get_cycles(),
-            0x18 => &single_byte::SingleByte { op: clc }.get_cycles(),
-            0x19 => &read::AbsoluteY { op: ora }.get_cycles(),
-            0x1A => &single_byte::SingleByte { op: nop }.get_cycles(),
-            0x1B => &unofficial::AbsoluteY { op: slo }.get_cycles(),
-            0x1C => &read::AbsoluteX { op: nop_abs_index }.get_cycles(),
-            0x1D => &read::AbsoluteX { op: ora_abs_index }.get_cycles(),
-            0x1E => &read_modify_write::AbsoluteX { op: asl_m }.get_cycles(),
-            0x1F => &unofficial::AbsoluteX { op: slo }.get_cycles(),
-
-            0x20 => &miscellaneous::JumpToSubroutine { op: jsr }.get_cycles(),
-            0x21 => &read::IndirectX { op: and }.get_cycles(),
+            0x13 => &unofficial::IndirectY { op: Slo }.get_cycles(),
+            0x14 => &read::ZeroPageX { op: Nop }.get_cycles(),
+            0x15 => &read::ZeroPageX { op: Ora }.get_cycles(),
+            0x16 => &read_modify_write::ZeroPageX { op: AslM }.get_cycles(),
+            0x17 => &unofficial::ZeroPageX { op: Slo }.get_cycles(),
+            0x18 => &single_byte::SingleByte { op: Clc }.get_cycles(),
+            0x19 => &read::AbsoluteY { op: Ora }.get_cycles(),
+            0x1A => &single_byte::SingleByte { op: Nop }.get_cycles(),
+            0x1B => &unofficial::AbsoluteY { op: Slo }.get_cycles(),
+            0x1C => &read::AbsoluteX { op: NopAbsIndex }.get_cycles(),
+            0x1D => &read::AbsoluteX { op: OraAbsIndex }.get_cycles(),
+            0x1E => &read_modify_write::AbsoluteX { op: AslM }.get_cycles(),
+            0x1F => &unofficial::AbsoluteX { op: Slo }.get_cycles(),
+
+            0x20 => &miscellaneous::JumpToSubroutine { op: Jsr }.get_cycles(),
+            0x21 => &read::IndirectX { op: And }.get_cycles(),
             0x22 => &unofficial::Halt {}.get_cycles(),
-            0x23 => &unofficial::IndirectX { op: rla }.get_cycles(),
-            0x24 => &read::ZeroPage { op: bit }.get_cycles(),
-            0x25 => &read::ZeroPage { op: and }.get_cycles(),
-            0x26 => &read_modify_write::ZeroPage { op: rol }.get_cycles(),
-            0x27 => &unofficial::ZeroPage { op: rla }.get_cycles(),
-            0x28 => &miscellaneous::Pull { op: plp }.get_cycles(),
-            0x29 => &read::Immediate { op: and }.get_cycles(),
-            0x2A => &single_byte::SingleByte { op: rol_a }.get_cycles(),
+            0x23 => &unofficial::IndirectX { op: Rla }.get_cycles(),
+            0x24 => &read::ZeroPage { op: Bit }.get_cycles(),
+            0x25 => &read::ZeroPage { op: And }.get_cycles(),
+            0x26 => &read_modify_write::ZeroPage { op: Rol }.get_cycles(),
+            0x27 => &unofficial::ZeroPage { op: Rla }.get_cycles(),
+            0x28 => &miscellaneous::Pull { op: Plp }.get_cycles(),
+            0x29 => &read::Immediate { op: And }.get_cycles(),
+            0x2A => &single_byte::SingleByte { op: RolA }.get_cycles(),
             0x2B => todo!("opcode not yet implemented: {opcode:02X}"),
-            0x2C => &read::Absolute { op: bit }.get_cycles(),
-            0x2D => &read::Absolute { op: and }.get_cycles(),
-            0x2E => &read_modify_write::Absolute { op: rol }.get_cycles(),
-            0x2F => &unofficial::Absolute { op: rla }.get_cycles(),
+            0x2C => &read::Absolute { op: Bit }.get_cycles(),
+            0x2D => &read::Absolute { op: And }.get_cycles(),
+            0x2E => &read_modify_write::Absolute { op: Rol }.get_cycles(),
+            0x2F => &unofficial::Absolute { op: Rla }.get_cycles(),
 
-            0x30 => &miscellaneous::Branch { op: bmi }.get_cycles(),
-            0x31 => &read::IndirectY { op: and_indirect_y }.get_cycles(),
+            0x30 => &miscellaneous::Branch { op: Bmi }.get_cycles(),
+            0x31 => &read::IndirectY { op: AndIndirectY }.get_cycles(),
             0x32 => &unofficial::Halt {}.get_cycles(),
-            0x33 => &unofficial::IndirectY { op: rla }.get_cycles(),
-            0x34 => &read::ZeroPageX { op: nop }.get_cycles(),
-            0x35 => &read::ZeroPageX { op: and }.get_cycles(),
-            0x36 => &read_modify_write::ZeroPageX { op: rol }.get_cycles(),
-            0x37 => &unofficial::ZeroPageX { op: rla }.get_cycles(),
-            0x38 => &single_byte::SingleByte { op: sec }.get_cycles(),
-            0x39 => &read::AbsoluteY { op: and }.get_cycles(),
-            0x3A => &single_byte::SingleByte { op: nop }.get_cycles(),
-            0x3B => &unofficial::AbsoluteY { op: rla }.get_cycles(),
-            0x3C => &read::AbsoluteX { op: nop_abs_index }.get_cycles(),
-            0x3D => &read::AbsoluteX { op: and_abs_index }.get_cycles(),
-            0x3E => &read_modify_write::AbsoluteX { op: rol }.get_cycles(),
-            0x3F => &unofficial::AbsoluteX { op: rla }.get_cycles(),
-
-            0x40 => &miscellaneous::ReturnFromInterrupt { op: rti }.get_cycles(),
-            0x41 => &read::IndirectX { op: eor }.get_cycles(),
+            0x33 => &unofficial::IndirectY { op: Rla }.get_cycles(),
+            0x34 => &read::ZeroPageX { op: Nop }.get_cycles(),
+            0x35 => &read::ZeroPageX { op: And }.get_cycles(),
+            0x36 => &read_modify_write::ZeroPageX { op: Rol }.get_cycles(),
+            0x37 => &unofficial::ZeroPageX { op: Rla }.get_cycles(),
+            0x38 => &single_byte::SingleByte { op: Sec }.get_cycles(),
+            0x39 => &read::AbsoluteY { op: And }.get_cycles(),
+            0x3A => &single_byte::SingleByte { op: Nop }.get_cycles(),
+            0x3B => &unofficial::AbsoluteY { op: Rla }.get_cycles(),
+            0x3C => &read::AbsoluteX { op: NopAbsIndex }.get_cycles(),
+            0x3D => &read::AbsoluteX { op: AndAbsIndex }.get_cycles(),
+            0x3E => &read_modify_write::AbsoluteX { op: Rol }.get_cycles(),
+            0x3F => &unofficial::AbsoluteX { op: Rla }.get_cycles(),
+
+            0x40 => &miscellaneous::ReturnFromInterrupt { op: Rti }.get_cycles(),
+            0x41 => &read::IndirectX { op: Eor }.get_cycles(),
             0x42 => &unofficial::Halt {}.get_cycles(),
-            0x43 => &unofficial::IndirectX { op: sre }.get_cycles(),
-            0x44 => &read::ZeroPage { op: nop }.get_cycles(),
-            0x45 => &read::ZeroPage { op: eor }.get_cycles(),
-            0x46 => &read_modify_write::ZeroPage { op: lsr_m }.get_cycles(),
-            0x47 => &unofficial::ZeroPage { op: sre }.get_cycles(),
-            0x48 => &miscellaneous::Push { op: pha }.get_cycles(),
-            0x49 => &read::Immediate { op: eor }.get_cycles(),
-            0x4A => &single_byte::SingleByte { op: lsr_a }.get_cycles(),
+            0x43 => &unofficial::IndirectX { op: Sre }.get_cycles(),
+            0x44 => &read::ZeroPage { op: Nop }.get_cycles(),
+            0x45 => &read::ZeroPage { op: Eor }.get_cycles(),
+            0x46 => &read_modify_write::ZeroPage { op: LsrM }.get_cycles(),
+            0x47 => &unofficial::ZeroPage { op: Sre }.get_cycles(),
+            0x48 => &miscellaneous::Push { op: Pha }.get_cycles(),
+            0x49 => &read::Immediate { op: Eor }.get_cycles(),
+            0x4A => &single_byte::SingleByte { op: LsrA }.get_cycles(),
             0x4B => todo!("opcode not yet implemented: {opcode:02X}"),
-            0x4C => &miscellaneous::JumpAbsolute { op: jmp_absolute }.get_cycles(),
-            0x4D => &read::Absolute { op: eor }.get_cycles(),
-            0x4E => &read_modify_write::Absolute { op: lsr_m }.get_cycles(),
-            0x4F => &unofficial::Absolute { op: sre }.get_cycles(),
+            0x4C => &miscellaneous::JumpAbsolute { op: JmpAbsolute }.get_cycles(),
+            0x4D => &read::Absolute { op: Eor }.get_cycles(),
+            0x4E => &read_modify_write::Absolute { op: LsrM }.get_cycles(),
+            0x4F => &unofficial::Absolute { op: Sre }.get_cycles(),
 
-            0x50 => &miscellaneous::Branch { op: bvc }.get_cycles(),
-            0x51 => &read::IndirectY { op: eor_indirect_y }.get_cycles(),
+            0x50 => &miscellaneous::Branch { op: Bvc }.get_cycles(),
+            0x51 => &read::IndirectY { op: EorIndirectY }.get_cycles(),
             0x52 => &unofficial::Halt {}.get_cycles(),
-            0x53 => &unofficial::IndirectY { op: sre }.get_cycles(),
-            0x54 => &read::ZeroPageX { op: nop }.get_cycles(),
-            0x55 => &read::ZeroPageX { op: eor }.get_cycles(),
-            0x56 => &read_modify_write::ZeroPageX { op: lsr_m }.get_cycles(),
-            0x57 => &unofficial::ZeroPageX { op: sre }.get_cycles(),
-            0x58 => &single_byte::SingleByte { op: cli }.get_cycles(),
-            0x59 => &read::AbsoluteY { op: eor }.get_cycles(),
-            0x5A => &single_byte::SingleByte { op: nop }.get_cycles(),
-            0x5B => &unofficial::AbsoluteY { op: sre }.get_cycles(),
-            0x5C => &read::AbsoluteX { op: nop_abs_index }.get_cycles(),
-            0x5D => &read::AbsoluteX { op: eor_abs_index }.get_cycles(),
-            0x5E => &read_modify_write::AbsoluteX { op: lsr_m }.get_cycles(),
-            0x5F => &unofficial::AbsoluteX { op: sre }.get_cycles(),
+            0x53 => &unofficial::IndirectY { op: Sre }.get_cycles(),
+            0x54 => &read::ZeroPageX { op: Nop }.get_cycles(),
+            0x55 => &read::ZeroPageX { op: Eor }.get_cycles(),
+            0x56 => &read_modify_write::ZeroPageX { op: LsrM }.get_cycles(),
+            0x57 => &unofficial::ZeroPageX { op: Sre }.get_cycles(),
+            0x58 => &single_byte::SingleByte { op: Cli }.get_cycles(),
+            0x59 => &read::AbsoluteY { op: Eor }.get_cycles(),
+            0x5A => &single_byte::SingleByte { op: Nop }.get_cycles(),
+            0x5B => &unofficial::AbsoluteY { op: Sre }.get_cycles(),
+            0x5C => &read::AbsoluteX { op: NopAbsIndex }.get_cycles(),
+            0x5D => &read::AbsoluteX { op: EorAbsIndex }.get_cycles(),
+            0x5E => &read_modify_write::AbsoluteX { op: LsrM }.get_cycles(),
+            0x5F => &unofficial::AbsoluteX { op: Sre }.get_cycles(),
 
             0x60 => &miscellaneous::ReturnFromSubroutine {}.get_cycles(),
-            0x61 => &read::IndirectX { op: adc }.get_cycles(),
+            0x61 => &read::IndirectX { op: Adc }.get_cycles(),
             0x62 => &unofficial::Halt {}.get_cycles(),
-            0x63 => &unofficial::IndirectX { op: rra }.get_cycles(),
-            0x64 => &read::ZeroPage { op: nop }.get_cycles(),
-            0x65 => &read::ZeroPage { op: adc }.get_cycles(),
-            0x66 => &read_modify_write::ZeroPage { op: ror_m }.get_cycles(),
-            0x67 => &unofficial::ZeroPage { op: rra }.get_cycles(),
-            0x68 => &miscellaneous::Pull { op: pla }.get_cycles(),
-            0x69 => &read::Immediate { op: adc }.get_cycles(),
-            0x6A => &single_byte::SingleByte { op: ror_a }.get_cycles(),
+            0x63 => &unofficial::IndirectX { op: Rra }.get_cycles(),
+            0x64 => &read::ZeroPage { op: Nop }.get_cycles(),
+            0x65 => &read::ZeroPage { op: Adc }.get_cycles(),
+            0x66 => &read_modify_write::ZeroPage { op: RorM }.get_cycles(),
+            0x67 => &unofficial::ZeroPage { op: Rra }.get_cycles(),
+            0x68 => &miscellaneous::Pull { op: Pla }.get_cycles(),
+            0x69 => &read::Immediate { op: Adc }.get_cycles(),
+            0x6A => &single_byte::SingleByte { op: RorA }.get_cycles(),
             0x6B => todo!("opcode not yet implemented: {opcode:02X}"),
             0x6C => &miscellaneous::JumpIndirect {}.get_cycles(),
-            0x6D => &read::Absolute { op: adc }.get_cycles(),
-            0x6E => &read_modify_write::Absolute { op: ror_m }.get_cycles(),
-            0x6F => &unofficial::Absolute { op: rra }.get_cycles(),
+            0x6D => &read::Absolute { op: Adc }.get_cycles(),
+            0x6E => &read_modify_write::Absolute { op: RorM }.get_cycles(),
+            0x6F => &unofficial::Absolute { op: Rra }.get_cycles(),
 
-            0x70 => &miscellaneous::Branch { op: bvs }.get_cycles(),
-            0x71 => &read::IndirectY { op: adc_indirect_y }.get_cycles(),
+            0x70 => &miscellaneous::Branch { op: Bvs }.get_cycles(),
+            0x71 => &read::IndirectY { op: AdcIndirectY }.get_cycles(),
             0x72 => &unofficial::Halt {}.get_cycles(),
-            0x73 => &unofficial::IndirectY { op: rra }.get_cycles(),
-            0x74 => &read::ZeroPageX { op: nop }.get_cycles(),
-            0x75 => &read::ZeroPageX { op: adc }.get_cycles(),
-            0x76 => &read_modify_write::ZeroPageX { op: ror_m }.get_cycles(),
-            0x77 => &unofficial::ZeroPageX { op: rra }.get_cycles(),
-            0x78 => &single_byte::SingleByte { op: sei }.get_cycles(),
-            0x79 => &read::AbsoluteY { op: adc }.get_cycles(),
-            0x7A => &single_byte::SingleByte { op: nop }.get_cycles(),
-            0x7B => &unofficial::AbsoluteY { op: rra }.get_cycles(),
-            0x7C => &read::AbsoluteX { op: nop_abs_index }.get_cycles(),
-            0x7D => &read::AbsoluteX { op: adc_abs_index }.get_cycles(),
-            0x7E => &read_modify_write::AbsoluteX { op: ror_m }.get_cycles(),
-            0x7F => &unofficial::AbsoluteX { op: rra }.get_cycles(),
-
-            0x80 => &read::Immediate { op: nop }.get_cycles(),
-            0x81 => &store::IndirectX { op: sta }.get_cycles(),
-            0x82 => &read::Immediate { op: nop }.get_cycles(),
-            0x83 => &store::IndirectX { op: sax }.get_cycles(),
-            0x84 => &store::ZeroPage { op: sty }.get_cycles(),
-            0x85 => &store::ZeroPage { op: sta }.get_cycles(),
-            0x86 => &store::ZeroPage { op: stx }.get_cycles(),
-            0x87 => &store::ZeroPage { op: sax }.get_cycles(),
-            0x88 => &single_byte::SingleByte { op: dey }.get_cycles(),
-            0x89 => &read::Immediate { op: nop }.get_cycles(),
-            0x8A => &single_byte::SingleByte { op: txa }.get_cycles(),
+            0x73 => &unofficial::IndirectY { op: Rra }.get_cycles(),
+            0x74 => &read::ZeroPageX { op: Nop }.get_cycles(),
+            0x75 => &read::ZeroPageX { op: Adc }.get_cycles(),
+            0x76 => &read_modify_write::ZeroPageX { op: RorM }.get_cycles(),
+            0x77 => &unofficial::ZeroPageX { op: Rra }.get_cycles(),
+            0x78 => &single_byte::SingleByte { op: Sei }.get_cycles(),
+            0x79 => &read::AbsoluteY { op: Adc }.get_cycles(),
+            0x7A => &single_byte::SingleByte { op: Nop }.get_cycles(),
+            0x7B => &unofficial::AbsoluteY { op: Rra }.get_cycles(),
+            0x7C => &read::AbsoluteX { op: NopAbsIndex }.get_cycles(),
+            0x7D => &read::AbsoluteX { op: AdcAbsIndex }.get_cycles(),
+            0x7E => &read_modify_write::AbsoluteX { op: RorM }.get_cycles(),
+            0x7F => &unofficial::AbsoluteX { op: Rra }.get_cycles(),
+
+            0x80 => &read::Immediate { op: Nop }.get_cycles(),
+            0x81 => &store::IndirectX { op: Sta }.get_cycles(),
+            0x82 => &read::Immediate { op: Nop }.get_cycles(),
+            0x83 => &store::IndirectX { op: Sax }.get_cycles(),
+            0x84 => &store::ZeroPage { op: Sty }.get_cycles(),
+            0x85 => &store::ZeroPage { op: Sta }.get_cycles(),
+            0x86 => &store::ZeroPage { op: Stx }.get_cycles(),
+            0x87 => &store::ZeroPage { op: Sax }.get_cycles(),
+            0x88 => &single_byte::SingleByte { op: Dey }.get_cycles(),
+            0x89 => &read::Immediate { op: Nop }.get_cycles(),
+            0x8A => &single_byte::SingleByte { op: Txa }.get_cycles(),
             0x8B => todo!("opcode not yet implemented: {opcode:02X}"),
-            0x8C => &store::Absolute { op: sty }.get_cycles(),
-            0x8D => &store::Absolute { op: sta }.get_cycles(),
-            0x8E => &store::Absolute { op: stx }.get_cycles(),
-            0x8F => &store::Absolute { op: sax }.get_cycles(),
+            0x8C => &store::Absolute { op: Sty }.get_cycles(),
+            0x8D => &store::Absolute { op: Sta }.get_cycles(),
+            0x8E => &store::Absolute { op: Stx }.get_cycles(),
+            0x8F => &store::Absolute { op: Sax }.get_cycles(),
 
-            0x90 => &miscellaneous::Branch { op: bcc }.get_cycles(),
-            0x91 => &store::IndirectY { op: sta }.get_cycles(),
+            0x90 => &miscellaneous::Branch { op: Bcc }.get_cycles(),
+            0x91 => &store::IndirectY { op: Sta }.get_cycles(),
             0x92 => &unofficial::Halt {}.get_cycles(),
             0x93 => todo!("opcode not yet implemented: {opcode:02X}"),
-            0x94 => &store::ZeroPageX { op: sty }.get_cycles(),
-            0x95 => &store::ZeroPageX { op: sta }.get_cycles(),
-            0x96 => &store::ZeroPageY { op: stx }.get_cycles(),
-            0x97 => &store::ZeroPageY { op: sax }.get_cycles(),
-            0x98 => &single_byte::SingleByte { op: tya }.get_cycles(),
-            0x99 => &store::AbsoluteY { op: sta }.get_cycles(),
-            0x9A => &single_byte::SingleByte { op: txs }.get_cycles(),
+            0x94 => &store::ZeroPageX { op: Sty }.get_cycles(),
+            0x95 => &store::ZeroPageX { op: Sta }.get_cycles(),
+            0x96 => &store::ZeroPageY { op: Stx }.get_cycles(),
+            0x97 => &store::ZeroPageY { op: Sax }.get_cycles(),
+            0x98 => &single_byte::SingleByte { op: Tya }.get_cycles(),
+            0x99 => &store::AbsoluteY { op: Sta }.get_cycles(),
+            0x9A => &single_byte::SingleByte { op: Txs }.get_cycles(),
             0x9B => todo!("opcode not yet implemented: {opcode:02X}"),
             0x9C => todo!("opcode not yet implemented: {opcode:02X}"),
-            0x9D => &store::AbsoluteX { op: sta }.get_cycles(),
+            0x9D => &store::AbsoluteX { op: Sta }.get_cycles(),
             0x9E => todo!("opcode not yet implemented: {opcode:02X}"),
             0x9F => todo!("opcode not yet implemented: {opcode:02X}"),
 
-            0xA0 => &read::Immediate { op: ldy }.get_cycles(),
-            0xA1 => &read::IndirectX { op: lda }.get_cycles(),
-            0xA2 => &read::Immediate { op: ldx }.get_cycles(),
-            0xA3 => &read::IndirectX { op: lax }.get_cycles(),
-            0xA4 => &read::ZeroPage { op: ldy }.get_cycles(),
-            0xA5 => &read::ZeroPage { op: lda }.get_cycles(),
-            0xA6 => &read::ZeroPage { op: ldx }.get_cycles(),
-            0xA7 => &read::ZeroPage { op: lax }.get_cycles(),
-            0xA8 => &single_byte::SingleByte { op: tay }.get_cycles(),
-            0xA9 => &read::Immediate { op: lda }.get_cycles(),
-            0xAA => &single_byte::SingleByte { op: tax }.get_cycles(),
+            0xA0 => &read::Immediate { op: Ldy }.get_cycles(),
+            0xA1 => &read::IndirectX { op: Lda }.get_cycles(),
+            0xA2 => &read::Immediate { op: Ldx }.get_cycles(),
+            0xA3 => &read::IndirectX { op: Lax }.get_cycles(),
+            0xA4 => &read::ZeroPage { op: Ldy }.get_cycles(),
+            0xA5 => &read::ZeroPage { op: Lda }.get_cycles(),
+            0xA6 => &read::ZeroPage { op: Ldx }.get_cycles(),
+            0xA7 => &read::ZeroPage { op: Lax }.get_cycles(),
+            0xA8 => &single_byte::SingleByte { op: Tay }.get_cycles(),
+            0xA9 => &read::Immediate { op: Lda }.get_cycles(),
+            0xAA => &single_byte::SingleByte { op: Tax }.get_cycles(),
             0xAB => todo!("opcode not yet implemented: {opcode:02X}"),
-            0xAC => &read::Absolute { op: ldy }.get_cycles(),
-            0xAD => &read::Absolute { op: lda }.get_cycles(),
-            0xAE => &read::Absolute { op: ldx }.get_cycles(),
-            0xAF => &read::Absolute { op: lax }.get_cycles(),
+            0xAC => &read::Absolute { op: Ldy }.get_cycles(),
+            0xAD => &read::Absolute { op: Lda }.get_cycles(),
+            0xAE => &read::Absolute { op: Ldx }.get_cycles(),
+            0xAF => &read::Absolute { op: Lax }.get_cycles(),
 
-            0xB0 => &miscellaneous::Branch { op: bcs }.get_cycles(),
-            0xB1 => &read::IndirectY { op: lda_indirect_y }.get_cycles(),
+            0xB0 => &miscellaneous::Branch { op: Bcs }.get_cycles(),
+            0xB1 => &read::IndirectY { op: LdaIndirectY }.get_cycles(),
             0xB2 => &unofficial::Halt {}.get_cycles(),
-            0xB3 => &read::IndirectY { op: lax_indirect_y }.get_cycles(),
-            0xB4 => &read::ZeroPageX { op: ldy }.get_cycles(),
-            0xB5 => &read::ZeroPageX { op: lda }.get_cycles(),
-            0xB6 => &read::ZeroPageY { op: ldx }.get_cycles(),
-            0xB7 => &read::ZeroPageY { op: lax }.get_cycles(),
-            0xB8 => &single_byte::SingleByte { op: clv }.get_cycles(),
-            0xB9 => &read::AbsoluteY { op: lda_abs_index }.get_cycles(),
-            0xBA => &single_byte::SingleByte { op: tsx }.get_cycles(),
+            0xB3 => &read::IndirectY { op: LaxIndirectY }.get_cycles(),
+            0xB4 => &read::ZeroPageX { op: Ldy }.get_cycles(),
+            0xB5 => &read::ZeroPageX { op: Lda }.get_cycles(),
+            0xB6 => &read::ZeroPageY { op: Ldx }.get_cycles(),
+            0xB7 => &read::ZeroPageY { op: Lax }.get_cycles(),
+            0xB8 => &single_byte::SingleByte { op: Clv }.get_cycles(),
+            0xB9 => &read::AbsoluteY { op: LdaAbsIndex }.get_cycles(),
+            0xBA => &single_byte::SingleByte { op: Tsx }.get_cycles(),
             0xBB => todo!("opcode not yet implemented: {opcode:02X}"),
-            0xBC => &read::AbsoluteX { op: ldy_abs_index }.get_cycles(),
-            0xBD => &read::AbsoluteX { op: lda_abs_index }.get_cycles(),
-            0xBE => &read::AbsoluteY { op: ldx_abs_index }.get_cycles(),
-            0xBF => &read::AbsoluteY { op: lax_abs_index }.get_cycles(),
-
-            0xC0 => &read::Immediate { op: cpy }.get_cycles(),
-            0xC1 => &read::IndirectX { op: cmp }.get_cycles(),
-            0xC2 => &read::Immediate { op: nop }.get_cycles(),
-            0xC3 => &unofficial::IndirectX { op: dcp }.get_cycles(),
-            0xC4 => &read::ZeroPage { op: cpy }.get_cycles(),
-            0xC5 => &read::ZeroPage { op: cmp }.get_cycles(),
-            0xC6 => &read_modify_write::ZeroPage { op: dec }.get_cycles(),
-            0xC7 => &unofficial::ZeroPage { op: dcp }.get_cycles(),
-            0xC8 => &single_byte::SingleByte { op: iny }.get_cycles(),
-            0xC9 => &read::Immediate { op: cmp }.get_cycles(),
-            0xCA => &single_byte::SingleByte { op: dex }.get_cycles(),
+            0xBC => &read::AbsoluteX { op: LdyAbsIndex }.get_cycles(),
+            0xBD => &read::AbsoluteX { op: LdaAbsIndex }.get_cycles(),
+            0xBE => &read::AbsoluteY { op: LdxAbsIndex }.get_cycles(),
+            0xBF => &read::AbsoluteY { op: LaxAbsIndex }.get_cycles(),
+
+            0xC0 => &read::Immediate { op: Cpy }.get_cycles(),
+            0xC1 => &read::IndirectX { op: Cmp }.get_cycles(),
+            0xC2 => &read::Immediate { op: Nop }.get_cycles(),
+            0xC3 => &unofficial::IndirectX { op: Dcp }.get_cycles(),
+            0xC4 => &read::ZeroPage { op: Cpy }.get_cycles(),
+            0xC5 => &read::ZeroPage { op: Cmp }.get_cycles(),
+            0xC6 => &read_modify_write::ZeroPage { op: Dec }.get_cycles(),
+            0xC7 => &unofficial::ZeroPage { op: Dcp }.get_cycles(),
+            0xC8 => &single_byte::SingleByte { op: Iny }.get_cycles(),
+            0xC9 => &read::Immediate { op: Cmp }.get_cycles(),
+            0xCA => &single_byte::SingleByte { op: Dex }.get_cycles(),
             0xCB => todo!("opcode not yet implemented: {opcode:02X}"),
-            0xCC => &read::Absolute { op: cpy }.get_cycles(),
-            0xCD => &read::Absolute { op: cmp }.get_cycles(),
-            0xCE => &read_modify_write::Absolute { op: dec }.get_cycles(),
-            0xCF => &unofficial::Absolute { op: dcp }.get_cycles(),
+            0xCC => &read::Absolute { op: Cpy }.get_cycles(),
+            0xCD => &read::Absolute { op: Cmp }.get_cycles(),
+            0xCE => &read_modify_write::Absolute { op: Dec }.get_cycles(),
+            0xCF => &unofficial::Absolute { op: Dcp }.get_cycles(),
 
-            0xD0 => &miscellaneous::Branch { op: bne }.get_cycles(),
-            0xD1 => &read::IndirectY { op: cmp_indirect_y }.get_cycles(),
+            0xD0 => &miscellaneous::Branch { op: Bne }.get_cycles(),
+            0xD1 => &read::IndirectY { op: CmpIndirectY }.get_cycles(),
             0xD2 => &unofficial::Halt {}.get_cycles(),
-            0xD3 => &unofficial::IndirectY { op: dcp }.get_cycles(),
-            0xD4 => &read::ZeroPageX { op: nop }.get_cycles(),
-            0xD5 => &read::ZeroPageX { op: cmp }.get_cycles(),
-            0xD6 => &read_modify_write::ZeroPageX { op: dec }.get_cycles(),
-            0xD7 => &unofficial::ZeroPageX { op: dcp }.get_cycles(),
-            0xD8 => &single_byte::SingleByte { op: cld }.get_cycles(),
-            0xD9 => &read::AbsoluteY { op: cmp }.get_cycles(),
-            0xDA => &single_byte::SingleByte { op: nop }.get_cycles(),
-            0xDB => &unofficial::AbsoluteY { op: dcp }.get_cycles(),
-            0xDC => &read::AbsoluteX { op: nop_abs_index }.get_cycles(),
-            0xDD => &read::AbsoluteX { op: cmp_abs_index }.get_cycles(),
-            0xDE => &read_modify_write::AbsoluteX { op: dec }.get_cycles(),
-            0xDF => &unofficial::AbsoluteX { op: dcp }.get_cycles(),
-
-            0xE0 => &read::Immediate { op: cpx }.get_cycles(),
-            0xE1 => &read::IndirectX { op: sbc }.get_cycles(),
-            0xE2 => &read::Immediate { op: nop }.get_cycles(),
-            0xE3 => &unofficial::IndirectX { op: isc }.get_cycles(),
-            0xE4 => &read::ZeroPage { op: cpx }.get_cycles(),
-            0xE5 => &read::ZeroPage { op: sbc }.get_cycles(),
-            0xE6 => &read_modify_write::ZeroPage { op: inc }.get_cycles(),
-            0xE7 => &unofficial::ZeroPage { op: isc }.get_cycles(),
-            0xE8 => &single_byte::SingleByte { op: inx }.get_cycles(),
-            0xE9 => &read::Immediate { op: sbc }.get_cycles(),
-            0xEA => &single_byte::SingleByte { op: nop }.get_cycles(),
-            0xEB => &read::Immediate { op: sbc }.get_cycles(),
-            0xEC => &read::Absolute { op: cpx }.get_cycles(),
-            0xED => &read::Absolute { op: sbc }.get_cycles(),
-            0xEE => &read_modify_write::Absolute { op: inc }.get_cycles(),
-            0xEF => &unofficial::Absolute { op: isc }.get_cycles(),
-
-            0xF0 => &miscellaneous::Branch { op: beq }.get_cycles(),
-            0xF1 => &read::IndirectY { op: sbc_indirect_y }.get_cycles(),
+            0xD3 => &unofficial::IndirectY { op: Dcp }.get_cycles(),
+            0xD4 => &read::ZeroPageX { op: Nop }.get_cycles(),
+            0xD5 => &read::ZeroPageX { op: Cmp }.get_cycles(),
+            0xD6 => &read_modify_write::ZeroPageX { op: Dec }.get_cycles(),
+            0xD7 => &unofficial::ZeroPageX { op: Dcp }.get_cycles(),
+            0xD8 => &single_byte::SingleByte { op: Cld }.get_cycles(),
+            0xD9 => &read::AbsoluteY { op: Cmp }.get_cycles(),
+            0xDA => &single_byte::SingleByte { op: Nop }.get_cycles(),
+            0xDB => &unofficial::AbsoluteY { op: Dcp }.get_cycles(),
+            0xDC => &read::AbsoluteX { op: NopAbsIndex }.get_cycles(),
+            0xDD => &read::AbsoluteX { op: CmpAbsIndex }.get_cycles(),
+            0xDE => &read_modify_write::AbsoluteX { op: Dec }.get_cycles(),
+            0xDF => &unofficial::AbsoluteX { op: Dcp }.get_cycles(),
+
+            0xE0 => &read::Immediate { op: Cpx }.get_cycles(),
+            0xE1 => &read::IndirectX { op: Sbc }.get_cycles(),
+            0xE2 => &read::Immediate { op: Nop }.get_cycles(),
+            0xE3 => &unofficial::IndirectX { op: Isc }.get_cycles(),
+            0xE4 => &read::ZeroPage { op: Cpx }.get_cycles(),
+            0xE5 => &read::ZeroPage { op: Sbc }.get_cycles(),
+            0xE6 => &read_modify_write::ZeroPage { op: Inc }.get_cycles(),
+            0xE7 => &unofficial::ZeroPage { op: Isc }.get_cycles(),
+            0xE8 => &single_byte::SingleByte { op: Inx }.get_cycles(),
+            0xE9 => &read::Immediate { op: Sbc }.get_cycles(),
+            0xEA => &single_byte::SingleByte { op: Nop }.get_cycles(),
+            0xEB => &read::Immediate { op: Sbc }.get_cycles(),
+            0xEC => &read::Absolute { op: Cpx }.get_cycles(),
+            0xED => &read::Absolute { op: Sbc }.get_cycles(),
+            0xEE => &read_modify_write::Absolute { op: Inc }.get_cycles(),
+            0xEF => &unofficial::Absolute { op: Isc }.get_cycles(),
+
+            0xF0 => &miscellaneous::Branch { op: Beq }.get_cycles(),
+            0xF1 => &read::IndirectY { op: SbcIndirectY }.get_cycles(),
             0xF2 => &unofficial::Halt {}.get_cycles(),
-            0xF3 => &unofficial::IndirectY { op: isc }.get_cycles(),
-            0xF4 => &read::ZeroPageX { op: nop }.get_cycles(),
-            0xF5 => &read::ZeroPageX { op: sbc }.get_cycles(),
-            0xF6 => &read_modify_write::ZeroPageX { op: inc }.get_cycles(),
-            0xF7 => &unofficial::ZeroPageX { op: isc }.get_cycles(),
-            0xF8 => &single_byte::SingleByte { op: sed }.get_cycles(),
-            0xF9 => &read::AbsoluteY { op: sbc }.get_cycles(),
-            0xFA => &single_byte::SingleByte { op: nop }.get_cycles(),
-            0xFB => &unofficial::AbsoluteY { op: isc }.get_cycles(),
-            0xFC => &read::AbsoluteX { op: nop_abs_index }.get_cycles(),
-            0xFD => &read::AbsoluteX { op: sbc_abs_index }.get_cycles(),
-            0xFE => &read_modify_write::AbsoluteX { op: inc }.get_cycles(),
-            0xFF => &unofficial::AbsoluteX { op: isc }.get_cycles(),
+            0xF3 => &unofficial::IndirectY { op: Isc }.get_cycles(),
+            0xF4 => &read::ZeroPageX { op: Nop }.get_cycles(),
+            0xF5 => &read::ZeroPageX { op: Sbc }.get_cycles(),
+            0xF6 => &read_modify_write::ZeroPageX { op: Inc }.get_cycles(),
+            0xF7 => &unofficial::ZeroPageX { op: Isc }.get_cycles(),
+            0xF8 => &single_byte::SingleByte { op: Sed }.get_cycles(),
+            0xF9 => &read::AbsoluteY { op: Sbc }.get_cycles(),
+            0xFA => &single_byte::SingleByte { op: Nop }.get_cycles(),
+            0xFB => &unofficial::AbsoluteY { op: Isc }.get_cycles(),
+            0xFC => &read::AbsoluteX { op: NopAbsIndex }.get_cycles(),
+            0xFD => &read::AbsoluteX { op: SbcAbsIndex }.get_cycles(),
+            0xFE => &read_modify_write::AbsoluteX { op: Inc }.get_cycles(),
+            0xFF => &unofficial::AbsoluteX { op: Isc }.get_cycles(),
         };
         self.cycle_queue.extend(cycles);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::cpu::{
+        cycles::{CYCLE_QUEUE_CAPACITY, CycleQueue, GET_OPCODE},
+        half_cycles::HalfCycle,
+    };
+
+    #[test]
+    fn cycle_queue_wraps_around() {
+        let mut queue = CycleQueue::new();
+        let mut expected = CycleQueue::new();
+        for _ in 0..CYCLE_QUEUE_CAPACITY - 1 {
+            queue.push_back([HalfCycle::Nop, HalfCycle::Nop]);
+        }
+        for _ in 0..CYCLE_QUEUE_CAPACITY - 1 {
+            queue.pop_front();
+        }
+
+        queue.extend(&[GET_OPCODE, [HalfCycle::GetPc, HalfCycle::ReadData]]);
+        expected.extend(&[GET_OPCODE, [HalfCycle::GetPc, HalfCycle::ReadData]]);
+        assert_eq!(queue, expected);
+
+        assert_eq!(queue.pop_front(), Some(GET_OPCODE));
+        assert_eq!(queue.len(), 1);
+        assert_ne!(queue, expected);
+    }
+
+    #[test]
+    fn half_cycles_round_trip_through_bytes() {
+        for byte in 0..=u8::MAX {
+            if let Ok(half_cycle) = HalfCycle::try_from(byte) {
+                assert_eq!(u8::from(half_cycle), byte);
+            }
+        }
+        assert_eq!(
+            HalfCycle::try_from(u8::from(HalfCycle::Sre)),
+            Ok(HalfCycle::Sre)
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     concat_u8,
-    emu::{buses::Buses, cpu::CPU},
+    emu::cpu::{CPU, bus::Bus},
     split_u16,
 };
 
@@ -8,62 +8,237 @@ pub mod operations;
 
 const STACK_PAGE_HIGH_ADDRESS: u8 = 0x01;
 
-pub type HalfCycle = fn(&mut CPU, &mut Buses);
+/// Defines [`HalfCycle`] with a variant for each half-cycle function.
+macro_rules! half_cycles {
+    ($($variant:ident => $function:path,)*) => {
+        /// A micro-op executed during one half of a CPU cycle. Each variant
+        /// runs the half-cycle function of the same name. Half-cycles are plain
+        /// data, so the CPU's cycle queue can be copied, compared and saved.
+        #[repr(u8)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub enum HalfCycle {
+            $($variant,)*
+        }
+
+        impl HalfCycle {
+            /// Every half-cycle, in the order of their discriminants.
+            const ALL: &[HalfCycle] = &[$(HalfCycle::$variant,)*];
+
+            /// Executes the half-cycle.
+            pub fn run(self, cpu: &mut CPU, buses: &mut impl Bus) {
+                match self {
+                    $(HalfCycle::$variant => $function(cpu, buses),)*
+                }
+            }
+        }
+    };
+}
+
+half_cycles! {
+    GetPcWithInc => get_pc_with_inc,
+    GetPc => get_pc,
+    GetSp => get_sp,
+    PushStack => push_stack,
+    PopStack => pop_stack,
+    GetEffectiveAddr => get_effective_addr,
+    GetEffectiveZeroPageAddr => get_effective_zero_page_addr,
+    GetBaseZeroPageAddr => get_base_zero_page_addr,
+    GetBaseZeroPageXIndexedAddr => get_base_zero_page_x_indexed_addr,
+    GetBaseZeroPageXIndexedAddrHighByte => get_base_zero_page_x_indexed_addr_high_byte,
+    GetBaseZeroPageYIndexedAddr => get_base_zero_page_y_indexed_addr,
+    GetIndirectAddr => get_indirect_addr,
+    GetIndirectAddrHighByte => get_indirect_addr_high_byte,
+    GetIndirectZeroPageAddr => get_indirect_zero_page_addr,
+    GetIndirectZeroPageAddrHighByte => get_indirect_zero_page_addr_high_byte,
+    GetIndirectYIndexedAddr => get_indirect_y_indexed_addr,
+    GetBaseAddrXIndexedWithCarry => get_base_addr_x_indexed_with_carry,
+    GetBaseAddrYIndexedWithCarry => get_base_addr_y_indexed_with_carry,
+    GetIrqVectorLowByte => get_irq_vector_low_byte,
+    GetIrqVectorHighByte => get_irq_vector_high_byte,
+    GetNmiVectorLowByte => get_nmi_vector_low_byte,
+    GetNmiVectorHighByte => get_nmi_vector_high_byte,
+    ReadOpcode => read_opcode,
+    ReadPcHighByte => read_pc_high_byte,
+    ReadPcLowByte => read_pc_low_byte,
+    ReadEffectiveAddrHighByte => read_effective_addr_high_byte,
+    ReadEffectiveAddrLowByte => read_effective_addr_low_byte,
+    ReadBaseAddrHighByte => read_base_addr_high_byte,
+    ReadBaseAddrLowByte => read_base_addr_low_byte,
+    ReadIndirectAddrHighByte => read_indirect_addr_high_byte,
+    ReadIndirectAddrLowByte => read_indirect_addr_low_byte,
+    ReadData => read_data,
+    WriteData => write_data,
+    WritePcHighByte => write_pc_high_byte,
+    WritePcLowByte => write_pc_low_byte,
+    WriteBreakStatus => write_break_status,
+
+    Lda => operations::access::lda,
+    LdaIndirectY => operations::access::lda_indirect_y,
+    LdaAbsIndex => operations::access::lda_abs_index,
+    Ldx => operations::access::ldx,
+    LdxAbsIndex => operations::access::ldx_abs_index,
+    Ldy => operations::access::ldy,
+    LdyAbsIndex => operations::access::ldy_abs_index,
+    Sta => operations::access::sta,
+    Stx => operations::access::stx,
+    Sty => operations::access::sty,
+
+    Inc => operations::arithmetic::inc,
+    Inx => operations::arithmetic::inx,
+    Iny => operations::arithmetic::iny,
+    Dec => operations::arithmetic::dec,
+    Dex => operations::arithmetic::dex,
+    Dey => operations::arithmetic::dey,
+    Adc => operations::arithmetic::adc,
+    AdcIndirectY => operations::arithmetic::adc_indirect_y,
+    AdcAbsIndex => operations::arithmetic::adc_abs_index,
+    Sbc => operations::arithmetic::sbc,
+    SbcIndirectY => operations::arithmetic::sbc_indirect_y,
+    SbcAbsIndex => operations::arithmetic::sbc_abs_index,
+
+    And => operations::bitwise::and,
+    AndIndirectY => operations::bitwise::and_indirect_y,
+    AndAbsIndex => operations::bitwise::and_abs_index,
+    Bit => operations::bitwise::bit,
+    Eor => operations::bitwise::eor,
+    EorIndirectY => operations::bitwise::eor_indirect_y,
+    EorAbsIndex => operations::bitwise::eor_abs_index,
+    Ora => operations::bitwise::ora,
+    OraIndirectY => operations::bitwise::ora_indirect_y,
+    OraAbsIndex => operations::bitwise::ora_abs_index,
+
+    Bcs => operations::branch::bcs,
+    Bcc => operations::branch::bcc,
+    Beq => operations::branch::beq,
+    Bne => operations::branch::bne,
+    Bmi => operations::branch::bmi,
+    Bpl => operations::branch::bpl,
+    Bvs => operations::branch::bvs,
+    Bvc => operations::branch::bvc,
+
+    Cmp => operations::compare::cmp,
+    CmpIndirectY => operations::compare::cmp_indirect_y,
+    CmpAbsIndex => operations::compare::cmp_abs_index,
+    Cpx => operations::compare::cpx,
+    Cpy => operations::compare::cpy,
+
+    Sec => operations::flags::sec,
+    Clc => operations::flags::clc,
+    Sed => operations::flags::sed,
+    Cld => operations::flags::cld,
+    Sei => operations::flags::sei,
+    Cli => operations::flags::cli,
+    Clv => operations::flags::clv,
+
+    Jsr => operations::jump::jsr,
+    JmpAbsolute => operations::jump::jmp_absolute,
+    Rti => operations::jump::rti,
+
+    Nop => operations::other::nop,
+    NopAbsIndex => operations::other::nop_abs_index,
+    Jam => operations::other::jam,
+
+    AslM => operations::shift::asl_m,
+    AslA => operations::shift::asl_a,
+    LsrM => operations::shift::lsr_m,
+    LsrA => operations::shift::lsr_a,
+    Rol => operations::shift::rol,
+    RolA => operations::shift::rol_a,
+    RorM => operations::shift::ror_m,
+    RorA => operations::shift::ror_a,
+
+    Pha => operations::stack::pha,
+    Php => operations::stack::php,
+    Pla => operations::stack::pla,
+    Plp => operations::stack::plp,
+
+    Tax => operations::transfer::tax,
+    Tay => operations::transfer::tay,
+    Tsx => operations::transfer::tsx,
+    Txa => operations::transfer::txa,
+    Txs => operations::transfer::txs,
+    Tya => operations::transfer::tya,
+
+    Lax => operations::unofficial::lax,
+    LaxIndirectY => operations::unofficial::lax_indirect_y,
+    LaxAbsIndex => operations::unofficial::lax_abs_index,
+    Sax => operations::unofficial::sax,
+    Dcp => operations::unofficial::dcp,
+    Isc => operations::unofficial::isc,
+    Rla => operations::unofficial::rla,
+    Rra => operations::unofficial::rra,
+    Slo => operations::unofficial::slo,
+    Sre => operations::unofficial::sre,
+}
+
+impl From<HalfCycle> for u8 {
+    fn from(value: HalfCycle) -> u8 {
+        value as u8
+    }
+}
+
+impl TryFrom<u8> for HalfCycle {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        HalfCycle::ALL.get(value as usize).copied().ok_or(value)
+    }
+}
 
 /// Loads the program counter onto the address bus and then increments the
 /// program counter.
-pub fn get_pc_with_inc(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = cpu.registers.pc;
+pub fn get_pc_with_inc(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr(cpu.registers.pc);
     let new_pc = concat_u8!(cpu.registers.pc.0, cpu.registers.pc.1).wrapping_add(1);
     cpu.registers.pc = split_u16!(new_pc);
 }
 
 /// Loads the program counter onto the address bus.
-pub fn get_pc(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = cpu.registers.pc;
+pub fn get_pc(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr(cpu.registers.pc);
 }
 
 /// Loads the stack pointer onto the address bus.
-pub fn get_sp(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (STACK_PAGE_HIGH_ADDRESS, cpu.registers.sp);
+pub fn get_sp(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((STACK_PAGE_HIGH_ADDRESS, cpu.registers.sp));
 }
 
 /// Loads the stack pointer onto the address bus and then decrements the stack
 /// pointer.
-pub fn push_stack(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (STACK_PAGE_HIGH_ADDRESS, cpu.registers.sp);
+pub fn push_stack(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((STACK_PAGE_HIGH_ADDRESS, cpu.registers.sp));
     cpu.registers.sp = cpu.registers.sp.wrapping_sub(1);
 }
 
 /// Loads the stack pointer onto the address bus and then increments the stack
 /// pointer.
-pub fn pop_stack(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (STACK_PAGE_HIGH_ADDRESS, cpu.registers.sp);
+pub fn pop_stack(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((STACK_PAGE_HIGH_ADDRESS, cpu.registers.sp));
     cpu.registers.sp = cpu.registers.sp.wrapping_add(1);
 }
 
 /// Loads the effective address onto the address bus.
-pub fn get_effective_addr(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = cpu.buses.effective_addr;
+pub fn get_effective_addr(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr(cpu.buses.effective_addr);
 }
 
 /// Loads the effective zero page address onto the address bus.
-pub fn get_effective_zero_page_addr(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (0x00, cpu.buses.effective_addr.1);
+pub fn get_effective_zero_page_addr(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((0x00, cpu.buses.effective_addr.1));
 }
 
 /// Loads the base zero page address onto the address bus.
-pub fn get_base_zero_page_addr(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (0x00, cpu.buses.base_addr.1);
+pub fn get_base_zero_page_addr(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((0x00, cpu.buses.base_addr.1));
 }
 
 /// Loads the base zero page address onto the address bus, offset by the
 /// X index register.
-pub fn get_base_zero_page_x_indexed_addr(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (
+pub fn get_base_zero_page_x_indexed_addr(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((
         0x00,
         cpu.buses.base_addr.1.wrapping_add(cpu.registers.x_index),
-    );
+    ));
 }
 
 /// Loads the base zero page address onto the address bus, offset by the X index
@@ -71,191 +246,191 @@ pub fn get_base_zero_page_x_indexed_addr(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Used in indirect X addressing to retrieve the high order byte of the
 /// effective address.
-pub fn get_base_zero_page_x_indexed_addr_high_byte(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (
+pub fn get_base_zero_page_x_indexed_addr_high_byte(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((
         0x00,
         cpu.buses
             .base_addr
             .1
             .wrapping_add(cpu.registers.x_index)
             .wrapping_add(1),
-    );
+    ));
 }
 
 /// Loads the base zero page address onto the address bus, offset by the Y index
 /// register.
-pub fn get_base_zero_page_y_indexed_addr(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (
+pub fn get_base_zero_page_y_indexed_addr(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((
         0x00,
         cpu.buses.base_addr.1.wrapping_add(cpu.registers.y_index),
-    );
+    ));
 }
 
 /// Loads the indirect address onto the address bus.
-pub fn get_indirect_addr(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (cpu.buses.indirect_addr.0, cpu.buses.indirect_addr.1);
+pub fn get_indirect_addr(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((cpu.buses.indirect_addr.0, cpu.buses.indirect_addr.1));
 }
 
 /// Loads the indirect address plus one onto the address bus.
 ///
 /// Used in the indirect jump instruction to retrieve the high order byte of the
 /// indirect address.
-pub fn get_indirect_addr_high_byte(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (
+pub fn get_indirect_addr_high_byte(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((
         cpu.buses.indirect_addr.0,
         cpu.buses.indirect_addr.1.wrapping_add(1),
-    );
+    ));
 }
 
 /// Loads the indirect zero page address onto the address bus.
-pub fn get_indirect_zero_page_addr(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (0x00, cpu.buses.indirect_addr.1);
+pub fn get_indirect_zero_page_addr(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((0x00, cpu.buses.indirect_addr.1));
 }
 
 /// Loads the indirect zero page address plus one onto the address bus.
 ///
 /// Used in indirect Y addressing to retrieve the high order byte of the
 /// effective address.
-pub fn get_indirect_zero_page_addr_high_byte(cpu: &mut CPU, buses: &mut Buses) {
-    buses.addr = (0x00, cpu.buses.indirect_addr.1.wrapping_add(1));
+pub fn get_indirect_zero_page_addr_high_byte(cpu: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((0x00, cpu.buses.indirect_addr.1.wrapping_add(1)));
 }
 
 /// Loads the base address offset by the Y index register onto both the address
 /// bus and the effective address.
-pub fn get_indirect_y_indexed_addr(cpu: &mut CPU, buses: &mut Buses) {
+pub fn get_indirect_y_indexed_addr(cpu: &mut CPU, buses: &mut impl Bus) {
     let new_effective_addr = (
         cpu.buses.base_addr.0,
         cpu.buses.base_addr.1.wrapping_add(cpu.registers.y_index),
     );
 
     cpu.buses.effective_addr = new_effective_addr;
-    buses.addr = new_effective_addr;
+    buses.set_addr(new_effective_addr);
 }
 
 /// Adds the X index register to the base address, storing the result in the
 /// address bus and effective address.
-pub fn get_base_addr_x_indexed_with_carry(cpu: &mut CPU, buses: &mut Buses) {
+pub fn get_base_addr_x_indexed_with_carry(cpu: &mut CPU, buses: &mut impl Bus) {
     let (base_address_high, base_address_low) = cpu.buses.base_addr;
     let (effective_address_low, overflow) = base_address_low.overflowing_add(cpu.registers.x_index);
     let effective_address_high = base_address_high.wrapping_add(overflow as u8);
 
-    buses.addr = (base_address_high, effective_address_low);
+    buses.set_addr((base_address_high, effective_address_low));
     cpu.buses.effective_addr = (effective_address_high, effective_address_low);
     cpu.crossed_page = overflow;
 }
 
 /// Adds the Y index register to the base address, storing the result in the
 /// address bus and effective address.
-pub fn get_base_addr_y_indexed_with_carry(cpu: &mut CPU, buses: &mut Buses) {
+pub fn get_base_addr_y_indexed_with_carry(cpu: &mut CPU, buses: &mut impl Bus) {
     let (base_address_high, base_address_low) = cpu.buses.base_addr;
     let (effective_address_low, overflow) = base_address_low.overflowing_add(cpu.registers.y_index);
     let effective_address_high = base_address_high.wrapping_add(overflow as u8);
 
-    buses.addr = (base_address_high, effective_address_low);
+    buses.set_addr((base_address_high, effective_address_low));
     cpu.buses.effective_addr = (effective_address_high, effective_address_low);
     cpu.crossed_page = overflow;
 }
 
 /// Loads the IRQ vector's low byte onto the address bus.
-pub fn get_irq_vector_low_byte(_: &mut CPU, buses: &mut Buses) {
-    buses.addr = (0xFF, 0xFE);
+pub fn get_irq_vector_low_byte(_: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((0xFF, 0xFE));
 }
 
 /// Loads the IRQ vector's high byte onto the address bus.
-pub fn get_irq_vector_high_byte(_: &mut CPU, buses: &mut Buses) {
-    buses.addr = (0xFF, 0xFF);
+pub fn get_irq_vector_high_byte(_: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((0xFF, 0xFF));
 }
 
 /// Loads the NMI vector's low byte onto the address bus.
-pub fn get_nmi_vector_low_byte(_: &mut CPU, buses: &mut Buses) {
-    buses.addr = (0xFF, 0xFA);
+pub fn get_nmi_vector_low_byte(_: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((0xFF, 0xFA));
 }
 
 /// Loads the NMI vector's high byte onto the address bus.
-pub fn get_nmi_vector_high_byte(_: &mut CPU, buses: &mut Buses) {
-    buses.addr = (0xFF, 0xFB)
+pub fn get_nmi_vector_high_byte(_: &mut CPU, buses: &mut impl Bus) {
+    buses.set_addr((0xFF, 0xFB));
 }
 
 /// Reads the byte addressed by the address bus and places it onto both the data
 /// bus and the instruction register.
-pub fn read_opcode(cpu: &mut CPU, buses: &mut Buses) {
+pub fn read_opcode(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.registers.ir = buses.read();
 }
 
 /// Reads the byte addressed by the address bus and places it onto both the data
 /// bus and the program counter high byte.
-pub fn read_pc_high_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn read_pc_high_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.registers.pc.0 = buses.read();
 }
 
 /// Reads the byte addressed by the address bus and places it onto both the data
 /// bus and the program counter low byte.
-pub fn read_pc_low_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn read_pc_low_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.registers.pc.1 = buses.read();
 }
 
 /// Reads the byte addressed by the address bus and places it onto both the data
 /// bus and the effective address high byte.
-pub fn read_effective_addr_high_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn read_effective_addr_high_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.buses.effective_addr.0 = buses.read();
 }
 
 /// Reads the byte addressed by the address bus and places it onto both the data
 /// bus and the effective address low byte.
-pub fn read_effective_addr_low_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn read_effective_addr_low_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.buses.effective_addr.1 = buses.read();
 }
 
 /// Reads the byte addressed by the address bus and places it onto both the data
 /// bus and the base address high byte.
-pub fn read_base_addr_high_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn read_base_addr_high_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.buses.base_addr.0 = buses.read();
 }
 
 /// Reads the byte addressed by the address bus and places it onto both the data
 /// bus and the base address low byte.
-pub fn read_base_addr_low_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn read_base_addr_low_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.buses.base_addr.1 = buses.read();
 }
 
 /// Reads the byte addressed by the address bus and places it onto both the data
 /// bus and the indirect address high byte.
-pub fn read_indirect_addr_high_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn read_indirect_addr_high_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.buses.indirect_addr.0 = buses.read();
 }
 
 /// Reads the byte addressed by the address bus and places it onto both the data
 /// bus and the indirect address low byte.
-pub fn read_indirect_addr_low_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn read_indirect_addr_low_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.buses.indirect_addr.1 = buses.read();
 }
 
 /// Reads the byte addressed by the address bus and places it onto the data bus.
-pub fn read_data(_: &mut CPU, buses: &mut Buses) {
+pub fn read_data(_: &mut CPU, buses: &mut impl Bus) {
     buses.read();
 }
 
 /// Writes the byte on the data bus into the memory location addressed by the
 /// address bus.
-pub fn write_data(_: &mut CPU, buses: &mut Buses) {
-    buses.write(buses.data);
+pub fn write_data(_: &mut CPU, buses: &mut impl Bus) {
+    buses.write(buses.get_data());
 }
 
 /// Writes the program counter high byte into the memory location addressed by
 /// the address bus.
-pub fn write_pc_high_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn write_pc_high_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     buses.write(cpu.registers.pc.0);
 }
 
 /// Writes the program counter low byte into the memory location addressed by
 /// the address bus.
-pub fn write_pc_low_byte(cpu: &mut CPU, buses: &mut Buses) {
+pub fn write_pc_low_byte(cpu: &mut CPU, buses: &mut impl Bus) {
     buses.write(cpu.registers.pc.1);
 }
 
 /// Writes the processor status register byte into the memory location addressed
 /// by the address bus, then sets the interrupt disable flag.
-pub fn write_break_status(cpu: &mut CPU, buses: &mut Buses) {
+pub fn write_break_status(cpu: &mut CPU, buses: &mut impl Bus) {
     buses.write(cpu.registers.psr | 0b_0011_0000);
     cpu.registers.psr.set_interrupt_disable(true);
 }
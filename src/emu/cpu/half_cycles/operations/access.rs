@@ -1,12 +1,9 @@
-use crate::emu::{
-    buses::Buses,
-    cpu::{CPU, half_cycles::get_effective_addr, registers::flags::Flags},
-};
+use crate::emu::cpu::{CPU, bus::Bus, half_cycles::HalfCycle, registers::flags::Flags};
 
 /// # Load Accumulator
 ///
 /// Loads a memory value into the accumulator.
-pub fn lda(cpu: &mut CPU, buses: &mut Buses) {
+pub fn lda(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
 
     cpu.registers.a = data;
@@ -18,11 +15,12 @@ pub fn lda(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Loads a memory value into the accumulator. Uses an additional cycle if a
 /// page is crossed.
-pub fn lda_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
+pub fn lda_indirect_y(cpu: &mut CPU, buses: &mut impl Bus) {
     lda(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, lda]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Lda]);
     }
 }
 
@@ -30,18 +28,19 @@ pub fn lda_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Loads a memory value into the accumulator. Uses an additional cycle if a
 /// page is crossed.
-pub fn lda_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn lda_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     lda(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, lda]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Lda]);
     }
 }
 
 /// # Load X Register
 ///
 /// Loads a memory value into the X register.
-pub fn ldx(cpu: &mut CPU, buses: &mut Buses) {
+pub fn ldx(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
 
     cpu.registers.x_index = data;
@@ -53,18 +52,19 @@ pub fn ldx(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Loads a memory value into the X register. Uses an additional cycle if a page
 /// is crossed.
-pub fn ldx_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn ldx_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     ldx(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, ldx]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Ldx]);
     }
 }
 
 /// # Load Y Register
 ///
 /// Loads a memory value into the Y register.
-pub fn ldy(cpu: &mut CPU, buses: &mut Buses) {
+pub fn ldy(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
 
     cpu.registers.y_index = data;
@@ -76,18 +76,19 @@ pub fn ldy(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Loads a memory value into the Y register. Uses an additional cycle if a page
 /// is crossed.
-pub fn ldy_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn ldy_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     ldy(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, ldy]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Ldy]);
     }
 }
 
 /// # Store Accumulator
 ///
 /// Stores the accumulator's value into memory.
-pub fn sta(cpu: &mut CPU, buses: &mut Buses) {
+pub fn sta(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = cpu.registers.a;
 
     buses.write(data)
@@ -96,7 +97,7 @@ pub fn sta(cpu: &mut CPU, buses: &mut Buses) {
 /// # Store X Register
 ///
 /// Stores the X register's value into memory.
-pub fn stx(cpu: &mut CPU, buses: &mut Buses) {
+pub fn stx(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = cpu.registers.x_index;
 
     buses.write(data);
@@ -105,7 +106,7 @@ pub fn stx(cpu: &mut CPU, buses: &mut Buses) {
 /// # Store Y Register
 ///
 /// Stores the Y register's value into memory.
-pub fn sty(cpu: &mut CPU, buses: &mut Buses) {
+pub fn sty(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = cpu.registers.y_index;
 
     buses.write(data);
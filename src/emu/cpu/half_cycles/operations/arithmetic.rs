@@ -1,15 +1,12 @@
 use crate::{
     did_signed_overflow,
-    emu::{
-        buses::Buses,
-        cpu::{CPU, half_cycles::get_effective_addr, registers::flags::Flags},
-    },
+    emu::cpu::{CPU, bus::Bus, half_cycles::HalfCycle, registers::flags::Flags},
 };
 
 /// # Increment Memory
 ///
 /// Adds 1 to a memory value.
-pub fn inc(cpu: &mut CPU, buses: &mut Buses) {
+pub fn inc(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = data.wrapping_add(1);
 
@@ -21,7 +18,7 @@ pub fn inc(cpu: &mut CPU, buses: &mut Buses) {
 /// # Increment X Register
 ///
 /// Adds 1 to the X register.
-pub fn inx(cpu: &mut CPU, _: &mut Buses) {
+pub fn inx(cpu: &mut CPU, _: &mut impl Bus) {
     let data = cpu.registers.x_index;
     let result = data.wrapping_add(1);
 
@@ -33,7 +30,7 @@ pub fn inx(cpu: &mut CPU, _: &mut Buses) {
 /// # Increment Y Register
 ///
 /// Adds 1 to the Y register.
-pub fn iny(cpu: &mut CPU, _: &mut Buses) {
+pub fn iny(cpu: &mut CPU, _: &mut impl Bus) {
     let data = cpu.registers.y_index;
     let result = data.wrapping_add(1);
 
@@ -45,7 +42,7 @@ pub fn iny(cpu: &mut CPU, _: &mut Buses) {
 /// # Decrement Memory
 ///
 /// Subtracts 1 from a memory value.
-pub fn dec(cpu: &mut CPU, buses: &mut Buses) {
+pub fn dec(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = data.wrapping_sub(1);
 
@@ -57,7 +54,7 @@ pub fn dec(cpu: &mut CPU, buses: &mut Buses) {
 /// # Decrement X Register
 ///
 /// Subtracts 1 from the X register.
-pub fn dex(cpu: &mut CPU, _: &mut Buses) {
+pub fn dex(cpu: &mut CPU, _: &mut impl Bus) {
     let data = cpu.registers.x_index;
     let result = data.wrapping_sub(1);
 
@@ -69,7 +66,7 @@ pub fn dex(cpu: &mut CPU, _: &mut Buses) {
 /// # Decrement Y Register
 ///
 /// Subtracts 1 from the Y register.
-pub fn dey(cpu: &mut CPU, _: &mut Buses) {
+pub fn dey(cpu: &mut CPU, _: &mut impl Bus) {
     let data = cpu.registers.y_index;
     let result = data.wrapping_sub(1);
 
@@ -81,7 +78,7 @@ pub fn dey(cpu: &mut CPU, _: &mut Buses) {
 /// # Add With Carry
 ///
 /// Adds a memory value and the carry flag to the accumulator.
-pub fn adc(cpu: &mut CPU, buses: &mut Buses) {
+pub fn adc(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let accumulator = cpu.registers.a;
     let (sum, overflow1) = accumulator.overflowing_add(data);
@@ -100,11 +97,12 @@ pub fn adc(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Adds a memory value and the carry flag to the accumulator. Uses an
 /// additional cycle if a page is crossed.
-pub fn adc_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
+pub fn adc_indirect_y(cpu: &mut CPU, buses: &mut impl Bus) {
     adc(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, adc]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Adc]);
     }
 }
 
@@ -112,11 +110,12 @@ pub fn adc_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Adds a memory value and the carry flag to the accumulator. Uses an
 /// additional cycle if a page is crossed.
-pub fn adc_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn adc_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     adc(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, adc]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Adc]);
     }
 }
 
@@ -124,7 +123,7 @@ pub fn adc_abs_index(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Subtracts a memory value and the bitwise NOT of the carry flag from the
 /// accumulator.
-pub fn sbc(cpu: &mut CPU, buses: &mut Buses) {
+pub fn sbc(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let accumulator = cpu.registers.a;
     let (sum, overflow1) = accumulator.overflowing_add(!data);
@@ -143,11 +142,12 @@ pub fn sbc(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Subtracts a memory value and the bitwise NOT of the carry flag from the
 /// accumulator. Uses an additional cycle if a page is crossed.
-pub fn sbc_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
+pub fn sbc_indirect_y(cpu: &mut CPU, buses: &mut impl Bus) {
     sbc(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, sbc]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Sbc]);
     }
 }
 
@@ -155,10 +155,11 @@ pub fn sbc_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Subtracts a memory value and the bitwise NOT of the carry flag from the
 /// accumulator. Uses an additional cycle if a page is crossed.
-pub fn sbc_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn sbc_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     sbc(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, sbc]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Sbc]);
     }
 }
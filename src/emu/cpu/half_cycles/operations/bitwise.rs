@@ -1,12 +1,9 @@
-use crate::emu::{
-    buses::Buses,
-    cpu::{CPU, half_cycles::get_effective_addr, registers::flags::Flags},
-};
+use crate::emu::cpu::{CPU, bus::Bus, half_cycles::HalfCycle, registers::flags::Flags};
 
 /// # Bitwise AND
 ///
 /// Bitwise AND of a memory value and the accumulator.
-pub fn and(cpu: &mut CPU, buses: &mut Buses) {
+pub fn and(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = cpu.registers.a & data;
 
@@ -19,11 +16,12 @@ pub fn and(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Bitwise AND of a memory value and the accumulator. Uses an additional cycle
 /// if a page is crossed.
-pub fn and_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
+pub fn and_indirect_y(cpu: &mut CPU, buses: &mut impl Bus) {
     and(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, and]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::And]);
     }
 }
 
@@ -31,11 +29,12 @@ pub fn and_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Bitwise AND of a memory value and the accumulator. Uses an additional cycle
 /// if a page is crossed.
-pub fn and_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn and_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     and(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, and]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::And]);
     }
 }
 
@@ -46,7 +45,7 @@ pub fn and_abs_index(cpu: &mut CPU, buses: &mut Buses) {
 ///   accumulator and a memory value.
 /// * Loads bit six from the memory value into the overflow flag.
 /// * Loads bit seven from the memory value into the negative flag.
-pub fn bit(cpu: &mut CPU, buses: &mut Buses) {
+pub fn bit(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = cpu.registers.a & data;
 
@@ -58,7 +57,7 @@ pub fn bit(cpu: &mut CPU, buses: &mut Buses) {
 /// # Bitwise Exclusive OR
 ///
 /// Bitwise XOR of a memory value and the accumulator.
-pub fn eor(cpu: &mut CPU, buses: &mut Buses) {
+pub fn eor(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = cpu.registers.a ^ data;
 
@@ -71,11 +70,12 @@ pub fn eor(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Bitwise XOR of a memory value and the accumulator. Uses an additional cycle
 /// if a page is crossed.
-pub fn eor_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
+pub fn eor_indirect_y(cpu: &mut CPU, buses: &mut impl Bus) {
     eor(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, eor]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Eor]);
     }
 }
 
@@ -83,18 +83,19 @@ pub fn eor_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Bitwise XOR of a memory value and the accumulator. Uses an additional cycle
 /// if a page is crossed.
-pub fn eor_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn eor_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     eor(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, eor]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Eor]);
     }
 }
 
 /// # Bitwise OR
 ///
 /// Bitwise OR of a memory value and the accumulator.
-pub fn ora(cpu: &mut CPU, buses: &mut Buses) {
+pub fn ora(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = cpu.registers.a | data;
 
@@ -107,11 +108,12 @@ pub fn ora(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Bitwise OR of a memory value and the accumulator. Uses an additional cycle
 /// if a page crossed.
-pub fn ora_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
+pub fn ora_indirect_y(cpu: &mut CPU, buses: &mut impl Bus) {
     ora(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, ora]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Ora]);
     }
 }
 
@@ -119,10 +121,11 @@ pub fn ora_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Bitwise OR of a memory value and the accumulator. Uses an additional cycle
 /// if a page crossed.
-pub fn ora_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn ora_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     ora(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, ora]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Ora]);
     }
 }
@@ -1,13 +1,10 @@
-use crate::emu::{
-    buses::Buses,
-    cpu::{CPU, half_cycles::operations::other::nop},
-};
+use crate::emu::cpu::{CPU, bus::Bus, half_cycles::HalfCycle};
 
 /// # Branch If Carry Set
 ///
 /// If the carry flag is set, branch to a nearby location by adding the branch
 /// offset to the program counter.
-pub fn bcs(cpu: &mut CPU, buses: &mut Buses) {
+pub fn bcs(cpu: &mut CPU, buses: &mut impl Bus) {
     do_branch(cpu, buses, cpu.registers.psr.get_carry());
 }
 
@@ -15,7 +12,7 @@ pub fn bcs(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// If the carry flag is clear, branch to a nearby location by adding the branch
 /// offset to the program counter.
-pub fn bcc(cpu: &mut CPU, buses: &mut Buses) {
+pub fn bcc(cpu: &mut CPU, buses: &mut impl Bus) {
     do_branch(cpu, buses, !cpu.registers.psr.get_carry())
 }
 
@@ -23,7 +20,7 @@ pub fn bcc(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// If the zero flag is set, branch to a nearby location by adding the branch
 /// offset to the program counter.
-pub fn beq(cpu: &mut CPU, buses: &mut Buses) {
+pub fn beq(cpu: &mut CPU, buses: &mut impl Bus) {
     do_branch(cpu, buses, cpu.registers.psr.get_zero())
 }
 
@@ -31,7 +28,7 @@ pub fn beq(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// If the zero flag is clear, branch to a nearby location by adding the branch
 /// offset to the program counter.
-pub fn bne(cpu: &mut CPU, buses: &mut Buses) {
+pub fn bne(cpu: &mut CPU, buses: &mut impl Bus) {
     do_branch(cpu, buses, !cpu.registers.psr.get_zero())
 }
 
@@ -39,7 +36,7 @@ pub fn bne(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// If the negative flag is set, branch to a nearby location by adding the
 /// branch offset to the program counter.
-pub fn bmi(cpu: &mut CPU, buses: &mut Buses) {
+pub fn bmi(cpu: &mut CPU, buses: &mut impl Bus) {
     do_branch(cpu, buses, cpu.registers.psr.get_negative())
 }
 
@@ -47,7 +44,7 @@ pub fn bmi(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// If the negative flag is clear, branch to a nearby location by adding the
 /// branch offset to the program counter.
-pub fn bpl(cpu: &mut CPU, buses: &mut Buses) {
+pub fn bpl(cpu: &mut CPU, buses: &mut impl Bus) {
     do_branch(cpu, buses, !cpu.registers.psr.get_negative())
 }
 
@@ -55,7 +52,7 @@ pub fn bpl(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// If the overflow flag is set, branch to a nearby location by adding the
 /// branch offset to the program counter.
-pub fn bvs(cpu: &mut CPU, buses: &mut Buses) {
+pub fn bvs(cpu: &mut CPU, buses: &mut impl Bus) {
     do_branch(cpu, buses, cpu.registers.psr.get_overflow())
 }
 
@@ -63,11 +60,11 @@ pub fn bvs(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// If the overflow flag is clear, branch to a nearby location by adding the
 /// branch offset to the program counter.
-pub fn bvc(cpu: &mut CPU, buses: &mut Buses) {
+pub fn bvc(cpu: &mut CPU, buses: &mut impl Bus) {
     do_branch(cpu, buses, !cpu.registers.psr.get_overflow())
 }
 
-fn do_branch(cpu: &mut CPU, buses: &mut Buses, condition: bool) {
+fn do_branch(cpu: &mut CPU, buses: &mut impl Bus, condition: bool) {
     let offset = buses.read() as i8;
 
     if !condition {
@@ -89,10 +86,10 @@ fn do_branch(cpu: &mut CPU, buses: &mut Buses, condition: bool) {
 
     // TODO: Don't use NOPs below, actually do something.
 
-    cpu.cycle_queue.push_back([nop, nop]);
+    cpu.cycle_queue.push_back([HalfCycle::Nop, HalfCycle::Nop]);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([nop, nop]);
+        cpu.cycle_queue.push_back([HalfCycle::Nop, HalfCycle::Nop]);
     }
 
     cpu.registers.pc = (pch_offset, pcl_offset)
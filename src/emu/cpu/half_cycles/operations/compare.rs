@@ -1,12 +1,9 @@
-use crate::emu::{
-    buses::Buses,
-    cpu::{CPU, half_cycles::get_effective_addr, registers::flags::Flags},
-};
+use crate::emu::cpu::{CPU, bus::Bus, half_cycles::HalfCycle, registers::flags::Flags};
 
 /// # Compare Accumulator
 ///
 /// Compares A to a memory value, setting flags as appropriate.
-pub fn cmp(cpu: &mut CPU, buses: &mut Buses) {
+pub fn cmp(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = cpu.registers.a.wrapping_sub(data);
 
@@ -19,11 +16,12 @@ pub fn cmp(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Compares A to a memory value, setting flags as appropriate. Uses an
 /// additional cycle if a page is crossed.
-pub fn cmp_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
+pub fn cmp_indirect_y(cpu: &mut CPU, buses: &mut impl Bus) {
     cmp(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, cmp]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Cmp]);
     }
 }
 
@@ -31,18 +29,19 @@ pub fn cmp_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Compares A to a memory value, setting flags as appropriate. Uses an
 /// additional cycle if a page is crossed.
-pub fn cmp_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn cmp_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     cmp(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, cmp]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Cmp]);
     }
 }
 
 /// # Compare X Register
 ///
 /// Compares X to a memory value, setting flags as appropriate.
-pub fn cpx(cpu: &mut CPU, buses: &mut Buses) {
+pub fn cpx(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = cpu.registers.x_index.wrapping_sub(data);
 
@@ -54,7 +53,7 @@ pub fn cpx(cpu: &mut CPU, buses: &mut Buses) {
 /// # Compare Y Register
 ///
 /// Compares Y to a memory value, setting flags as appropriate.
-pub fn cpy(cpu: &mut CPU, buses: &mut Buses) {
+pub fn cpy(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = cpu.registers.y_index.wrapping_sub(data);
 
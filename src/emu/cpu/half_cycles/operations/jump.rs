@@ -1,31 +1,31 @@
-use crate::emu::{buses::Buses, cpu::CPU};
+use crate::emu::cpu::{CPU, bus::Bus};
 
 /// # Jump To Subroutine
 ///
 /// Sets the program counter to a new value. Allows returning via RTS.
-pub fn jsr(cpu: &mut CPU, buses: &mut Buses) {
+pub fn jsr(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
 
     cpu.buses.effective_addr.0 = data;
-    buses.data = data;
+    buses.set_data(data);
     cpu.registers.pc = cpu.buses.effective_addr;
 }
 
 /// # Jump
 ///
 /// Sets the program counter to a new value. Does not allow returning.
-pub fn jmp_absolute(cpu: &mut CPU, buses: &mut Buses) {
+pub fn jmp_absolute(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
 
     cpu.buses.effective_addr.0 = data;
-    buses.data = data;
+    buses.set_data(data);
     cpu.registers.pc = cpu.buses.effective_addr;
 }
 
 /// # Return From Interrupt
 ///
 /// Returns from an interrupt handler.
-pub fn rti(cpu: &mut CPU, buses: &mut Buses) {
+pub fn rti(cpu: &mut CPU, buses: &mut impl Bus) {
     // B and extra bit are ignored
     let masked_stack_status = buses.read() & 0b_1100_1111;
     let masked_processor_status = cpu.registers.psr & 0b_0011_0000;
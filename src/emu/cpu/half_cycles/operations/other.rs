@@ -1,25 +1,23 @@
-use crate::emu::{
-    buses::Buses,
-    cpu::{CPU, half_cycles::get_effective_addr},
-};
+use crate::emu::cpu::{CPU, bus::Bus, half_cycles::HalfCycle};
 
 /// # No Operation
 ///
 /// Does nothing.
-pub fn nop(_: &mut CPU, _: &mut Buses) {}
+pub fn nop(_: &mut CPU, _: &mut impl Bus) {}
 
 /// # No Operation
 ///
 /// Does nothing. Uses an additional cycle if a page is crossed.
-pub fn nop_abs_index(cpu: &mut CPU, _: &mut Buses) {
+pub fn nop_abs_index(cpu: &mut CPU, _: &mut impl Bus) {
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, nop]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Nop]);
     }
 }
 
 /// # Jam
 ///
 /// Stops the CPU.
-pub fn jam(cpu: &mut CPU, _: &mut Buses) {
+pub fn jam(cpu: &mut CPU, _: &mut impl Bus) {
     cpu.is_halted = true;
 }
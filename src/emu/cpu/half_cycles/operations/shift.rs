@@ -1,13 +1,10 @@
-use crate::emu::{
-    buses::Buses,
-    cpu::{CPU, registers::flags::Flags},
-};
+use crate::emu::cpu::{CPU, bus::Bus, registers::flags::Flags};
 
 /// # Arithmetic Shift Left (Memory Value)
 ///
 /// Shifts all of the bits of a memory value one position to the left. Bit seven
 /// is shifted into the carry flag, and 0 is shifted into bit zero.
-pub fn asl_m(cpu: &mut CPU, buses: &mut Buses) {
+pub fn asl_m(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = data << 1;
 
@@ -21,7 +18,7 @@ pub fn asl_m(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Shifts all of the bits of the accumulator one position to the left. Bit
 /// seven is shifted into the carry flag, and 0 is shifted into bit zero.
-pub fn asl_a(cpu: &mut CPU, _: &mut Buses) {
+pub fn asl_a(cpu: &mut CPU, _: &mut impl Bus) {
     let data = cpu.registers.a;
     let result = data << 1;
 
@@ -36,7 +33,7 @@ pub fn asl_a(cpu: &mut CPU, _: &mut Buses) {
 /// Shifts all of the bits of a memory value one position to
 /// the right, moving the value of each bit into the next bit. 0 is shifted into
 /// bit seven, and bit zero is shifted into the carry flag.
-pub fn lsr_m(cpu: &mut CPU, buses: &mut Buses) {
+pub fn lsr_m(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = data >> 1;
 
@@ -51,7 +48,7 @@ pub fn lsr_m(cpu: &mut CPU, buses: &mut Buses) {
 /// Shifts all of the bits of the accumulator one position to the right, moving
 /// the value of each bit into the next bit. 0 is shifted into bit seven, and
 /// bit zero is shifted into the carry flag.
-pub fn lsr_a(cpu: &mut CPU, _: &mut Buses) {
+pub fn lsr_a(cpu: &mut CPU, _: &mut impl Bus) {
     let data = cpu.registers.a;
     let result = data >> 1;
 
@@ -66,7 +63,7 @@ pub fn lsr_a(cpu: &mut CPU, _: &mut Buses) {
 /// Shifts a memory value to the left, moving the value of each bit into the
 /// neighboring bit and treating the carry flag as though it is both to the left
 /// of bit seven and to the right of bit zero.
-pub fn rol(cpu: &mut CPU, buses: &mut Buses) {
+pub fn rol(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let result = (data << 1) | (cpu.registers.psr.get_carry() as u8);
 
@@ -81,7 +78,7 @@ pub fn rol(cpu: &mut CPU, buses: &mut Buses) {
 /// Shifts the accumulator to the left, moving the value of each bit into the
 /// neighboring bit and treating the carry flag as though it is both to the left
 /// of bit seven and to the right of bit zero.
-pub fn rol_a(cpu: &mut CPU, _: &mut Buses) {
+pub fn rol_a(cpu: &mut CPU, _: &mut impl Bus) {
     let data = cpu.registers.a;
     let result = (data << 1) | (cpu.registers.psr.get_carry() as u8);
 
@@ -96,7 +93,7 @@ pub fn rol_a(cpu: &mut CPU, _: &mut Buses) {
 /// Shifts a memory value to the right, moving the value of each bit into the
 /// neighboring bit and treating the carry flag as though it is both to the
 /// right of bit zero and to the left of bit seven.
-pub fn ror_m(cpu: &mut CPU, buses: &mut Buses) {
+pub fn ror_m(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = buses.read();
     let masked_data = data & 0b_1111_1110;
     let result = (masked_data | cpu.registers.psr.get_carry() as u8).rotate_right(1);
@@ -112,7 +109,7 @@ pub fn ror_m(cpu: &mut CPU, buses: &mut Buses) {
 /// Shifts the accumulator to the right, moving the value of each bit into the
 /// neighboring bit and treating the carry flag as though it is both to the
 /// right of bit zero and to the left of bit seven.
-pub fn ror_a(cpu: &mut CPU, _: &mut Buses) {
+pub fn ror_a(cpu: &mut CPU, _: &mut impl Bus) {
     let data = cpu.registers.a;
     let masked_data = data & 0b_1111_1110;
     let result = (masked_data | cpu.registers.psr.get_carry() as u8).rotate_right(1);
@@ -1,26 +1,23 @@
-use crate::emu::{
-    buses::Buses,
-    cpu::{CPU, registers::flags::Flags},
-};
+use crate::emu::cpu::{CPU, bus::Bus, registers::flags::Flags};
 
 /// # Push Accumulator
 ///
 /// Pushes the accumulator's value onto the top of the stack.
-pub fn pha(cpu: &mut CPU, buses: &mut Buses) {
+pub fn pha(cpu: &mut CPU, buses: &mut impl Bus) {
     buses.write(cpu.registers.a);
 }
 
 /// # Push Processor Status
 ///
 /// Pushes the processor status flags onto the top of the stack.
-pub fn php(cpu: &mut CPU, buses: &mut Buses) {
+pub fn php(cpu: &mut CPU, buses: &mut impl Bus) {
     buses.write(cpu.registers.psr | 0b_0011_0000);
 }
 
 /// # Pull Acumulator
 ///
 /// Pops the value from the top of the stack and loads it into the accumulator.
-pub fn pla(cpu: &mut CPU, buses: &mut Buses) {
+pub fn pla(cpu: &mut CPU, buses: &mut impl Bus) {
     let result = buses.read();
 
     cpu.registers.psr.set_zero(result == 0);
@@ -32,7 +29,7 @@ pub fn pla(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Pops the value from the top of the stack and loads it into the processor
 /// status flags.
-pub fn plp(cpu: &mut CPU, buses: &mut Buses) {
+pub fn plp(cpu: &mut CPU, buses: &mut impl Bus) {
     // B and extra bit are ignored
     let masked_stack_status = buses.read() & 0b_1100_1111;
     let masked_psr = cpu.registers.psr & 0b_0011_0000;
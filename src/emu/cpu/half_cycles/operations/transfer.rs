@@ -1,16 +1,13 @@
-use crate::emu::{
-    buses::Buses,
-    cpu::{CPU, registers::flags::Flags},
-};
+use crate::emu::cpu::{CPU, bus::Bus, registers::flags::Flags};
 
 /// # Transfer Accumulator to X Register
 ///
 /// Copies the accumulator's value into the X register.
-pub fn tax(cpu: &mut CPU, buses: &mut Buses) {
+pub fn tax(cpu: &mut CPU, buses: &mut impl Bus) {
     let result = cpu.registers.a;
 
     cpu.registers.x_index = result;
-    buses.addr = cpu.registers.pc;
+    buses.set_addr(cpu.registers.pc);
     cpu.registers.psr.set_zero(result == 0);
     cpu.registers.psr.set_negative(result & Flags::N != 0);
 }
@@ -18,11 +15,11 @@ pub fn tax(cpu: &mut CPU, buses: &mut Buses) {
 /// # Transfer Accumulator to Y Register
 ///
 /// Copies the accumulator's value into the Y register.
-pub fn tay(cpu: &mut CPU, buses: &mut Buses) {
+pub fn tay(cpu: &mut CPU, buses: &mut impl Bus) {
     let result = cpu.registers.a;
 
     cpu.registers.y_index = result;
-    buses.addr = cpu.registers.pc;
+    buses.set_addr(cpu.registers.pc);
     cpu.registers.psr.set_zero(result == 0);
     cpu.registers.psr.set_negative(result & Flags::N != 0);
 }
@@ -30,11 +27,11 @@ pub fn tay(cpu: &mut CPU, buses: &mut Buses) {
 /// # Transfer Stack Pointer to X Register
 ///
 /// Copies the stack pointer's value into the X register.
-pub fn tsx(cpu: &mut CPU, buses: &mut Buses) {
+pub fn tsx(cpu: &mut CPU, buses: &mut impl Bus) {
     let result = cpu.registers.sp;
 
     cpu.registers.x_index = result;
-    buses.addr = cpu.registers.pc;
+    buses.set_addr(cpu.registers.pc);
     cpu.registers.psr.set_zero(result == 0);
     cpu.registers.psr.set_negative(result & Flags::N != 0);
 }
@@ -42,11 +39,11 @@ pub fn tsx(cpu: &mut CPU, buses: &mut Buses) {
 /// # Transfer X Register to Accumulator
 ///
 /// Copies the X register's value into the accumulator.
-pub fn txa(cpu: &mut CPU, buses: &mut Buses) {
+pub fn txa(cpu: &mut CPU, buses: &mut impl Bus) {
     let result = cpu.registers.x_index;
 
     cpu.registers.a = result;
-    buses.addr = cpu.registers.pc;
+    buses.set_addr(cpu.registers.pc);
     cpu.registers.psr.set_zero(result == 0);
     cpu.registers.psr.set_negative(result & Flags::N != 0);
 }
@@ -54,19 +51,19 @@ pub fn txa(cpu: &mut CPU, buses: &mut Buses) {
 /// # Transfer X Register to Stack Pointer
 ///
 /// Copies the X register's value into the stack pointer.
-pub fn txs(cpu: &mut CPU, buses: &mut Buses) {
+pub fn txs(cpu: &mut CPU, buses: &mut impl Bus) {
     cpu.registers.sp = cpu.registers.x_index;
-    buses.addr = cpu.registers.pc;
+    buses.set_addr(cpu.registers.pc);
 }
 
 /// # Transfer Y Register to Accumulator
 ///
 /// Copies the Y register's value into the accumulator.
-pub fn tya(cpu: &mut CPU, buses: &mut Buses) {
+pub fn tya(cpu: &mut CPU, buses: &mut impl Bus) {
     let result = cpu.registers.y_index;
 
     cpu.registers.a = result;
-    buses.addr = cpu.registers.pc;
+    buses.set_addr(cpu.registers.pc);
     cpu.registers.psr.set_zero(result == 0);
     cpu.registers.psr.set_negative(result & Flags::N != 0);
 }
@@ -1,14 +1,11 @@
-use crate::emu::{
-    buses::Buses,
-    cpu::{CPU, half_cycles::get_effective_addr},
-};
+use crate::emu::cpu::{CPU, bus::Bus, half_cycles::HalfCycle};
 
 // See: https://www.nesdev.org/wiki/Programming_with_unofficial_opcodes
 
 /// # Load Accumulator and X Register
 ///
 /// Loads a memory value into the accumulator and X register.
-pub fn lax(cpu: &mut CPU, buses: &mut Buses) {
+pub fn lax(cpu: &mut CPU, buses: &mut impl Bus) {
     super::access::lda(cpu, buses);
     super::access::ldx(cpu, buses);
 }
@@ -17,11 +14,12 @@ pub fn lax(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Loads a memory value into the accumulator and X register. Uses an additional
 /// cycle if a page is crossed.
-pub fn lax_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
+pub fn lax_indirect_y(cpu: &mut CPU, buses: &mut impl Bus) {
     lax(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, lax]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Lax]);
     }
 }
 
@@ -29,18 +27,19 @@ pub fn lax_indirect_y(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Loads a memory value into the accumulator and X register. Uses an additional
 /// cycle if a page is crossed.
-pub fn lax_abs_index(cpu: &mut CPU, buses: &mut Buses) {
+pub fn lax_abs_index(cpu: &mut CPU, buses: &mut impl Bus) {
     lax(cpu, buses);
 
     if cpu.crossed_page {
-        cpu.cycle_queue.push_back([get_effective_addr, lax]);
+        cpu.cycle_queue
+            .push_back([HalfCycle::GetEffectiveAddr, HalfCycle::Lax]);
     }
 }
 
 /// # Store Bitwise AND
 ///
 /// Stores the bitwise AND of the accumulator and X register into memory.
-pub fn sax(cpu: &mut CPU, buses: &mut Buses) {
+pub fn sax(cpu: &mut CPU, buses: &mut impl Bus) {
     let data = cpu.registers.a & cpu.registers.x_index;
 
     buses.write(data);
@@ -50,7 +49,7 @@ pub fn sax(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Subtracts 1 from a memory value, and then compares A to that memory value,
 /// setting flags as appropriate.
-pub fn dcp(cpu: &mut CPU, buses: &mut Buses) {
+pub fn dcp(cpu: &mut CPU, buses: &mut impl Bus) {
     super::arithmetic::dec(cpu, buses);
     super::compare::cmp(cpu, buses);
 }
@@ -59,7 +58,7 @@ pub fn dcp(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Adds 1 to a memory value, and then subtracts that memory value and the
 /// bitwise NOT of the carry flag from the accumulator.
-pub fn isc(cpu: &mut CPU, buses: &mut Buses) {
+pub fn isc(cpu: &mut CPU, buses: &mut impl Bus) {
     super::arithmetic::inc(cpu, buses);
     super::arithmetic::sbc(cpu, buses);
 }
@@ -68,7 +67,7 @@ pub fn isc(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Performs a rotate left on a memory value, and then performs a bitwise AND
 /// of that value with the accumulator.
-pub fn rla(cpu: &mut CPU, buses: &mut Buses) {
+pub fn rla(cpu: &mut CPU, buses: &mut impl Bus) {
     super::shift::rol(cpu, buses);
     super::bitwise::and(cpu, buses);
 }
@@ -77,7 +76,7 @@ pub fn rla(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Performs a rotate right on a memory value, and then adds that memory value
 /// and the carry flag to the accumulator.
-pub fn rra(cpu: &mut CPU, buses: &mut Buses) {
+pub fn rra(cpu: &mut CPU, buses: &mut impl Bus) {
     super::shift::ror_m(cpu, buses);
     super::arithmetic::adc(cpu, buses);
 }
@@ -86,7 +85,7 @@ pub fn rra(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Performs an arithmetic shift left on a memory value, and then performs a
 /// bitwise OR of that memory value and the accumulator.
-pub fn slo(cpu: &mut CPU, buses: &mut Buses) {
+pub fn slo(cpu: &mut CPU, buses: &mut impl Bus) {
     super::shift::asl_m(cpu, buses);
     super::bitwise::ora(cpu, buses);
 }
@@ -95,7 +94,7 @@ pub fn slo(cpu: &mut CPU, buses: &mut Buses) {
 ///
 /// Performs a logical shift right on a memory value, and then performs a
 /// bitwise XOR of that memory value and the accumulator.
-pub fn sre(cpu: &mut CPU, buses: &mut Buses) {
+pub fn sre(cpu: &mut CPU, buses: &mut impl Bus) {
     super::shift::lsr_m(cpu, buses);
     super::bitwise::eor(cpu, buses);
 }
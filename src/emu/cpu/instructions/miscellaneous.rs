@@ -1,4 +1,8 @@
-use crate::emu::cpu::{cycles::*, half_cycles::*, instructions::Instruction};
+use crate::emu::cpu::{
+    cycles::*,
+    half_cycles::HalfCycle::{self, *},
+    instructions::Instruction,
+};
 
 pub struct Push {
     pub op: HalfCycle,
@@ -6,7 +10,7 @@ pub struct Push {
 
 impl Instruction<2> for Push {
     fn get_cycles(&self) -> [Cycle; 2] {
-        [[get_pc, read_data], [push_stack, self.op]]
+        [[GetPc, ReadData], [PushStack, self.op]]
     }
 }
 
@@ -16,11 +20,7 @@ pub struct Pull {
 
 impl Instruction<3> for Pull {
     fn get_cycles(&self) -> [Cycle; 3] {
-        [
-            [get_pc, read_data],
-            [pop_stack, read_data],
-            [get_sp, self.op],
-        ]
+        [[GetPc, ReadData], [PopStack, ReadData], [GetSp, self.op]]
     }
 }
 
@@ -32,10 +32,10 @@ impl Instruction<5> for JumpToSubroutine {
     fn get_cycles(&self) -> [Cycle; 5] {
         [
             GET_EFFECTIVE_ADDR_LOW_BYTE,
-            [get_sp, read_data],
-            [push_stack, write_pc_high_byte],
-            [push_stack, write_pc_low_byte],
-            [get_pc_with_inc, self.op],
+            [GetSp, ReadData],
+            [PushStack, WritePcHighByte],
+            [PushStack, WritePcLowByte],
+            [GetPcWithInc, self.op],
         ]
     }
 }
@@ -45,12 +45,12 @@ pub struct Break {}
 impl Instruction<6> for Break {
     fn get_cycles(&self) -> [Cycle; 6] {
         [
-            [get_pc_with_inc, read_data],
-            [push_stack, write_pc_high_byte],
-            [push_stack, write_pc_low_byte],
-            [push_stack, write_break_status],
-            [get_irq_vector_low_byte, read_pc_low_byte],
-            [get_irq_vector_high_byte, read_pc_high_byte],
+            [GetPcWithInc, ReadData],
+            [PushStack, WritePcHighByte],
+            [PushStack, WritePcLowByte],
+            [PushStack, WriteBreakStatus],
+            [GetIrqVectorLowByte, ReadPcLowByte],
+            [GetIrqVectorHighByte, ReadPcHighByte],
         ]
     }
 }
@@ -62,11 +62,11 @@ pub struct ReturnFromInterrupt {
 impl Instruction<5> for ReturnFromInterrupt {
     fn get_cycles(&self) -> [Cycle; 5] {
         [
-            [get_pc_with_inc, read_data],
-            [pop_stack, read_data],
-            [pop_stack, self.op],
-            [pop_stack, read_pc_low_byte],
-            [get_sp, read_pc_high_byte],
+            [GetPcWithInc, ReadData],
+            [PopStack, ReadData],
+            [PopStack, self.op],
+            [PopStack, ReadPcLowByte],
+            [GetSp, ReadPcHighByte],
         ]
     }
 }
@@ -77,7 +77,7 @@ pub struct JumpAbsolute {
 
 impl Instruction<2> for JumpAbsolute {
     fn get_cycles(&self) -> [Cycle; 2] {
-        [GET_EFFECTIVE_ADDR_LOW_BYTE, [get_pc_with_inc, self.op]]
+        [GET_EFFECTIVE_ADDR_LOW_BYTE, [GetPcWithInc, self.op]]
     }
 }
 
@@ -86,10 +86,10 @@ pub struct JumpIndirect {}
 impl Instruction<4> for JumpIndirect {
     fn get_cycles(&self) -> [Cycle; 4] {
         [
-            [get_pc_with_inc, read_indirect_addr_low_byte],
-            [get_pc_with_inc, read_indirect_addr_high_byte],
-            [get_indirect_addr, read_pc_low_byte],
-            [get_indirect_addr_high_byte, read_pc_high_byte],
+            [GetPcWithInc, ReadIndirectAddrLowByte],
+            [GetPcWithInc, ReadIndirectAddrHighByte],
+            [GetIndirectAddr, ReadPcLowByte],
+            [GetIndirectAddrHighByte, ReadPcHighByte],
         ]
     }
 }
@@ -99,11 +99,11 @@ pub struct ReturnFromSubroutine {}
 impl Instruction<5> for ReturnFromSubroutine {
     fn get_cycles(&self) -> [Cycle; 5] {
         [
-            [get_pc_with_inc, read_data],
-            [pop_stack, read_data],
-            [pop_stack, read_pc_low_byte],
-            [get_sp, read_pc_high_byte],
-            [get_pc_with_inc, read_data],
+            [GetPcWithInc, ReadData],
+            [PopStack, ReadData],
+            [PopStack, ReadPcLowByte],
+            [GetSp, ReadPcHighByte],
+            [GetPcWithInc, ReadData],
         ]
     }
 }
@@ -114,6 +114,6 @@ pub struct Branch {
 
 impl Instruction<1> for Branch {
     fn get_cycles(&self) -> [Cycle; 1] {
-        [[get_pc_with_inc, self.op]]
+        [[GetPcWithInc, self.op]]
     }
 }
@@ -1,4 +1,8 @@
-use crate::emu::cpu::{cycles::*, half_cycles::*, instructions::Instruction};
+use crate::emu::cpu::{
+    cycles::*,
+    half_cycles::HalfCycle::{self, *},
+    instructions::Instruction,
+};
 
 pub struct Immediate {
     pub op: HalfCycle,
@@ -6,7 +10,7 @@ pub struct Immediate {
 
 impl Instruction<1> for Immediate {
     fn get_cycles(&self) -> [Cycle; 1] {
-        [[get_pc_with_inc, self.op]]
+        [[GetPcWithInc, self.op]]
     }
 }
 
@@ -18,7 +22,7 @@ impl Instruction<2> for ZeroPage {
     fn get_cycles(&self) -> [Cycle; 2] {
         [
             GET_EFFECTIVE_ADDR_LOW_BYTE,
-            [get_effective_zero_page_addr, self.op],
+            [GetEffectiveZeroPageAddr, self.op],
         ]
     }
 }
@@ -32,7 +36,7 @@ impl Instruction<3> for Absolute {
         [
             GET_EFFECTIVE_ADDR_LOW_BYTE,
             GET_EFFECTIVE_ADDR_HIGH_BYTE,
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -46,15 +50,12 @@ impl Instruction<5> for IndirectX {
         [
             GET_BASE_ADDR_LOW_BYTE,
             READ_FROM_BASE_ZERO_PAGE_ADDR,
+            [GetBaseZeroPageXIndexedAddr, ReadEffectiveAddrLowByte],
             [
-                get_base_zero_page_x_indexed_addr,
-                read_effective_addr_low_byte,
+                GetBaseZeroPageXIndexedAddrHighByte,
+                ReadEffectiveAddrHighByte,
             ],
-            [
-                get_base_zero_page_x_indexed_addr_high_byte,
-                read_effective_addr_high_byte,
-            ],
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -66,13 +67,10 @@ pub struct IndirectY {
 impl Instruction<4> for IndirectY {
     fn get_cycles(&self) -> [Cycle; 4] {
         [
-            [get_pc_with_inc, read_indirect_addr_low_byte],
-            [get_indirect_zero_page_addr, read_base_addr_low_byte],
-            [
-                get_indirect_zero_page_addr_high_byte,
-                read_base_addr_high_byte,
-            ],
-            [get_base_addr_y_indexed_with_carry, self.op],
+            [GetPcWithInc, ReadIndirectAddrLowByte],
+            [GetIndirectZeroPageAddr, ReadBaseAddrLowByte],
+            [GetIndirectZeroPageAddrHighByte, ReadBaseAddrHighByte],
+            [GetBaseAddrYIndexedWithCarry, self.op],
         ]
     }
 }
@@ -86,7 +84,7 @@ impl Instruction<3> for AbsoluteX {
         [
             GET_BASE_ADDR_LOW_BYTE,
             GET_BASE_ADDR_HIGH_BYTE,
-            [get_base_addr_x_indexed_with_carry, self.op],
+            [GetBaseAddrXIndexedWithCarry, self.op],
         ]
     }
 }
@@ -100,7 +98,7 @@ impl Instruction<3> for AbsoluteY {
         [
             GET_BASE_ADDR_LOW_BYTE,
             GET_BASE_ADDR_HIGH_BYTE,
-            [get_base_addr_y_indexed_with_carry, self.op],
+            [GetBaseAddrYIndexedWithCarry, self.op],
         ]
     }
 }
@@ -114,7 +112,7 @@ impl Instruction<3> for ZeroPageX {
         [
             GET_BASE_ADDR_LOW_BYTE,
             READ_FROM_BASE_ZERO_PAGE_ADDR,
-            [get_base_zero_page_x_indexed_addr, self.op],
+            [GetBaseZeroPageXIndexedAddr, self.op],
         ]
     }
 }
@@ -128,7 +126,7 @@ impl Instruction<3> for ZeroPageY {
         [
             GET_BASE_ADDR_LOW_BYTE,
             READ_FROM_BASE_ZERO_PAGE_ADDR,
-            [get_base_zero_page_y_indexed_addr, self.op],
+            [GetBaseZeroPageYIndexedAddr, self.op],
         ]
     }
 }
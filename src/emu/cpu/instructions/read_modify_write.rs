@@ -1,4 +1,8 @@
-use crate::emu::cpu::{cycles::*, half_cycles::*, instructions::Instruction};
+use crate::emu::cpu::{
+    cycles::*,
+    half_cycles::HalfCycle::{self, *},
+    instructions::Instruction,
+};
 
 pub struct ZeroPage {
     pub op: HalfCycle,
@@ -8,9 +12,9 @@ impl Instruction<4> for ZeroPage {
     fn get_cycles(&self) -> [Cycle; 4] {
         [
             GET_EFFECTIVE_ADDR_LOW_BYTE,
-            [get_effective_zero_page_addr, read_data],
-            [get_effective_zero_page_addr, write_data],
-            [get_effective_zero_page_addr, self.op],
+            [GetEffectiveZeroPageAddr, ReadData],
+            [GetEffectiveZeroPageAddr, WriteData],
+            [GetEffectiveZeroPageAddr, self.op],
         ]
     }
 }
@@ -26,7 +30,7 @@ impl Instruction<5> for Absolute {
             GET_EFFECTIVE_ADDR_HIGH_BYTE,
             READ_FROM_EFFECTIVE_ADDRESS,
             WRITE_TO_EFFECTIVE_ADDRESS,
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -40,9 +44,9 @@ impl Instruction<5> for ZeroPageX {
         [
             GET_BASE_ADDR_LOW_BYTE,
             READ_FROM_BASE_ZERO_PAGE_ADDR,
-            [get_base_zero_page_x_indexed_addr, read_data],
-            [get_base_zero_page_x_indexed_addr, write_data],
-            [get_base_zero_page_x_indexed_addr, self.op],
+            [GetBaseZeroPageXIndexedAddr, ReadData],
+            [GetBaseZeroPageXIndexedAddr, WriteData],
+            [GetBaseZeroPageXIndexedAddr, self.op],
         ]
     }
 }
@@ -56,10 +60,10 @@ impl Instruction<6> for AbsoluteX {
         [
             GET_BASE_ADDR_LOW_BYTE,
             GET_BASE_ADDR_HIGH_BYTE,
-            [get_base_addr_x_indexed_with_carry, read_data],
+            [GetBaseAddrXIndexedWithCarry, ReadData],
             READ_FROM_EFFECTIVE_ADDRESS,
             WRITE_TO_EFFECTIVE_ADDRESS,
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -1,4 +1,8 @@
-use crate::emu::cpu::{cycles::*, half_cycles::*, instructions::Instruction};
+use crate::emu::cpu::{
+    cycles::*,
+    half_cycles::HalfCycle::{self, *},
+    instructions::Instruction,
+};
 
 pub struct SingleByte {
     pub op: HalfCycle,
@@ -9,6 +13,6 @@ impl Instruction<1> for SingleByte {
         // @TODO: Some of the `operations` here are supposed to set the address
         // bus to PC + 1 but they just don't. Need to determine if this is
         // necessary or not.
-        [[self.op, read_opcode]]
+        [[self.op, ReadOpcode]]
     }
 }
@@ -1,4 +1,8 @@
-use crate::emu::cpu::{cycles::*, half_cycles::*, instructions::Instruction};
+use crate::emu::cpu::{
+    cycles::*,
+    half_cycles::HalfCycle::{self, *},
+    instructions::Instruction,
+};
 
 pub struct ZeroPage {
     pub op: HalfCycle,
@@ -8,7 +12,7 @@ impl Instruction<2> for ZeroPage {
     fn get_cycles(&self) -> [Cycle; 2] {
         [
             GET_EFFECTIVE_ADDR_LOW_BYTE,
-            [get_effective_zero_page_addr, self.op],
+            [GetEffectiveZeroPageAddr, self.op],
         ]
     }
 }
@@ -22,7 +26,7 @@ impl Instruction<3> for Absolute {
         [
             GET_EFFECTIVE_ADDR_LOW_BYTE,
             GET_EFFECTIVE_ADDR_HIGH_BYTE,
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -36,15 +40,12 @@ impl Instruction<5> for IndirectX {
         [
             GET_BASE_ADDR_LOW_BYTE,
             READ_FROM_BASE_ZERO_PAGE_ADDR,
+            [GetBaseZeroPageXIndexedAddr, ReadEffectiveAddrLowByte],
             [
-                get_base_zero_page_x_indexed_addr,
-                read_effective_addr_low_byte,
+                GetBaseZeroPageXIndexedAddrHighByte,
+                ReadEffectiveAddrHighByte,
             ],
-            [
-                get_base_zero_page_x_indexed_addr_high_byte,
-                read_effective_addr_high_byte,
-            ],
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -56,14 +57,11 @@ pub struct IndirectY {
 impl Instruction<5> for IndirectY {
     fn get_cycles(&self) -> [Cycle; 5] {
         [
-            [get_pc_with_inc, read_indirect_addr_low_byte],
-            [get_indirect_zero_page_addr, read_base_addr_low_byte],
-            [
-                get_indirect_zero_page_addr_high_byte,
-                read_base_addr_high_byte,
-            ],
-            [get_indirect_y_indexed_addr, read_data],
-            [get_effective_addr, self.op],
+            [GetPcWithInc, ReadIndirectAddrLowByte],
+            [GetIndirectZeroPageAddr, ReadBaseAddrLowByte],
+            [GetIndirectZeroPageAddrHighByte, ReadBaseAddrHighByte],
+            [GetIndirectYIndexedAddr, ReadData],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -77,8 +75,8 @@ impl Instruction<4> for AbsoluteX {
         [
             GET_BASE_ADDR_LOW_BYTE,
             GET_BASE_ADDR_HIGH_BYTE,
-            [get_base_addr_x_indexed_with_carry, read_data],
-            [get_effective_addr, self.op],
+            [GetBaseAddrXIndexedWithCarry, ReadData],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -92,8 +90,8 @@ impl Instruction<4> for AbsoluteY {
         [
             GET_BASE_ADDR_LOW_BYTE,
             GET_BASE_ADDR_HIGH_BYTE,
-            [get_base_addr_y_indexed_with_carry, read_data],
-            [get_effective_addr, self.op],
+            [GetBaseAddrYIndexedWithCarry, ReadData],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -106,8 +104,8 @@ impl Instruction<3> for ZeroPageX {
     fn get_cycles(&self) -> [Cycle; 3] {
         [
             GET_BASE_ADDR_LOW_BYTE,
-            [get_base_zero_page_addr, read_data],
-            [get_base_zero_page_x_indexed_addr, self.op],
+            [GetBaseZeroPageAddr, ReadData],
+            [GetBaseZeroPageXIndexedAddr, self.op],
         ]
     }
 }
@@ -120,8 +118,8 @@ impl Instruction<3> for ZeroPageY {
     fn get_cycles(&self) -> [Cycle; 3] {
         [
             GET_BASE_ADDR_LOW_BYTE,
-            [get_base_zero_page_addr, read_data],
-            [get_base_zero_page_y_indexed_addr, self.op],
+            [GetBaseZeroPageAddr, ReadData],
+            [GetBaseZeroPageYIndexedAddr, self.op],
         ]
     }
 }
@@ -1,9 +1,6 @@
 use crate::emu::cpu::{
     cycles::*,
-    half_cycles::{
-        operations::other::{jam, nop},
-        *,
-    },
+    half_cycles::HalfCycle::{self, *},
     instructions::Instruction,
 };
 
@@ -15,9 +12,9 @@ impl Instruction<4> for ZeroPage {
     fn get_cycles(&self) -> [Cycle; 4] {
         [
             GET_EFFECTIVE_ADDR_LOW_BYTE,
-            [get_effective_zero_page_addr, read_data],
-            [get_effective_zero_page_addr, write_data],
-            [get_effective_zero_page_addr, self.op],
+            [GetEffectiveZeroPageAddr, ReadData],
+            [GetEffectiveZeroPageAddr, WriteData],
+            [GetEffectiveZeroPageAddr, self.op],
         ]
     }
 }
@@ -31,9 +28,9 @@ impl Instruction<5> for ZeroPageX {
         [
             GET_BASE_ADDR_LOW_BYTE,
             READ_FROM_BASE_ZERO_PAGE_ADDR,
-            [get_base_zero_page_x_indexed_addr, read_data],
-            [get_base_zero_page_x_indexed_addr, write_data],
-            [get_base_zero_page_x_indexed_addr, self.op],
+            [GetBaseZeroPageXIndexedAddr, ReadData],
+            [GetBaseZeroPageXIndexedAddr, WriteData],
+            [GetBaseZeroPageXIndexedAddr, self.op],
         ]
     }
 }
@@ -49,7 +46,7 @@ impl Instruction<5> for Absolute {
             GET_EFFECTIVE_ADDR_HIGH_BYTE,
             READ_FROM_EFFECTIVE_ADDRESS,
             WRITE_TO_EFFECTIVE_ADDRESS,
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -63,10 +60,10 @@ impl Instruction<6> for AbsoluteX {
         [
             GET_BASE_ADDR_LOW_BYTE,
             GET_BASE_ADDR_HIGH_BYTE,
-            [get_base_addr_x_indexed_with_carry, read_data],
+            [GetBaseAddrXIndexedWithCarry, ReadData],
             READ_FROM_EFFECTIVE_ADDRESS,
             WRITE_TO_EFFECTIVE_ADDRESS,
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -80,10 +77,10 @@ impl Instruction<6> for AbsoluteY {
         [
             GET_BASE_ADDR_LOW_BYTE,
             GET_BASE_ADDR_HIGH_BYTE,
-            [get_base_addr_y_indexed_with_carry, read_data],
+            [GetBaseAddrYIndexedWithCarry, ReadData],
             READ_FROM_EFFECTIVE_ADDRESS,
             WRITE_TO_EFFECTIVE_ADDRESS,
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -96,18 +93,15 @@ impl Instruction<7> for IndirectX {
     fn get_cycles(&self) -> [Cycle; 7] {
         [
             GET_BASE_ADDR_LOW_BYTE,
-            [get_base_zero_page_addr, read_data],
+            [GetBaseZeroPageAddr, ReadData],
+            [GetBaseZeroPageXIndexedAddr, ReadEffectiveAddrLowByte],
             [
-                get_base_zero_page_x_indexed_addr,
-                read_effective_addr_low_byte,
-            ],
-            [
-                get_base_zero_page_x_indexed_addr_high_byte,
-                read_effective_addr_high_byte,
+                GetBaseZeroPageXIndexedAddrHighByte,
+                ReadEffectiveAddrHighByte,
             ],
             READ_FROM_EFFECTIVE_ADDRESS,
             WRITE_TO_EFFECTIVE_ADDRESS,
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -119,16 +113,13 @@ pub struct IndirectY {
 impl Instruction<7> for IndirectY {
     fn get_cycles(&self) -> [Cycle; 7] {
         [
-            [get_pc_with_inc, read_indirect_addr_low_byte],
-            [get_indirect_zero_page_addr, read_base_addr_low_byte],
-            [
-                get_indirect_zero_page_addr_high_byte,
-                read_base_addr_high_byte,
-            ],
-            [get_base_addr_y_indexed_with_carry, read_data],
+            [GetPcWithInc, ReadIndirectAddrLowByte],
+            [GetIndirectZeroPageAddr, ReadBaseAddrLowByte],
+            [GetIndirectZeroPageAddrHighByte, ReadBaseAddrHighByte],
+            [GetBaseAddrYIndexedWithCarry, ReadData],
             READ_FROM_EFFECTIVE_ADDRESS,
             WRITE_TO_EFFECTIVE_ADDRESS,
-            [get_effective_addr, self.op],
+            [GetEffectiveAddr, self.op],
         ]
     }
 }
@@ -137,6 +128,6 @@ pub struct Halt {}
 
 impl Instruction<1> for Halt {
     fn get_cycles(&self) -> [Cycle; 1] {
-        [[nop, jam]]
+        [[Nop, Jam]]
     }
 }
@@ -1,22 +1,28 @@
-use std::collections::VecDeque;
-
 use crate::{
     emu::{
-        buses::Buses as ExternalBuses,
         cpu::{
-            cycles::{Cycle, HANDLE_IRQ, HANDLE_NMI},
-            half_cycles::{get_pc, read_opcode},
+            bus::Bus,
+            cycles::{CYCLE_QUEUE_CAPACITY, Cycle, CycleQueue, HANDLE_IRQ, HANDLE_NMI},
+            half_cycles::HalfCycle,
             registers::{REGISTERS_AT_POWERON, Registers},
         },
+        error::{Error, StateError},
+        state::{StateReader, StateWriter},
     },
     split_u16,
 };
 
+pub mod bus;
 pub mod cycles;
 pub mod flags;
 pub mod half_cycles;
 pub mod instructions;
 pub mod registers;
+/// Conformance tests that run the CPU against the single-step test vectors of
+/// SingleStepTests/ProcessorTests, which give each opcode's initial and final
+/// states along with the bus access made on every cycle.
+#[cfg(test)]
+mod single_step;
 
 #[derive(Default, Clone, Copy)]
 /// Internal CPU buses.
@@ -33,7 +39,7 @@ pub struct Buses {
 #[derive(Default, Clone)]
 pub struct CPU {
     /// The next cycles to be executed by the CPU.
-    cycle_queue: VecDeque<Cycle>,
+    cycle_queue: CycleQueue,
     /// The number of half-cycles executed by the CPU.
     half_cycle_count: u64,
     /// `true` if the CPU is halted, `false` otherwise.
@@ -58,7 +64,7 @@ pub struct CPU {
 impl CPU {
     pub fn new(half_cycle_count: u64, registers: Registers) -> Self {
         Self {
-            cycle_queue: VecDeque::default(),
+            cycle_queue: CycleQueue::new(),
             half_cycle_count,
             is_halted: false,
             registers,
@@ -71,8 +77,8 @@ impl CPU {
         }
     }
 
-    pub fn get_cycle_queue(&self) -> VecDeque<Cycle> {
-        self.cycle_queue.clone()
+    pub fn get_cycle_queue(&self) -> CycleQueue {
+        self.cycle_queue
     }
 
     pub fn get_cycle_count(&self) -> u64 {
@@ -87,6 +93,119 @@ impl CPU {
         self.registers
     }
 
+    /// Returns `true` if the CPU has finished an instruction and hasn't
+    /// started the next one.
+    pub fn is_between_instructions(&self) -> bool {
+        self.cycle_queue.is_empty()
+    }
+
+    /// Writes the CPU's state to a save state, including the cycles left in the
+    /// current instruction.
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.cycle_queue.len() as u8);
+        for cycle in self.cycle_queue.iter() {
+            state.write_bytes(&cycle.map(u8::from));
+        }
+
+        state.write_u64(self.half_cycle_count);
+        state.write_bool(self.is_halted);
+
+        let registers = self.registers;
+        state.write_u8(registers.a);
+        state.write_u8(registers.x_index);
+        state.write_u8(registers.y_index);
+        state.write_bytes(&[registers.pc.0, registers.pc.1]);
+        state.write_u8(registers.sp);
+        state.write_u8(registers.psr.into());
+        state.write_u8(registers.ir);
+
+        for (high, low) in [
+            self.buses.base_addr,
+            self.buses.effective_addr,
+            self.buses.indirect_addr,
+        ] {
+            state.write_bytes(&[high, low]);
+        }
+
+        state.write_bool(self.crossed_page);
+        state.write_bool(self.prev_nmi);
+        state.write_bool(self.nmi_detected);
+        state.write_bool(self.irq_detected);
+        state.write_u8(match self.interrupt_disabled {
+            None => 0,
+            Some(false) => 1,
+            Some(true) => 2,
+        });
+    }
+
+    /// Restores the state written by [`CPU::save_state`].
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        let read_pair = |state: &mut StateReader| -> Result<(u8, u8), Error> {
+            let mut pair = [0; 2];
+            state.read_bytes(&mut pair)?;
+            Ok((pair[0], pair[1]))
+        };
+
+        let len = state.read_u8()? as usize;
+        if len > CYCLE_QUEUE_CAPACITY {
+            return Err(StateError::InvalidState {
+                message: format!("{len} cycles can't be queued at once"),
+            }
+            .into());
+        }
+
+        self.cycle_queue.clear();
+        for _ in 0..len {
+            let mut cycle = [0; 2];
+            state.read_bytes(&mut cycle)?;
+            let [phase1, phase2] = cycle.map(HalfCycle::try_from);
+            let (Ok(phase1), Ok(phase2)) = (phase1, phase2) else {
+                return Err(StateError::InvalidState {
+                    message: format!("{cycle:02X?} is not a valid cycle"),
+                }
+                .into());
+            };
+            self.cycle_queue.push_back([phase1, phase2]);
+        }
+
+        self.half_cycle_count = state.read_u64()?;
+        self.is_halted = state.read_bool()?;
+
+        self.registers = Registers {
+            a: state.read_u8()?,
+            x_index: state.read_u8()?,
+            y_index: state.read_u8()?,
+            pc: read_pair(state)?,
+            sp: state.read_u8()?,
+            psr: state.read_u8()?.into(),
+            ir: state.read_u8()?,
+        };
+
+        self.buses = Buses {
+            base_addr: read_pair(state)?,
+            effective_addr: read_pair(state)?,
+            indirect_addr: read_pair(state)?,
+        };
+
+        self.crossed_page = state.read_bool()?;
+        self.prev_nmi = state.read_bool()?;
+        self.nmi_detected = state.read_bool()?;
+        self.irq_detected = state.read_bool()?;
+        self.interrupt_disabled = match state.read_u8()? {
+            0 => None,
+            1 => Some(false),
+            2 => Some(true),
+            value => {
+                return Err(StateError::InvalidState {
+                    message: format!("{value} is not a pending interrupt disable flag"),
+                }
+                .into());
+            }
+        };
+
+        Ok(())
+    }
+
     /// Advances the state of the CPU ahead by a single cycle.
     ///
     /// Executes a single cycle from the cycle queue. If there are no cycles in
@@ -98,7 +217,7 @@ impl CPU {
     ///
     /// * `buses`: The external buses the CPU will use to access RAM and I/O.
     ///
-    pub fn tick(&mut self, buses: &mut ExternalBuses) {
+    pub fn tick(&mut self, buses: &mut impl Bus) {
         let cycle = self.cycle_queue.pop_front();
         match cycle {
             Some(cycle) => self.run_cycle(buses, cycle),
@@ -128,7 +247,7 @@ impl CPU {
     ///
     /// * `buses`: The external buses the CPU will use to access RAM and I/O.
     ///
-    fn handle_interrupts(&mut self, buses: &mut ExternalBuses) -> bool {
+    fn handle_interrupts(&mut self, buses: &mut impl Bus) -> bool {
         let nmi = self.nmi_detected;
         let irq = self.irq_detected && !self.registers.psr.get_interrupt_disable();
 
@@ -137,12 +256,12 @@ impl CPU {
         }
 
         if nmi {
-            self.run_cycle(buses, [get_pc, read_opcode]);
-            self.cycle_queue.extend(HANDLE_NMI.to_vec());
+            self.run_cycle(buses, [HalfCycle::GetPc, HalfCycle::ReadOpcode]);
+            self.cycle_queue.extend(&HANDLE_NMI);
             self.nmi_detected = false;
         } else if irq {
-            self.run_cycle(buses, [get_pc, read_opcode]);
-            self.cycle_queue.extend(HANDLE_IRQ.to_vec());
+            self.run_cycle(buses, [HalfCycle::GetPc, HalfCycle::ReadOpcode]);
+            self.cycle_queue.extend(&HANDLE_IRQ);
             self.irq_detected = false;
         }
 
@@ -161,11 +280,11 @@ impl CPU {
     /// * `buses`: The external buses the CPU will use to access RAM and I/O.
     /// * `cycle`: The cycle to be executed.
     ///
-    fn run_cycle(&mut self, buses: &mut ExternalBuses, cycle: Cycle) {
+    fn run_cycle(&mut self, buses: &mut impl Bus, cycle: Cycle) {
         let [phase1, phase2] = cycle;
 
-        phase1(self, buses);
-        phase2(self, buses);
+        phase1.run(self, buses);
+        phase2.run(self, buses);
 
         self.irq_detected = buses.get_irq();
 
@@ -191,7 +310,7 @@ impl CPU {
     /// * `initial_pc`: An optional program counter value. If `None` then the
     ///   program counter is set to the vector stored at `$FFFC`.
     ///
-    pub fn poweron(&mut self, buses: &mut ExternalBuses, initial_pc: Option<u16>) {
+    pub fn poweron(&mut self, buses: &mut impl Bus, initial_pc: Option<u16>) {
         self.registers = REGISTERS_AT_POWERON;
         self.registers.pc = match initial_pc {
             Some(addr) => split_u16!(addr),
@@ -205,8 +324,10 @@ impl CPU {
 
     /// Resets the CPU.
     ///
-    /// Sets the CPU's registers to their appropriate values following a power
-    /// cycle.
+    /// Sets the CPU's registers to their appropriate values following the reset
+    /// button being pressed. The current instruction and any pending interrupts
+    /// are abandoned, and the stack pointer is decremented by three as the
+    /// reset sequence goes through the motions of pushing to the stack.
     ///
     /// # Arguments
     ///
@@ -214,7 +335,13 @@ impl CPU {
     /// * `initial_pc`: An optional program counter value. If `None` then the
     ///   program counter is set to the vector stored at `$FFFC`.
     ///
-    pub fn reset(&mut self, buses: &mut ExternalBuses, initial_pc: Option<u16>) {
+    pub fn reset(&mut self, buses: &mut impl Bus, initial_pc: Option<u16>) {
+        self.cycle_queue.clear();
+        self.nmi_detected = false;
+        self.irq_detected = false;
+        self.interrupt_disabled = None;
+
+        self.registers.sp = self.registers.sp.wrapping_sub(3);
         self.registers.psr.set_interrupt_disable(true);
         self.registers.pc = match initial_pc {
             Some(addr) => split_u16!(addr),
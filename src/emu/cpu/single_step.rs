@@ -0,0 +1,281 @@
+use serde::Deserialize;
+
+use crate::{
+    concat_u8,
+    emu::cpu::{CPU, bus::Bus, registers::Registers},
+    split_u16,
+};
+
+/// The directory the test vectors are read from, with a file for each opcode
+/// named after it in lowercase hex, such as `a9.json`.
+const TESTS_DIR: &str = "tests/single_step";
+
+/// The opcodes that are known to fail, which aren't run as they'd panic or
+/// never finish, along with the reason they fail.
+const KNOWN_FAILURES: &[(&[u8], &str)] = &[
+    (
+        &[
+            0x0B, 0x2B, 0x4B, 0x6B, 0x8B, 0x93, 0x9B, 0x9C, 0x9E, 0x9F, 0xAB, 0xBB, 0xCB,
+        ],
+        "not yet implemented",
+    ),
+    (
+        &[
+            0x02, 0x12, 0x22, 0x32, 0x42, 0x52, 0x62, 0x72, 0x92, 0xB2, 0xD2, 0xF2,
+        ],
+        "JAM halts the CPU rather than finishing",
+    ),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+    Read,
+    Write,
+}
+
+/// A bus access, as its address, data and direction.
+type Access = (u16, u8, Direction);
+
+/// A bus with a flat 64 KiB of RAM, which records every access.
+struct TestBus {
+    ram: Vec<u8>,
+    addr: (u8, u8),
+    data: u8,
+    accesses: Vec<Access>,
+}
+
+impl Bus for TestBus {
+    fn set_addr(&mut self, addr: (u8, u8)) {
+        self.addr = addr;
+    }
+
+    fn get_data(&self) -> u8 {
+        self.data
+    }
+
+    fn set_data(&mut self, data: u8) {
+        self.data = data;
+    }
+
+    fn read(&mut self) -> u8 {
+        let addr = concat_u8!(self.addr.0, self.addr.1);
+        self.data = self.ram[addr as usize];
+        self.accesses.push((addr, self.data, Direction::Read));
+        self.data
+    }
+
+    fn write(&mut self, data: u8) {
+        let addr = concat_u8!(self.addr.0, self.addr.1);
+        self.data = data;
+        self.ram[addr as usize] = data;
+        self.accesses.push((addr, data, Direction::Write));
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.ram[addr as usize]
+    }
+
+    fn get_irq(&self) -> bool {
+        false
+    }
+
+    fn get_nmi(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct CpuState {
+    pc: u16,
+    s: u8,
+    a: u8,
+    x: u8,
+    y: u8,
+    p: u8,
+    ram: Vec<(u16, u8)>,
+}
+
+#[derive(Deserialize)]
+struct TestCase {
+    name: String,
+    initial: CpuState,
+    #[serde(rename = "final")]
+    expected: CpuState,
+    cycles: Vec<Access>,
+}
+
+/// Runs a test case, returning a description of how the CPU differed from it.
+fn run_test_case(bus: &mut TestBus, case: &TestCase) -> Result<(), String> {
+    let initial = &case.initial;
+    for &(addr, data) in initial.ram.iter() {
+        bus.ram[addr as usize] = data;
+    }
+
+    let mut cpu = CPU::new(
+        0,
+        Registers {
+            a: initial.a,
+            x_index: initial.x,
+            y_index: initial.y,
+            pc: split_u16!(initial.pc),
+            sp: initial.s,
+            psr: initial.p.into(),
+            ir: 0x00,
+        },
+    );
+
+    let mut cycles = Vec::new();
+    loop {
+        cpu.tick(bus);
+        cycles.push(std::mem::take(&mut bus.accesses));
+
+        if cpu.is_between_instructions() {
+            break;
+        }
+    }
+
+    // The interrupt disable flag changed by CLI, SEI and PLP is only updated
+    // as the next instruction starts.
+    if let Some(interrupt_disable) = cpu.interrupt_disabled.take() {
+        cpu.registers.psr.set_interrupt_disable(interrupt_disable);
+    }
+
+    let registers = cpu.registers;
+    let actual = CpuState {
+        pc: concat_u8!(registers.pc.0, registers.pc.1),
+        s: registers.sp,
+        a: registers.a,
+        x: registers.x_index,
+        y: registers.y_index,
+        p: u8::from(registers.psr),
+        ram: (case.expected.ram.iter())
+            .map(|&(addr, _)| (addr, bus.ram[addr as usize]))
+            .collect(),
+    };
+
+    // Clears the RAM that was used, for the next test case.
+    for &(addr, _) in initial.ram.iter() {
+        bus.ram[addr as usize] = 0;
+    }
+    for &(addr, _, _) in cycles.iter().flatten() {
+        bus.ram[addr as usize] = 0;
+    }
+
+    for (index, expected) in case.cycles.iter().enumerate() {
+        let accesses = cycles.get(index).map(Vec::as_slice).unwrap_or_default();
+        if accesses != [*expected] {
+            return Err(format!(
+                "cycle {}: expected {expected:02X?}, got {accesses:02X?}",
+                index + 1
+            ));
+        }
+    }
+    if cycles.len() != case.cycles.len() {
+        return Err(format!(
+            "expected {} cycles, took {}",
+            case.cycles.len(),
+            cycles.len()
+        ));
+    }
+    if actual != case.expected {
+        return Err(format!(
+            "expected {:02X?}, got {actual:02X?}",
+            case.expected
+        ));
+    }
+
+    Ok(())
+}
+
+fn parse_test_cases(data: &[u8]) -> Vec<TestCase> {
+    serde_json::from_slice(data).expect("test vectors should be an array of test cases")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::cpu::single_step::{
+        KNOWN_FAILURES, TESTS_DIR, TestBus, parse_test_cases, run_test_case,
+    };
+
+    #[test]
+    fn test_cases_are_run() {
+        // LDA #$42, then JMP ($02FF), whose pointer's high byte is read from $0200.
+        let data = br#"[
+            {
+                "name": "a9 42 00",
+                "initial": {"pc": 4096, "s": 253, "a": 0, "x": 0, "y": 0, "p": 38, "ram": [[4096, 169], [4097, 66]]},
+                "final": {"pc": 4098, "s": 253, "a": 66, "x": 0, "y": 0, "p": 36, "ram": [[4096, 169], [4097, 66]]},
+                "cycles": [[4096, 169, "read"], [4097, 66, "read"]]
+            },
+            {
+                "name": "6c ff 02",
+                "initial": {"pc": 8192, "s": 253, "a": 0, "x": 0, "y": 0, "p": 36, "ram": [[8192, 108], [8193, 255], [8194, 2], [767, 52], [512, 18], [768, 99]]},
+                "final": {"pc": 4660, "s": 253, "a": 0, "x": 0, "y": 0, "p": 36, "ram": [[768, 99]]},
+                "cycles": [[8192, 108, "read"], [8193, 255, "read"], [8194, 2, "read"], [767, 52, "read"], [512, 18, "read"]]
+            }
+        ]"#;
+        let mut bus = TestBus {
+            ram: vec![0; 0x10000],
+            addr: (0, 0),
+            data: 0,
+            accesses: Vec::new(),
+        };
+
+        let mut cases = parse_test_cases(data);
+        assert_eq!(cases.len(), 2);
+        for case in cases.iter() {
+            assert_eq!(run_test_case(&mut bus, case), Ok(()), "{}", case.name);
+        }
+
+        cases[1].cycles.pop();
+        assert!(run_test_case(&mut bus, &cases[1]).is_err());
+    }
+
+    #[test]
+    #[ignore = "slow; needs the nes6502 vectors downloaded by download-tests.sh"]
+    fn single_step_tests() {
+        let mut bus = TestBus {
+            ram: vec![0; 0x10000],
+            addr: (0, 0),
+            data: 0,
+            accesses: Vec::new(),
+        };
+
+        let known_failures: Vec<_> = (KNOWN_FAILURES.iter())
+            .flat_map(|(opcodes, reason)| opcodes.iter().map(move |opcode| (*opcode, *reason)))
+            .collect();
+
+        let mut tested = 0;
+        let mut failures = Vec::new();
+        for opcode in 0..=u8::MAX {
+            if known_failures.iter().any(|&(known, _)| known == opcode) {
+                continue;
+            }
+            let Ok(data) = std::fs::read(format!("{TESTS_DIR}/{opcode:02x}.json")) else {
+                continue;
+            };
+
+            tested += 1;
+            // Only the first failure of each opcode is reported.
+            for case in parse_test_cases(&data) {
+                if let Err(err) = run_test_case(&mut bus, &case) {
+                    failures.push(format!("{opcode:02X} ({}): {err}", case.name));
+                    break;
+                }
+            }
+        }
+
+        assert!(tested > 0, "{TESTS_DIR} should hold the test vectors");
+        println!("{} known failures weren't run:", known_failures.len());
+        for (opcode, reason) in known_failures.iter() {
+            println!("{opcode:02X}: {reason}");
+        }
+        assert!(
+            failures.is_empty(),
+            "{} of {tested} opcodes failed:\n{}",
+            failures.len(),
+            failures.join("\n")
+        );
+    }
+}
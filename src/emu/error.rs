@@ -4,6 +4,10 @@ use std::fmt;
 pub enum Error {
     FileError { err: FileError },
     CartridgeError { err: CartridgeError },
+    PatchError { err: PatchError },
+    ConfigError { err: ConfigError },
+    StateError { err: StateError },
+    MovieError { err: MovieError },
 }
 
 impl fmt::Display for Error {
@@ -11,6 +15,10 @@ impl fmt::Display for Error {
         match self {
             Self::FileError { err } => write!(f, "{err}"),
             Self::CartridgeError { err } => write!(f, "{err}"),
+            Self::PatchError { err } => write!(f, "{err}"),
+            Self::ConfigError { err } => write!(f, "{err}"),
+            Self::StateError { err } => write!(f, "{err}"),
+            Self::MovieError { err } => write!(f, "{err}"),
         }
     }
 }
@@ -27,17 +35,48 @@ impl From<CartridgeError> for Error {
     }
 }
 
+impl From<PatchError> for Error {
+    fn from(err: PatchError) -> Self {
+        Error::PatchError { err }
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(err: ConfigError) -> Self {
+        Error::ConfigError { err }
+    }
+}
+
+impl From<StateError> for Error {
+    fn from(err: StateError) -> Self {
+        Error::StateError { err }
+    }
+}
+
+impl From<MovieError> for Error {
+    fn from(err: MovieError) -> Self {
+        Error::MovieError { err }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum FileError {
     FileOpenFailed { message: String },
+    FileWriteFailed { message: String },
+    ArchiveFailed { message: String },
 }
 
 impl fmt::Display for FileError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let failure = "file load failed";
         match self {
             Self::FileOpenFailed { message } => {
-                write!(f, "{failure}: {message}")
+                write!(f, "file load failed: {message}")
+            }
+            Self::FileWriteFailed { message } => {
+                write!(f, "file write failed: {message}")
+            }
+            Self::ArchiveFailed { message } => {
+                write!(f, "archive read failed: {message}")
             }
         }
     }
@@ -48,6 +87,16 @@ pub enum CartridgeError {
     MissingHeader,
     InvalidHeader { message: String },
     NotSupported { message: String },
+    TruncatedTrainer { expected: usize, actual: usize },
+    TruncatedPrgRom { expected: usize, actual: usize },
+    TruncatedChrRom { expected: usize, actual: usize },
+    JunkHeader { junk: Vec<u8> },
+    TrailingData { size: usize },
+    InvalidDatabaseEntry { line: usize, message: String },
+    InvalidDatabaseImport { message: String },
+    TruncatedDisk { expected: usize, actual: usize },
+    MissingFdsBios { path: Option<String> },
+    InvalidFdsBios { size: usize },
 }
 
 impl fmt::Display for CartridgeError {
@@ -63,6 +112,181 @@ impl fmt::Display for CartridgeError {
             Self::NotSupported { message } => {
                 write!(f, "{failure}: {message}")
             }
+            Self::TruncatedTrainer { expected, actual } => {
+                write!(
+                    f,
+                    "{failure}: trainer truncated (expected {expected} bytes, found {actual})"
+                )
+            }
+            Self::TruncatedPrgRom { expected, actual } => {
+                write!(
+                    f,
+                    "{failure}: PRG ROM truncated (expected {expected} bytes, found {actual})"
+                )
+            }
+            Self::TruncatedChrRom { expected, actual } => {
+                write!(
+                    f,
+                    "{failure}: CHR ROM truncated (expected {expected} bytes, found {actual})"
+                )
+            }
+            Self::JunkHeader { junk } => {
+                let text = String::from_utf8_lossy(junk);
+                write!(
+                    f,
+                    "header bytes 7-15 contain junk ({:?}); they were ignored",
+                    text.trim_end_matches('\0')
+                )
+            }
+            Self::TrailingData { size } => {
+                write!(
+                    f,
+                    "{size} bytes of trailing data after CHR ROM were ignored"
+                )
+            }
+            Self::InvalidDatabaseEntry { line, message } => {
+                write!(f, "game database line {line} is invalid: {message}")
+            }
+            Self::InvalidDatabaseImport { message } => {
+                write!(f, "game database import failed: {message}")
+            }
+            Self::TruncatedDisk { expected, actual } => {
+                write!(
+                    f,
+                    "{failure}: disk image truncated (expected {expected} bytes, found {actual})"
+                )
+            }
+            Self::MissingFdsBios { path: Some(path) } => {
+                write!(
+                    f,
+                    "{failure}: FDS BIOS not found at {path}; supply it with --fds-bios"
+                )
+            }
+            Self::MissingFdsBios { path: None } => {
+                write!(f, "{failure}: FDS disk images require the FDS BIOS")
+            }
+            Self::InvalidFdsBios { size } => {
+                write!(f, "{failure}: FDS BIOS should be 8192 bytes, found {size}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PatchError {
+    UnknownFormat { path: String },
+    InvalidPatch { message: String },
+    SourceChecksumMismatch { expected: u32, actual: u32 },
+    TargetChecksumMismatch { expected: u32, actual: u32 },
+    PatchChecksumMismatch { expected: u32, actual: u32 },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let failure = "patch failed";
+        match self {
+            Self::UnknownFormat { path } => {
+                write!(f, "{failure}: {path} is not an IPS, BPS, or UPS patch")
+            }
+            Self::InvalidPatch { message } => {
+                write!(f, "{failure}: {message}")
+            }
+            Self::SourceChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "{failure}: ROM CRC32 is {actual:08X}, but the patch expects {expected:08X}"
+                )
+            }
+            Self::TargetChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "{failure}: patched ROM CRC32 is {actual:08X}, but the patch expects {expected:08X}"
+                )
+            }
+            Self::PatchChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "{failure}: patch CRC32 is {actual:08X}, but should be {expected:08X}; the patch is corrupt"
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ConfigError {
+    InvalidEntry { line: usize, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let failure = "config load failed";
+        match self {
+            Self::InvalidEntry { line, message } => {
+                write!(f, "{failure}: line {line} is invalid: {message}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StateError {
+    MissingHeader,
+    UnsupportedVersion { expected: u16, actual: u16 },
+    RomMismatch { expected: u32, actual: u32 },
+    Truncated,
+    TrailingData { size: usize },
+    InvalidState { message: String },
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let failure = "state load failed";
+        match self {
+            Self::MissingHeader => {
+                write!(f, "{failure}: not a save state")
+            }
+            Self::UnsupportedVersion { expected, actual } => {
+                write!(
+                    f,
+                    "{failure}: state is format version {actual}, but only version {expected} is supported"
+                )
+            }
+            Self::RomMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "{failure}: state was saved from a ROM with CRC32 {actual:08X}, but the loaded ROM's is {expected:08X}"
+                )
+            }
+            Self::Truncated => {
+                write!(f, "{failure}: state is truncated")
+            }
+            Self::TrailingData { size } => {
+                write!(f, "{failure}: {size} bytes of unexpected data in state")
+            }
+            Self::InvalidState { message } => {
+                write!(f, "{failure}: {message}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MovieError {
+    InvalidLine { line: usize, message: String },
+    NotSupported { message: String },
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let failure = "movie load failed";
+        match self {
+            Self::InvalidLine { line, message } => {
+                write!(f, "{failure}: line {line} is invalid: {message}")
+            }
+            Self::NotSupported { message } => {
+                write!(f, "{failure}: {message}")
+            }
         }
     }
 }
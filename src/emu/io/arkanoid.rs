@@ -0,0 +1,178 @@
+// https://www.nesdev.org/wiki/Arkanoid_controller
+
+use crate::emu::{
+    error::Error,
+    io::{InputDevice, PORT_COUNT},
+    ppu::{PPU, frame::Frame},
+    state::{StateReader, StateWriter},
+};
+
+/// The range of values the potentiometer reports from one end of its travel
+/// to the other.
+const POTENTIOMETER_MIN: u32 = 0x62;
+const POTENTIOMETER_MAX: u32 = 0xF2;
+
+/// The Arkanoid "Vaus" controller: a paddle whose potentiometer is read
+/// serially, inverted and most significant bit first, plus a fire button. The
+/// paddle is turned by moving the mouse horizontally.
+pub struct Arkanoid {
+    /// The bit the potentiometer is read on, if any.
+    data_mask: u8,
+    /// The bit the button is read on, if any.
+    button_mask: u8,
+    strobe: bool,
+    potentiometer: u8,
+    shift_register: u8,
+    button_pressed: bool,
+}
+
+impl Arkanoid {
+    /// Creates the NES version of the controller, which is read entirely on
+    /// `$4017`: the potentiometer on D3, and the button on D4.
+    pub fn nes() -> Self {
+        Self::new(0b_0000_1000, 0b_0001_0000)
+    }
+
+    /// Creates the half of the Famicom version of the controller read through
+    /// the given port. The Famicom version plugs into the expansion port, with
+    /// the button read on D1 of `$4016`, and the potentiometer on D1 of
+    /// `$4017`.
+    pub fn famicom(port: usize) -> Self {
+        debug_assert!(port < PORT_COUNT);
+        match port {
+            0 => Self::new(0, 0b_0000_0010),
+            _ => Self::new(0b_0000_0010, 0),
+        }
+    }
+
+    fn new(data_mask: u8, button_mask: u8) -> Self {
+        let potentiometer = ((POTENTIOMETER_MIN + POTENTIOMETER_MAX) / 2) as u8;
+        Self {
+            data_mask,
+            button_mask,
+            strobe: false,
+            potentiometer,
+            shift_register: potentiometer,
+            button_pressed: false,
+        }
+    }
+}
+
+impl InputDevice for Arkanoid {
+    fn write(&mut self, data: u8) {
+        self.strobe = data & 0b_0000_0001 == 1;
+        if self.strobe {
+            self.shift_register = self.potentiometer;
+        }
+    }
+
+    fn read(&mut self, ppu: &PPU) -> u8 {
+        let response = self.peek(ppu);
+
+        if !self.strobe {
+            self.shift_register <<= 1;
+        }
+
+        response
+    }
+
+    fn peek(&self, _ppu: &PPU) -> u8 {
+        let mut data = 0;
+        // The potentiometer's bits are inverted.
+        if self.shift_register & 0b_1000_0000 == 0 {
+            data |= self.data_mask;
+        }
+        if self.button_pressed {
+            data |= self.button_mask;
+        }
+
+        data
+    }
+
+    fn set_pointer_position(&mut self, position: Option<(i32, i32)>) {
+        // Leave the paddle where it is while the pointer is off the screen.
+        let Some((x, _)) = position else {
+            return;
+        };
+
+        let x = x.clamp(0, Frame::WIDTH as i32 - 1) as u32;
+        let range = POTENTIOMETER_MAX - POTENTIOMETER_MIN;
+        self.potentiometer = (POTENTIOMETER_MIN + x * range / (Frame::WIDTH as u32 - 1)) as u8;
+    }
+
+    fn set_pointer_pressed(&mut self, pressed: bool) {
+        self.button_pressed = pressed;
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.strobe);
+        state.write_u8(self.shift_register);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.strobe = state.read_bool()?;
+        self.shift_register = state.read_u8()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        cartridge::{LoadOptions, ines::INES_TAG, read_cartridge},
+        io::{InputDevice, arkanoid::Arkanoid},
+        ppu::PPU,
+    };
+
+    fn create_ppu() -> PPU {
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 0]);
+        rom.resize(16 + 0x4000, 0);
+        PPU::new(read_cartridge(&rom, &LoadOptions::default()).expect("ROM should load"))
+    }
+
+    /// Latches the controller, then reads it ten times.
+    fn read_bits(arkanoid: &mut Arkanoid, ppu: &PPU) -> Vec<u8> {
+        arkanoid.write(1);
+        arkanoid.write(0);
+        (0..10).map(|_| arkanoid.read(ppu)).collect()
+    }
+
+    #[test]
+    fn nes_potentiometer_is_read_inverted_msb_first() {
+        let ppu = create_ppu();
+        let mut arkanoid = Arkanoid::nes();
+
+        // The leftmost position reads 0x62, and the rightmost 0xF2.
+        arkanoid.set_pointer_position(Some((0, 0)));
+        assert_eq!(
+            read_bits(&mut arkanoid, &ppu),
+            [0x08, 0x00, 0x00, 0x08, 0x08, 0x08, 0x00, 0x08, 0x08, 0x08]
+        );
+
+        arkanoid.set_pointer_position(Some((1000, 0)));
+        arkanoid.set_pointer_pressed(true);
+        assert_eq!(
+            read_bits(&mut arkanoid, &ppu),
+            [0x10, 0x10, 0x10, 0x10, 0x18, 0x18, 0x10, 0x18, 0x18, 0x18]
+        );
+    }
+
+    #[test]
+    fn famicom_halves_are_read_on_d1() {
+        let ppu = create_ppu();
+        let mut button = Arkanoid::famicom(0);
+        let mut paddle = Arkanoid::famicom(1);
+
+        for arkanoid in [&mut button, &mut paddle] {
+            arkanoid.set_pointer_position(Some((0, 0)));
+            arkanoid.set_pointer_pressed(true);
+        }
+
+        assert_eq!(read_bits(&mut button, &ppu), [0x02; 10]);
+        assert_eq!(
+            read_bits(&mut paddle, &ppu),
+            [0x02, 0x00, 0x00, 0x02, 0x02, 0x02, 0x00, 0x02, 0x02, 0x02]
+        );
+    }
+}
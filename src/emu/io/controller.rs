@@ -1,3 +1,10 @@
+use crate::emu::{
+    error::Error,
+    io::InputDevice,
+    ppu::PPU,
+    state::{StateReader, StateWriter},
+};
+
 #[derive(Copy, Clone)]
 pub struct Buttons(u8);
 
@@ -36,11 +43,20 @@ impl From<Buttons> for u8 {
     }
 }
 
+/// The standard controller, which reports its eight buttons one at a time
+/// through a shift register.
+///
+/// The buttons are latched into the shift register while the strobe is high,
+/// so a game reads them as they were when it last strobed the controller,
+/// however the host's input changes afterwards.
+///
+/// https://www.nesdev.org/wiki/Standard_controller
 #[derive(Copy, Clone)]
 pub struct Controller {
     strobe: bool,
     button_index: u8,
     buttons: Buttons,
+    latched_buttons: Buttons,
 }
 
 impl Controller {
@@ -49,32 +65,66 @@ impl Controller {
             strobe: false,
             button_index: 0,
             buttons: Buttons(0),
+            latched_buttons: Buttons(0),
         }
     }
+}
 
-    pub fn write(&mut self, data: u8) {
-        self.strobe = data & 0b_0000_0001 == 1;
-        if self.strobe {
+impl InputDevice for Controller {
+    fn write(&mut self, data: u8) {
+        let strobe = data & 0b_0000_0001 == 1;
+        // The buttons are latched until the strobe falls.
+        if self.strobe || strobe {
+            self.latched_buttons = self.buttons;
             self.button_index = 0;
         }
+        self.strobe = strobe;
     }
 
-    pub fn read(&mut self) -> u8 {
+    fn read(&mut self, ppu: &PPU) -> u8 {
+        let response = self.peek(ppu);
+
+        if !self.strobe && self.button_index <= 7 {
+            self.button_index += 1;
+        }
+
+        response
+    }
+
+    fn peek(&self, _ppu: &PPU) -> u8 {
+        // While the strobe is high, the buttons are latched continuously, so
+        // the first button is reported as it is.
+        if self.strobe {
+            return u8::from(self.buttons) & 1;
+        }
+
+        // Official controllers report 1 once all buttons have been read.
         if self.button_index > 7 {
             return 1;
         }
 
-        let response = (u8::from(self.buttons) & (1 << self.button_index)) >> self.button_index;
+        (u8::from(self.latched_buttons) >> self.button_index) & 1
+    }
 
-        if !self.strobe {
-            self.button_index += 1;
+    fn set_button_pressed(&mut self, controller: usize, button: Buttons, pressed: bool) {
+        if controller == 0 {
+            self.buttons.set_button(u8::from(button), pressed);
         }
+    }
 
-        response
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.strobe);
+        state.write_u8(self.button_index);
+        state.write_u8(self.buttons.into());
+        state.write_u8(self.latched_buttons.into());
     }
 
-    pub fn set_button_pressed(&mut self, button: Buttons, pressed: bool) {
-        self.buttons.set_button(u8::from(button), pressed);
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.strobe = state.read_bool()?;
+        self.button_index = state.read_u8()?;
+        self.buttons = state.read_u8()?.into();
+        self.latched_buttons = state.read_u8()?.into();
+        Ok(())
     }
 }
 
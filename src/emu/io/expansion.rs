@@ -0,0 +1,89 @@
+// https://www.nesdev.org/wiki/Expansion_port
+
+use sdl2::keyboard::Keycode;
+
+use crate::emu::{
+    error::Error,
+    io::{
+        InputDevice,
+        controller::{Buttons, Controller},
+    },
+    ppu::PPU,
+    state::{StateReader, StateWriter},
+};
+
+/// The bit of a `$4016`/`$4017` read driven by the Famicom's own controller.
+const CONTROLLER_DATA_MASK: u8 = 0b_0000_0001;
+/// The bits of a `$4016`/`$4017` read driven by the expansion port.
+const EXPANSION_DATA_MASK: u8 = 0b_0001_1110;
+
+/// One of the Famicom's hardwired controllers, sharing its register with a
+/// device in the expansion port. The controller is read on D0, and the
+/// expansion device on D1–D4.
+pub struct FamicomExpansion {
+    controller: Controller,
+    device: Box<dyn InputDevice>,
+}
+
+impl FamicomExpansion {
+    pub fn new(device: Box<dyn InputDevice>) -> Self {
+        Self {
+            controller: Controller::new(),
+            device,
+        }
+    }
+}
+
+impl InputDevice for FamicomExpansion {
+    fn write(&mut self, data: u8) {
+        self.controller.write(data);
+        self.device.write(data);
+    }
+
+    fn read(&mut self, ppu: &PPU) -> u8 {
+        (self.controller.read(ppu) & CONTROLLER_DATA_MASK)
+            | (self.device.read(ppu) & EXPANSION_DATA_MASK)
+    }
+
+    fn peek(&self, ppu: &PPU) -> u8 {
+        (self.controller.peek(ppu) & CONTROLLER_DATA_MASK)
+            | (self.device.peek(ppu) & EXPANSION_DATA_MASK)
+    }
+
+    /// Controller 0 is the Famicom's own controller; any others belong to the
+    /// expansion device.
+    fn set_button_pressed(&mut self, controller: usize, button: Buttons, pressed: bool) {
+        match controller {
+            0 => self.controller.set_button_pressed(0, button, pressed),
+            _ => self
+                .device
+                .set_button_pressed(controller - 1, button, pressed),
+        }
+    }
+
+    fn set_pointer_position(&mut self, position: Option<(i32, i32)>) {
+        self.device.set_pointer_position(position);
+    }
+
+    fn set_pointer_pressed(&mut self, pressed: bool) {
+        self.device.set_pointer_pressed(pressed);
+    }
+
+    fn set_key_pressed(&mut self, key: Keycode, pressed: bool) {
+        self.device.set_key_pressed(key, pressed);
+    }
+
+    fn is_keyboard(&self) -> bool {
+        self.device.is_keyboard()
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        self.controller.save_state(state);
+        self.device.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.controller.load_state(state)?;
+        self.device.load_state(state)
+    }
+}
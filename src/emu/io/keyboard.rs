@@ -0,0 +1,228 @@
+// https://www.nesdev.org/wiki/Family_BASIC_Keyboard
+
+use sdl2::keyboard::Keycode;
+
+use crate::emu::{
+    error::Error,
+    io::InputDevice,
+    ppu::PPU,
+    state::{StateReader, StateWriter},
+};
+
+/// The number of rows in the key matrix. Each row has two columns of four
+/// keys.
+const ROW_COUNT: usize = 9;
+/// The number of rows the keyboard steps through before wrapping around. The
+/// extra row reads as no keys pressed, which games use to detect the keyboard.
+const SCANNED_ROW_COUNT: usize = ROW_COUNT + 1;
+
+/// The bits of a `$4017` read the key matrix is reported on.
+const KEY_DATA_MASK: u8 = 0b_0001_1110;
+
+/// The host keys for the key matrix. Each column lists the keys reported on
+/// D4, D3, D2 and D1, in that order. Keys without an obvious host equivalent
+/// are mapped to nearby keys: ¥ to backslash, KANA to right alt, GRPH to left
+/// alt, STOP to pause, ESC to backquote (escape quits the emulator), @ to page
+/// up, : to quote, ^ to equals, _ to page down, CTR to left ctrl and CLR HOME
+/// to home.
+const KEY_MATRIX: [[[Keycode; 4]; 2]; ROW_COUNT] = [
+    [
+        [
+            Keycode::RightBracket,
+            Keycode::LeftBracket,
+            Keycode::Return,
+            Keycode::F8,
+        ],
+        [
+            Keycode::Pause,
+            Keycode::Backslash,
+            Keycode::RShift,
+            Keycode::RAlt,
+        ],
+    ],
+    [
+        [
+            Keycode::Semicolon,
+            Keycode::Quote,
+            Keycode::PageUp,
+            Keycode::F7,
+        ],
+        [
+            Keycode::Equals,
+            Keycode::Minus,
+            Keycode::Slash,
+            Keycode::PageDown,
+        ],
+    ],
+    [
+        [Keycode::K, Keycode::L, Keycode::O, Keycode::F6],
+        [Keycode::Num0, Keycode::P, Keycode::Comma, Keycode::Period],
+    ],
+    [
+        [Keycode::J, Keycode::U, Keycode::I, Keycode::F5],
+        [Keycode::Num8, Keycode::Num9, Keycode::N, Keycode::M],
+    ],
+    [
+        [Keycode::H, Keycode::G, Keycode::Y, Keycode::F4],
+        [Keycode::Num6, Keycode::Num7, Keycode::V, Keycode::B],
+    ],
+    [
+        [Keycode::D, Keycode::R, Keycode::T, Keycode::F3],
+        [Keycode::Num4, Keycode::Num5, Keycode::C, Keycode::F],
+    ],
+    [
+        [Keycode::A, Keycode::S, Keycode::W, Keycode::F2],
+        [Keycode::Num3, Keycode::E, Keycode::Z, Keycode::X],
+    ],
+    [
+        [Keycode::LCtrl, Keycode::Q, Keycode::Backquote, Keycode::F1],
+        [Keycode::Num2, Keycode::Num1, Keycode::LAlt, Keycode::LShift],
+    ],
+    [
+        [Keycode::Left, Keycode::Right, Keycode::Up, Keycode::Home],
+        [
+            Keycode::Insert,
+            Keycode::Delete,
+            Keycode::Space,
+            Keycode::Down,
+        ],
+    ],
+];
+
+/// The Family BASIC keyboard, plugged into the Famicom's expansion port. Writes
+/// to `$4016` select a row and column of the key matrix, whose four keys are
+/// then read on D1–D4 of `$4017`.
+pub struct FamilyBasicKeyboard {
+    /// The pressed keys of each row and column, with bit 3 set for the key
+    /// reported on D4, down to bit 0 for D1.
+    pressed_keys: [[u8; 2]; ROW_COUNT],
+    row: usize,
+    column: usize,
+    enabled: bool,
+}
+
+impl FamilyBasicKeyboard {
+    pub fn new() -> Self {
+        Self {
+            pressed_keys: [[0; 2]; ROW_COUNT],
+            row: 0,
+            column: 0,
+            enabled: false,
+        }
+    }
+}
+
+impl InputDevice for FamilyBasicKeyboard {
+    fn write(&mut self, data: u8) {
+        let previous_column = self.column;
+        self.column = ((data & 0b_0000_0010) >> 1) as usize;
+        self.enabled = data & 0b_0000_0100 != 0;
+        if !self.enabled {
+            return;
+        }
+
+        // The row advances each time the column goes from 1 back to 0.
+        if self.column == 0 && previous_column == 1 {
+            self.row = (self.row + 1) % SCANNED_ROW_COUNT;
+        }
+        if data & 0b_0000_0001 != 0 {
+            self.row = 0;
+        }
+    }
+
+    fn read(&mut self, ppu: &PPU) -> u8 {
+        self.peek(ppu)
+    }
+
+    fn peek(&self, _ppu: &PPU) -> u8 {
+        if !self.enabled || self.row >= ROW_COUNT {
+            return KEY_DATA_MASK;
+        }
+
+        // Pressed keys read as 0.
+        (!self.pressed_keys[self.row][self.column] << 1) & KEY_DATA_MASK
+    }
+
+    fn set_key_pressed(&mut self, key: Keycode, pressed: bool) {
+        for (row, columns) in KEY_MATRIX.iter().enumerate() {
+            for (column, keys) in columns.iter().enumerate() {
+                let Some(index) = keys.iter().position(|&k| k == key) else {
+                    continue;
+                };
+
+                let mask = 0b_0000_1000 >> index;
+                if pressed {
+                    self.pressed_keys[row][column] |= mask;
+                } else {
+                    self.pressed_keys[row][column] &= !mask;
+                }
+            }
+        }
+    }
+
+    fn is_keyboard(&self) -> bool {
+        true
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.row as u8);
+        state.write_u8(self.column as u8);
+        state.write_bool(self.enabled);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.row = state.read_u8()? as usize % SCANNED_ROW_COUNT;
+        self.column = (state.read_u8()? & 0b_0000_0001) as usize;
+        self.enabled = state.read_bool()?;
+        Ok(())
+    }
+}
+
+impl Default for FamilyBasicKeyboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::keyboard::Keycode;
+
+    use crate::emu::{
+        cartridge::{LoadOptions, ines::INES_TAG, read_cartridge},
+        io::{InputDevice, keyboard::FamilyBasicKeyboard},
+        ppu::PPU,
+    };
+
+    #[test]
+    fn key_matrix_is_scanned() {
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 0]);
+        rom.resize(16 + 0x4000, 0);
+        let cart = read_cartridge(&rom, &LoadOptions::default()).expect("ROM should load");
+        let ppu = PPU::new(cart);
+
+        let mut keyboard = FamilyBasicKeyboard::new();
+        keyboard.set_key_pressed(Keycode::Return, true);
+        keyboard.set_key_pressed(Keycode::Q, true);
+
+        // Reset to row 0, column 0.
+        keyboard.write(0b_0000_0101);
+        assert_eq!(keyboard.read(&ppu), 0b_0001_1010);
+
+        let mut rows = Vec::new();
+        for _ in 0..9 {
+            keyboard.write(0b_0000_0110);
+            keyboard.write(0b_0000_0100);
+            rows.push(keyboard.read(&ppu));
+        }
+
+        // Rows 1–9 are read, where only row 7 has a key pressed: Q, on D3.
+        assert_eq!(rows[6], 0b_0001_0110);
+        assert!(
+            rows.iter()
+                .enumerate()
+                .all(|(i, &row)| i == 6 || row == 0b_0001_1110)
+        );
+    }
+}
@@ -1 +1,182 @@
+// https://www.nesdev.org/wiki/Input_devices
+// https://www.nesdev.org/wiki/Standard_controller#Output_($4016/$4017_read)
+
+use clap::ValueEnum;
+use sdl2::keyboard::Keycode;
+
+use crate::emu::{
+    cartridge::expansion_device,
+    error::Error,
+    io::{
+        arkanoid::Arkanoid,
+        controller::{Buttons, Controller},
+        expansion::FamicomExpansion,
+        keyboard::FamilyBasicKeyboard,
+        multitap::{FamicomFourPlayerAdapter, FourScore},
+        power_pad::{FamilyTrainer, MatSide, PowerPad},
+        zapper::Zapper,
+    },
+    ppu::PPU,
+    state::{StateReader, StateWriter},
+};
+
+pub mod arkanoid;
 pub mod controller;
+pub mod expansion;
+pub mod keyboard;
+pub mod multitap;
+pub mod power_pad;
+pub mod zapper;
+
+/// The number of controller ports on the console.
+pub const PORT_COUNT: usize = 2;
+
+/// The bits of a `$4016`/`$4017` read that are driven by the device in the
+/// port. The remaining bits are open bus.
+pub const PORT_DATA_MASK: u8 = 0b_0001_1111;
+
+/// A device plugged into one of the controller ports, which the CPU talks to
+/// through `$4016` and `$4017`.
+pub trait InputDevice {
+    /// Handles a write to `$4016`. Bit 0 is the strobe, which latches the
+    /// device's state, and bits 1–2 are extra outputs that some devices use.
+    fn write(&mut self, data: u8);
+    /// Returns the bits the device drives on D0–D4 in response to a read,
+    /// clocking out the next bit of serial data. The PPU is given for devices
+    /// that sense the picture being output.
+    fn read(&mut self, ppu: &PPU) -> u8;
+    /// Returns the bits the device would drive on D0–D4 without clocking it.
+    fn peek(&self, ppu: &PPU) -> u8;
+
+    /// Presses or releases a button on one of the controllers attached
+    /// through the device. Devices without buttons ignore this.
+    fn set_button_pressed(&mut self, _controller: usize, _button: Buttons, _pressed: bool) {}
+
+    /// Moves the mouse pointer to the given position in the frame, or off the
+    /// screen if `None`. Devices that aren't aimed with the mouse ignore this.
+    fn set_pointer_position(&mut self, _position: Option<(i32, i32)>) {}
+
+    /// Presses or releases the mouse button. Devices that aren't controlled by
+    /// the mouse ignore this.
+    fn set_pointer_pressed(&mut self, _pressed: bool) {}
+
+    /// Presses or releases a key on the host keyboard. Devices that aren't
+    /// controlled by the keyboard ignore this.
+    fn set_key_pressed(&mut self, _key: Keycode, _pressed: bool) {}
+
+    /// Returns `true` if the device is typed on with the whole host keyboard,
+    /// so that keys otherwise used as hotkeys should be left to it.
+    fn is_keyboard(&self) -> bool {
+        false
+    }
+
+    /// Writes the state the console can see, such as the strobe, shift
+    /// registers and controller buttons, to a save state. Keys, mat buttons and
+    /// the pointer aren't saved, as they follow the host's own input devices.
+    /// Devices without such state save nothing.
+    fn save_state(&self, _state: &mut StateWriter) {}
+    /// Restores the state written by [`InputDevice::save_state`].
+    fn load_state(&mut self, _state: &mut StateReader) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// The devices plugged into the controller ports.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum InputType {
+    /// A standard controller in each port.
+    #[default]
+    Standard,
+    /// The NES Four Score, for up to four players.
+    FourScore,
+    /// A Famicom four player adapter in the expansion port, for up to four
+    /// players.
+    FamicomFourPlayer,
+    /// A standard controller in the first port, and a Zapper light gun aimed
+    /// with the mouse in the second.
+    Zapper,
+    /// A standard controller in the first port, and the NES Arkanoid
+    /// controller turned with the mouse in the second.
+    ArkanoidNes,
+    /// The Famicom Arkanoid controller in the expansion port, turned with the
+    /// mouse.
+    ArkanoidFamicom,
+    /// A standard controller in the first port, and a Power Pad in the second
+    /// with side A up, stepped on with the keyboard.
+    PowerPadSideA,
+    /// A standard controller in the first port, and a Power Pad in the second
+    /// with side B up, stepped on with the keyboard.
+    PowerPadSideB,
+    /// A Family Trainer mat in the expansion port with side A up, stepped on
+    /// with the keyboard.
+    FamilyTrainerSideA,
+    /// A Family Trainer mat in the expansion port with side B up, stepped on
+    /// with the keyboard.
+    FamilyTrainerSideB,
+    /// The Family BASIC keyboard in the expansion port, typed on with the host
+    /// keyboard.
+    FamilyBasicKeyboard,
+}
+
+impl InputType {
+    /// Returns the input type for an NES 2.0 default expansion device, if it is
+    /// supported.
+    pub fn from_expansion_device(device: u8) -> Option<Self> {
+        match device {
+            expansion_device::STANDARD_CONTROLLERS => Some(InputType::Standard),
+            expansion_device::FOUR_SCORE => Some(InputType::FourScore),
+            expansion_device::FAMICOM_FOUR_PLAYERS_ADAPTER => Some(InputType::FamicomFourPlayer),
+            expansion_device::ZAPPER => Some(InputType::Zapper),
+            expansion_device::ARKANOID_NES => Some(InputType::ArkanoidNes),
+            expansion_device::ARKANOID_FAMICOM => Some(InputType::ArkanoidFamicom),
+            expansion_device::POWER_PAD_SIDE_A => Some(InputType::PowerPadSideA),
+            expansion_device::POWER_PAD_SIDE_B => Some(InputType::PowerPadSideB),
+            expansion_device::FAMILY_TRAINER_SIDE_A => Some(InputType::FamilyTrainerSideA),
+            expansion_device::FAMILY_TRAINER_SIDE_B => Some(InputType::FamilyTrainerSideB),
+            expansion_device::FAMILY_BASIC_KEYBOARD => Some(InputType::FamilyBasicKeyboard),
+            _ => None,
+        }
+    }
+
+    /// Creates the devices for both controller ports.
+    pub fn create_ports(self) -> [Box<dyn InputDevice>; PORT_COUNT] {
+        match self {
+            InputType::Standard => [Box::new(Controller::new()), Box::new(Controller::new())],
+            InputType::FourScore => [Box::new(FourScore::new(0)), Box::new(FourScore::new(1))],
+            InputType::FamicomFourPlayer => [
+                Box::new(FamicomFourPlayerAdapter::new()),
+                Box::new(FamicomFourPlayerAdapter::new()),
+            ],
+            InputType::Zapper => [Box::new(Controller::new()), Box::new(Zapper::new())],
+            InputType::ArkanoidNes => [Box::new(Controller::new()), Box::new(Arkanoid::nes())],
+            InputType::ArkanoidFamicom => [
+                Box::new(FamicomExpansion::new(Box::new(Arkanoid::famicom(0)))),
+                Box::new(FamicomExpansion::new(Box::new(Arkanoid::famicom(1)))),
+            ],
+            InputType::PowerPadSideA => [
+                Box::new(Controller::new()),
+                Box::new(PowerPad::new(MatSide::A)),
+            ],
+            InputType::PowerPadSideB => [
+                Box::new(Controller::new()),
+                Box::new(PowerPad::new(MatSide::B)),
+            ],
+            InputType::FamilyTrainerSideA => [
+                Box::new(Controller::new()),
+                Box::new(FamicomExpansion::new(Box::new(FamilyTrainer::new(
+                    MatSide::A,
+                )))),
+            ],
+            InputType::FamilyTrainerSideB => [
+                Box::new(Controller::new()),
+                Box::new(FamicomExpansion::new(Box::new(FamilyTrainer::new(
+                    MatSide::B,
+                )))),
+            ],
+            InputType::FamilyBasicKeyboard => [
+                Box::new(Controller::new()),
+                Box::new(FamicomExpansion::new(Box::new(FamilyBasicKeyboard::new()))),
+            ],
+        }
+    }
+}
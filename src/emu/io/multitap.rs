@@ -0,0 +1,207 @@
+// https://www.nesdev.org/wiki/Four_player_adapters
+
+use crate::emu::{
+    error::Error,
+    io::{
+        InputDevice,
+        controller::{Buttons, Controller},
+    },
+    ppu::PPU,
+    state::{StateReader, StateWriter},
+};
+
+/// The number of reads before a Four Score port starts returning 1s: eight
+/// for each controller, then eight for the signature.
+const FOUR_SCORE_REPORT_LENGTH: u8 = 24;
+
+/// The signatures the Four Score reports on `$4016` and `$4017` after both
+/// controllers, so games can detect it.
+pub const FOUR_SCORE_SIGNATURES: [u8; 2] = [0b_0000_1000, 0b_0000_0100];
+
+/// One port of the NES Four Score. The port reports the buttons of its first
+/// controller (player 1 or 2), then its second controller (player 3 or 4),
+/// then a signature byte. Like the standard controller, the report is latched
+/// while the strobe is high.
+pub struct FourScore {
+    strobe: bool,
+    read_count: u8,
+    buttons: [Buttons; 2],
+    latched_report: u32,
+    signature: u8,
+}
+
+impl FourScore {
+    /// Creates the half of the Four Score that plugs into the given port.
+    pub fn new(port: usize) -> Self {
+        Self {
+            strobe: false,
+            read_count: 0,
+            buttons: [Buttons::from(0), Buttons::from(0)],
+            latched_report: 0,
+            signature: FOUR_SCORE_SIGNATURES[port],
+        }
+    }
+
+    fn get_report(&self) -> u32 {
+        u8::from(self.buttons[0]) as u32
+            | (u8::from(self.buttons[1]) as u32) << 8
+            | (self.signature as u32) << 16
+    }
+}
+
+impl InputDevice for FourScore {
+    fn write(&mut self, data: u8) {
+        let strobe = data & 0b_0000_0001 == 1;
+        if self.strobe || strobe {
+            self.latched_report = self.get_report();
+            self.read_count = 0;
+        }
+        self.strobe = strobe;
+    }
+
+    fn read(&mut self, ppu: &PPU) -> u8 {
+        let response = self.peek(ppu);
+
+        if !self.strobe && self.read_count < FOUR_SCORE_REPORT_LENGTH {
+            self.read_count += 1;
+        }
+
+        response
+    }
+
+    fn peek(&self, _ppu: &PPU) -> u8 {
+        if self.strobe {
+            return (self.get_report() & 1) as u8;
+        }
+
+        if self.read_count >= FOUR_SCORE_REPORT_LENGTH {
+            return 1;
+        }
+
+        ((self.latched_report >> self.read_count) & 1) as u8
+    }
+
+    fn set_button_pressed(&mut self, controller: usize, button: Buttons, pressed: bool) {
+        if let Some(buttons) = self.buttons.get_mut(controller) {
+            buttons.set_button(u8::from(button), pressed);
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.strobe);
+        state.write_u8(self.read_count);
+        for buttons in self.buttons {
+            state.write_u8(buttons.into());
+        }
+        state.write_u32(self.latched_report);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.strobe = state.read_bool()?;
+        self.read_count = state.read_u8()?;
+        for buttons in self.buttons.iter_mut() {
+            *buttons = state.read_u8()?.into();
+        }
+        self.latched_report = state.read_u32()?;
+        Ok(())
+    }
+}
+
+/// One port of a Famicom four player adapter plugged into the expansion port.
+/// The Famicom's own controller (player 1 or 2) is read on D0, and the
+/// adapter's controller (player 3 or 4) on D1.
+pub struct FamicomFourPlayerAdapter {
+    controllers: [Controller; 2],
+}
+
+impl FamicomFourPlayerAdapter {
+    pub fn new() -> Self {
+        Self {
+            controllers: [Controller::new(), Controller::new()],
+        }
+    }
+}
+
+impl InputDevice for FamicomFourPlayerAdapter {
+    fn write(&mut self, data: u8) {
+        for controller in self.controllers.iter_mut() {
+            controller.write(data);
+        }
+    }
+
+    fn read(&mut self, ppu: &PPU) -> u8 {
+        self.controllers[0].read(ppu) | (self.controllers[1].read(ppu) << 1)
+    }
+
+    fn peek(&self, ppu: &PPU) -> u8 {
+        self.controllers[0].peek(ppu) | (self.controllers[1].peek(ppu) << 1)
+    }
+
+    fn set_button_pressed(&mut self, controller: usize, button: Buttons, pressed: bool) {
+        if let Some(device) = self.controllers.get_mut(controller) {
+            device.set_button_pressed(0, button, pressed);
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        for controller in self.controllers.iter() {
+            controller.save_state(state);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        for controller in self.controllers.iter_mut() {
+            controller.load_state(state)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for FamicomFourPlayerAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        cartridge::{LoadOptions, ines::INES_TAG, read_cartridge},
+        io::{
+            InputDevice,
+            controller::Buttons,
+            multitap::{FOUR_SCORE_SIGNATURES, FourScore},
+        },
+        ppu::PPU,
+    };
+
+    #[test]
+    fn four_score_report() {
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 0]);
+        rom.resize(16 + 0x4000, 0);
+        let cart = read_cartridge(&rom, &LoadOptions::default()).expect("ROM should load");
+        let ppu = PPU::new(cart);
+
+        let mut four_score = FourScore::new(0);
+        four_score.set_button_pressed(0, Buttons::from(Buttons::START), true);
+        four_score.set_button_pressed(1, Buttons::from(Buttons::A), true);
+        four_score.write(1);
+        four_score.write(0);
+        // Buttons pressed after the strobe falls aren't reported.
+        four_score.set_button_pressed(0, Buttons::from(Buttons::B), true);
+
+        let mut report = [0u8; 3];
+        for byte in report.iter_mut() {
+            for bit in 0..8 {
+                *byte |= four_score.read(&ppu) << bit;
+            }
+        }
+
+        assert_eq!(
+            report,
+            [Buttons::START, Buttons::A, FOUR_SCORE_SIGNATURES[0]]
+        );
+        assert_eq!(four_score.read(&ppu), 1);
+    }
+}
@@ -0,0 +1,302 @@
+// https://www.nesdev.org/wiki/Power_Pad
+
+use sdl2::keyboard::Keycode;
+
+use crate::emu::{
+    error::Error,
+    io::InputDevice,
+    ppu::PPU,
+    state::{StateReader, StateWriter},
+};
+
+/// The number of rows and columns of buttons on the mat.
+const MAT_ROWS: usize = 3;
+const MAT_COLUMNS: usize = 4;
+
+/// The host keys for the mat's buttons, laid out in the same grid.
+const MAT_KEYS: [[Keycode; MAT_COLUMNS]; MAT_ROWS] = [
+    [Keycode::U, Keycode::I, Keycode::O, Keycode::P],
+    [Keycode::J, Keycode::K, Keycode::L, Keycode::Semicolon],
+    [Keycode::M, Keycode::Comma, Keycode::Period, Keycode::Slash],
+];
+
+/// The buttons (numbered from 1) reported on D3 of the Power Pad, in order.
+const POWER_PAD_LOW_BUTTONS: [usize; 8] = [2, 1, 5, 9, 6, 10, 11, 7];
+/// The buttons (numbered from 1) reported on D4 of the Power Pad, in order.
+/// The bits after these read as 1.
+const POWER_PAD_HIGH_BUTTONS: [usize; 4] = [4, 3, 12, 8];
+
+/// The side of the mat facing up. Side B has all twelve buttons, numbered
+/// left to right and top to bottom; side A is the same mat flipped over, so
+/// its columns are mirrored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MatSide {
+    A,
+    B,
+}
+
+/// The twelve buttons of a mat, pressed by keys on the host keyboard.
+struct Mat {
+    side: MatSide,
+    /// Bit `n` is set while button `n + 1` is pressed.
+    pressed_buttons: u16,
+}
+
+impl Mat {
+    fn new(side: MatSide) -> Self {
+        Self {
+            side,
+            pressed_buttons: 0,
+        }
+    }
+
+    /// Returns `true` if the button, numbered from 1, is pressed.
+    fn is_pressed(&self, button: usize) -> bool {
+        self.pressed_buttons & (1 << (button - 1)) != 0
+    }
+
+    fn set_key_pressed(&mut self, key: Keycode, pressed: bool) {
+        for (row, keys) in MAT_KEYS.iter().enumerate() {
+            let Some(column) = keys.iter().position(|&k| k == key) else {
+                continue;
+            };
+
+            let column = match self.side {
+                MatSide::A => MAT_COLUMNS - 1 - column,
+                MatSide::B => column,
+            };
+            let mask = 1 << (row * MAT_COLUMNS + column);
+            if pressed {
+                self.pressed_buttons |= mask;
+            } else {
+                self.pressed_buttons &= !mask;
+            }
+        }
+    }
+}
+
+/// The NES Power Pad. Its buttons are read serially through two shift
+/// registers, on D3 and D4 of `$4017`.
+pub struct PowerPad {
+    mat: Mat,
+    strobe: bool,
+    low_shift_register: u8,
+    high_shift_register: u8,
+}
+
+impl PowerPad {
+    pub fn new(side: MatSide) -> Self {
+        Self {
+            mat: Mat::new(side),
+            strobe: false,
+            low_shift_register: 0xFF,
+            high_shift_register: 0xFF,
+        }
+    }
+
+    fn latch(&mut self) {
+        self.low_shift_register = 0;
+        for (bit, &button) in POWER_PAD_LOW_BUTTONS.iter().enumerate() {
+            if self.mat.is_pressed(button) {
+                self.low_shift_register |= 1 << bit;
+            }
+        }
+
+        self.high_shift_register = 0b_1111_0000;
+        for (bit, &button) in POWER_PAD_HIGH_BUTTONS.iter().enumerate() {
+            if self.mat.is_pressed(button) {
+                self.high_shift_register |= 1 << bit;
+            }
+        }
+    }
+}
+
+impl InputDevice for PowerPad {
+    fn write(&mut self, data: u8) {
+        self.strobe = data & 0b_0000_0001 == 1;
+        if self.strobe {
+            self.latch();
+        }
+    }
+
+    fn read(&mut self, ppu: &PPU) -> u8 {
+        let response = self.peek(ppu);
+
+        if self.strobe {
+            self.latch();
+        } else {
+            self.low_shift_register = (self.low_shift_register >> 1) | 0b_1000_0000;
+            self.high_shift_register = (self.high_shift_register >> 1) | 0b_1000_0000;
+        }
+
+        response
+    }
+
+    fn peek(&self, _ppu: &PPU) -> u8 {
+        ((self.low_shift_register & 1) << 3) | ((self.high_shift_register & 1) << 4)
+    }
+
+    fn set_key_pressed(&mut self, key: Keycode, pressed: bool) {
+        self.mat.set_key_pressed(key, pressed);
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.strobe);
+        state.write_u8(self.low_shift_register);
+        state.write_u8(self.high_shift_register);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.strobe = state.read_bool()?;
+        self.low_shift_register = state.read_u8()?;
+        self.high_shift_register = state.read_u8()?;
+        Ok(())
+    }
+}
+
+/// Bandai's Family Trainer mat, the Famicom version of the Power Pad. Instead
+/// of a serial interface, writes select which rows of the mat to ignore, and
+/// reads return the four columns of the remaining rows on D1–D4.
+pub struct FamilyTrainer {
+    mat: Mat,
+    /// Bit 2 is set to ignore the top row, bit 1 the middle and bit 0 the
+    /// bottom.
+    ignored_rows: u8,
+}
+
+impl FamilyTrainer {
+    pub fn new(side: MatSide) -> Self {
+        Self {
+            mat: Mat::new(side),
+            ignored_rows: 0b_0000_0111,
+        }
+    }
+}
+
+impl InputDevice for FamilyTrainer {
+    fn write(&mut self, data: u8) {
+        self.ignored_rows = data & 0b_0000_0111;
+    }
+
+    fn read(&mut self, ppu: &PPU) -> u8 {
+        self.peek(ppu)
+    }
+
+    fn peek(&self, _ppu: &PPU) -> u8 {
+        let mut pressed_columns = 0;
+        for row in 0..MAT_ROWS {
+            if self.ignored_rows & (1 << (MAT_ROWS - 1 - row)) != 0 {
+                continue;
+            }
+
+            for column in 0..MAT_COLUMNS {
+                if self.mat.is_pressed(row * MAT_COLUMNS + column + 1) {
+                    pressed_columns |= 1 << (column + 1);
+                }
+            }
+        }
+
+        // Pressed buttons read as 0.
+        pressed_columns ^ 0b_0001_1110
+    }
+
+    fn set_key_pressed(&mut self, key: Keycode, pressed: bool) {
+        self.mat.set_key_pressed(key, pressed);
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.ignored_rows);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.ignored_rows = state.read_u8()? & 0b_0000_0111;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::keyboard::Keycode;
+
+    use crate::emu::{
+        cartridge::{LoadOptions, ines::INES_TAG, read_cartridge},
+        io::{
+            InputDevice,
+            power_pad::{FamilyTrainer, MatSide, PowerPad},
+        },
+        ppu::PPU,
+    };
+
+    fn create_ppu() -> PPU {
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 0]);
+        rom.resize(16 + 0x4000, 0);
+        PPU::new(read_cartridge(&rom, &LoadOptions::default()).expect("ROM should load"))
+    }
+
+    /// Latches the Power Pad, then reads it ten times, returning the bits read
+    /// on D3 and on D4.
+    fn read_bits(power_pad: &mut PowerPad, ppu: &PPU) -> (Vec<u8>, Vec<u8>) {
+        power_pad.write(1);
+        power_pad.write(0);
+        (0..10)
+            .map(|_| {
+                let data = power_pad.read(ppu);
+                ((data >> 3) & 1, (data >> 4) & 1)
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn power_pad_buttons_are_read_in_order() {
+        let ppu = create_ppu();
+        let mut power_pad = PowerPad::new(MatSide::B);
+
+        // Buttons 2, 9 and 12.
+        for key in [Keycode::I, Keycode::M, Keycode::Slash] {
+            power_pad.set_key_pressed(key, true);
+        }
+
+        let (low, high) = read_bits(&mut power_pad, &ppu);
+        assert_eq!(low, [1, 0, 0, 1, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(high, [0, 0, 1, 0, 1, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn side_a_is_mirrored() {
+        let ppu = create_ppu();
+        let mut power_pad = PowerPad::new(MatSide::A);
+
+        // The top left key is button 4 on side A, and button 1 on side B.
+        power_pad.set_key_pressed(Keycode::U, true);
+        let (low, high) = read_bits(&mut power_pad, &ppu);
+        assert_eq!(low, [0, 0, 0, 0, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(high, [1, 0, 0, 0, 1, 1, 1, 1, 1, 1]);
+
+        power_pad.set_key_pressed(Keycode::U, false);
+        let (low, high) = read_bits(&mut power_pad, &ppu);
+        assert_eq!(low, [0, 0, 0, 0, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(high, [0, 0, 0, 0, 1, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn family_trainer_ignores_masked_rows() {
+        let ppu = create_ppu();
+        let mut trainer = FamilyTrainer::new(MatSide::B);
+
+        // Button 1 in the top row, and button 7 in the middle row.
+        trainer.set_key_pressed(Keycode::U, true);
+        trainer.set_key_pressed(Keycode::L, true);
+
+        for (ignored_rows, expected) in [
+            (0b_0000_0111, 0b_0001_1110),
+            (0b_0000_0011, 0b_0001_1100),
+            (0b_0000_0101, 0b_0001_0110),
+            (0b_0000_0110, 0b_0001_1110),
+            (0b_0000_0000, 0b_0001_0100),
+        ] {
+            trainer.write(ignored_rows);
+            assert_eq!(trainer.read(&ppu), expected);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+// https://www.nesdev.org/wiki/Zapper
+
+use sdl2::rect::Point;
+
+use crate::emu::{
+    io::InputDevice,
+    ppu::{PPU, frame::Frame},
+};
+
+/// Set when the light sensor does *not* see light.
+const LIGHT_NOT_DETECTED_MASK: u8 = 0b_0000_1000;
+/// Set while the trigger is pulled.
+const TRIGGER_PULLED_MASK: u8 = 0b_0001_0000;
+
+/// The number of scanlines the sensor keeps reporting light after the beam
+/// passes a bright pixel.
+const LIGHT_PERSISTENCE_SCANLINES: i32 = 20;
+/// How far from the pointer, in pixels, the sensor can see.
+const SENSOR_RADIUS: i32 = 2;
+/// The minimum brightness (0–255) of a pixel the sensor reacts to.
+const BRIGHTNESS_THRESHOLD: u32 = 0x55;
+
+/// The Zapper light gun, aimed with the mouse. The light sensor reacts to
+/// bright pixels near the pointer shortly after the PPU outputs them.
+pub struct Zapper {
+    /// The position of the pointer in the frame, if it is on the screen.
+    position: Option<(i32, i32)>,
+    trigger_pulled: bool,
+}
+
+impl Zapper {
+    pub fn new() -> Self {
+        Self {
+            position: None,
+            trigger_pulled: false,
+        }
+    }
+
+    /// Returns `true` if a bright pixel near the pointer was output within the
+    /// last few scanlines.
+    fn is_light_detected(&self, ppu: &PPU) -> bool {
+        let Some((x, y)) = self.position else {
+            return false;
+        };
+
+        let scanline = ppu.get_scanline_index() as i32;
+        let dot = ppu.get_cycle_count() as i32;
+        let frame = ppu.get_frame();
+
+        for pixel_y in (y - SENSOR_RADIUS)..=(y + SENSOR_RADIUS) {
+            let scanlines_since_output = scanline - pixel_y;
+            if !(0..=LIGHT_PERSISTENCE_SCANLINES).contains(&scanlines_since_output) {
+                continue;
+            }
+
+            for pixel_x in (x - SENSOR_RADIUS)..=(x + SENSOR_RADIUS) {
+                if !is_in_frame(pixel_x, pixel_y) {
+                    continue;
+                }
+
+                // The beam hasn't reached this pixel yet.
+                if scanlines_since_output == 0 && dot <= pixel_x {
+                    continue;
+                }
+
+                let color = frame.get_pixel(Point::new(pixel_x, pixel_y));
+                let brightness = (color.r as u32 + color.g as u32 + color.b as u32) / 3;
+                if brightness >= BRIGHTNESS_THRESHOLD {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+}
+
+fn is_in_frame(x: i32, y: i32) -> bool {
+    (0..Frame::WIDTH as i32).contains(&x) && (0..Frame::HEIGHT as i32).contains(&y)
+}
+
+impl InputDevice for Zapper {
+    fn write(&mut self, _data: u8) {
+        // ignore; the Zapper has no serial interface
+    }
+
+    fn read(&mut self, ppu: &PPU) -> u8 {
+        self.peek(ppu)
+    }
+
+    fn peek(&self, ppu: &PPU) -> u8 {
+        let mut data = 0;
+        if !self.is_light_detected(ppu) {
+            data |= LIGHT_NOT_DETECTED_MASK;
+        }
+        if self.trigger_pulled {
+            data |= TRIGGER_PULLED_MASK;
+        }
+
+        data
+    }
+
+    fn set_pointer_position(&mut self, position: Option<(i32, i32)>) {
+        self.position = position.filter(|&(x, y)| is_in_frame(x, y));
+    }
+
+    fn set_pointer_pressed(&mut self, pressed: bool) {
+        self.trigger_pulled = pressed;
+    }
+}
+
+impl Default for Zapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        cartridge::{LoadOptions, ines::INES_TAG, read_cartridge},
+        io::{InputDevice, zapper::Zapper},
+        ppu::{OAM_SIZE, PPU},
+    };
+
+    /// Ticks the PPU until the beam reaches the given scanline and dot.
+    fn run_to(ppu: &mut PPU, scanline: u32, dot: u32) {
+        while ppu.get_scanline_index() != scanline || ppu.get_cycle_count() < dot {
+            ppu.tick();
+        }
+    }
+
+    #[test]
+    fn light_is_sensed_behind_the_beam() {
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 0]);
+        rom.resize(16 + 0x4000, 0);
+        let cart = read_cartridge(&rom, &LoadOptions::default()).expect("ROM should load");
+        let mut ppu = PPU::new(cart);
+
+        // A white 8x8 sprite at (100, 100) on a black backdrop.
+        let mut write_vram = |addr: u16, data: &[u8]| {
+            ppu.write_ppu_addr((addr >> 8) as u8);
+            ppu.write_ppu_addr(addr as u8);
+            for byte in data {
+                ppu.write_ppu_data(*byte);
+            }
+        };
+        write_vram(0x0010, &[0xFF; 8]);
+        write_vram(0x3F00, &[0x0F]);
+        write_vram(0x3F11, &[0x30]);
+        let mut oam = [0xFF; OAM_SIZE];
+        oam[..4].copy_from_slice(&[100, 1, 0, 100]);
+        ppu.write_oam_dma(&oam);
+
+        // Let the frame be rendered.
+        while ppu.get_frame_count() == 1 {
+            ppu.tick();
+        }
+
+        let mut zapper = Zapper::new();
+        zapper.set_pointer_position(Some((102, 102)));
+
+        run_to(&mut ppu, 50, 0);
+        assert_eq!(zapper.peek(&ppu), 0b_0000_1000);
+
+        // The beam is on the sprite's first row, but left of it.
+        run_to(&mut ppu, 100, 0);
+        assert_eq!(zapper.peek(&ppu), 0b_0000_1000);
+
+        run_to(&mut ppu, 100, 110);
+        assert_eq!(zapper.peek(&ppu), 0b_0000_0000);
+
+        // The sensor keeps seeing the rows near the pointer for a while.
+        run_to(&mut ppu, 124, 0);
+        assert_eq!(zapper.peek(&ppu), 0b_0000_0000);
+
+        run_to(&mut ppu, 125, 0);
+        assert_eq!(zapper.peek(&ppu), 0b_0000_1000);
+
+        zapper.set_pointer_pressed(true);
+        assert_eq!(zapper.peek(&ppu), 0b_0001_1000);
+
+        // Pointing away from the sprite sees nothing.
+        run_to(&mut ppu, 100, 110);
+        zapper.set_pointer_position(Some((20, 102)));
+        assert_eq!(zapper.peek(&ppu), 0b_0001_1000);
+    }
+}
@@ -5,6 +5,7 @@ pub mod error;
 pub mod io;
 pub mod nes;
 pub mod ppu;
+pub mod state;
 
 #[macro_export]
 macro_rules! concat_u8 {
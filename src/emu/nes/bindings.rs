@@ -0,0 +1,1002 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use sdl2::{controller::Button, event::Event, keyboard::Keycode};
+use serde::{Deserialize, Serialize};
+
+use crate::emu::{
+    error::{ConfigError, Error, FileError},
+    io::controller::Buttons,
+    nes::{gamepad::Gamepads, host_input::FRAMES_PER_SECOND},
+};
+
+/// The number of players that can have bindings.
+pub const PLAYER_COUNT: usize = 4;
+
+/// The buttons of a standard controller, with their names in the config file.
+pub const BUTTONS: [(&str, u8); 8] = [
+    ("up", Buttons::UP),
+    ("down", Buttons::DOWN),
+    ("left", Buttons::LEFT),
+    ("right", Buttons::RIGHT),
+    ("select", Buttons::SELECT),
+    ("start", Buttons::START),
+    ("b", Buttons::B),
+    ("a", Buttons::A),
+];
+
+/// An input on a player's controller that host keys and gamepad buttons can
+/// be bound to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Input {
+    /// Holds the buttons down.
+    Button(u8),
+    /// Presses and releases the buttons repeatedly, at the player's turbo
+    /// rate.
+    Turbo(u8),
+}
+
+/// The inputs of a player's controller, in the order they're bound and
+/// written to the config file, with their names in the config file.
+pub const INPUTS: [(&str, Input); INPUT_COUNT] = [
+    ("up", Input::Button(Buttons::UP)),
+    ("down", Input::Button(Buttons::DOWN)),
+    ("left", Input::Button(Buttons::LEFT)),
+    ("right", Input::Button(Buttons::RIGHT)),
+    ("select", Input::Button(Buttons::SELECT)),
+    ("start", Input::Button(Buttons::START)),
+    ("b", Input::Button(Buttons::B)),
+    ("a", Input::Button(Buttons::A)),
+    ("turbo_b", Input::Turbo(Buttons::B)),
+    ("turbo_a", Input::Turbo(Buttons::A)),
+];
+const INPUT_COUNT: usize = 10;
+
+/// How far, out of 32767, an analog stick must be pushed to press a direction
+/// on the D-pad.
+pub const DEFAULT_STICK_THRESHOLD: i16 = 0x4000;
+
+/// How many times a second turbo buttons are pressed. Rates must divide the
+/// frame rate evenly, like 30, 20 or 15, so that every press is the same
+/// number of frames long.
+pub const DEFAULT_TURBO_RATE: u32 = 15;
+/// The fastest turbo rate, which presses the button every other frame.
+pub const MAX_TURBO_RATE: u32 = 30;
+
+/// Separates the frames of a macro.
+const FRAME_SEPARATOR: char = ',';
+/// Separates the buttons held on a frame of a macro.
+const FRAME_BUTTON_SEPARATOR: char = '+';
+/// Follows a frame of a macro with the number of times it repeats.
+const FRAME_REPEAT_SEPARATOR: char = '*';
+/// Stands for a frame of a macro with no buttons held.
+const EMPTY_FRAME: &str = "-";
+
+/// The names of gamepad buttons in the config file, which are the same as in
+/// SDL's game controller mapping strings.
+const GAMEPAD_BUTTON_NAMES: [(&str, Button); 21] = [
+    ("a", Button::A),
+    ("b", Button::B),
+    ("x", Button::X),
+    ("y", Button::Y),
+    ("back", Button::Back),
+    ("guide", Button::Guide),
+    ("start", Button::Start),
+    ("leftstick", Button::LeftStick),
+    ("rightstick", Button::RightStick),
+    ("leftshoulder", Button::LeftShoulder),
+    ("rightshoulder", Button::RightShoulder),
+    ("dpup", Button::DPadUp),
+    ("dpdown", Button::DPadDown),
+    ("dpleft", Button::DPadLeft),
+    ("dpright", Button::DPadRight),
+    ("misc1", Button::Misc1),
+    ("paddle1", Button::Paddle1),
+    ("paddle2", Button::Paddle2),
+    ("paddle3", Button::Paddle3),
+    ("paddle4", Button::Paddle4),
+    ("touchpad", Button::Touchpad),
+];
+
+const CONFIG_HEADER: &str = "# Green NES input bindings\n\n";
+const CONFIG_DIR_NAME: &str = "green-nes";
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// A host device that inputs can be bound to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum BindingDevice {
+    Keyboard,
+    Gamepad,
+}
+
+impl BindingDevice {
+    fn get_name(self) -> &'static str {
+        match self {
+            BindingDevice::Keyboard => "keyboard",
+            BindingDevice::Gamepad => "gamepad",
+        }
+    }
+}
+
+/// What pressing a bound key or gamepad button does.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Holds an input on a player's controller.
+    Input { player: usize, input: Input },
+    /// Plays the macro with the given index.
+    Macro { index: usize },
+}
+
+/// The host inputs bound to one player's controller.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerBindings {
+    /// The key bound to each input, in the order of `INPUTS`.
+    pub keys: [Option<Keycode>; INPUT_COUNT],
+    /// The gamepad button bound to each input, in the order of `INPUTS`.
+    pub gamepad_buttons: [Option<Button>; INPUT_COUNT],
+    /// How far the gamepad's left stick must be pushed to press a direction.
+    pub stick_threshold: i16,
+    /// How many times a second turbo buttons are pressed.
+    pub turbo_rate: u32,
+}
+
+impl PlayerBindings {
+    fn new(keys: [Option<Keycode>; INPUT_COUNT]) -> Self {
+        Self {
+            keys,
+            gamepad_buttons: [
+                Some(Button::DPadUp),
+                Some(Button::DPadDown),
+                Some(Button::DPadLeft),
+                Some(Button::DPadRight),
+                Some(Button::Back),
+                Some(Button::Start),
+                Some(Button::A),
+                Some(Button::B),
+                Some(Button::X),
+                Some(Button::Y),
+            ],
+            stick_threshold: DEFAULT_STICK_THRESHOLD,
+            turbo_rate: DEFAULT_TURBO_RATE,
+        }
+    }
+}
+
+/// A sequence of button presses played on a player's controller, one entry
+/// per frame, when its key or gamepad button is pressed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Macro {
+    pub name: String,
+    pub player: usize,
+    pub key: Option<Keycode>,
+    /// The gamepad button that plays the macro, on the player's gamepad.
+    pub gamepad_button: Option<Button>,
+    /// The buttons held on each frame of the macro.
+    pub frames: Vec<u8>,
+}
+
+/// The host inputs bound to each player's controller, loaded from a TOML
+/// config file with a section per player and device, and a section per macro:
+///
+/// ```toml
+/// [player1]
+/// turbo_rate = 15
+///
+/// [player1.keyboard]
+/// up = "Up"
+/// turbo_a = "Q"
+///
+/// [player1.gamepad]
+/// a = "b"
+/// stick_threshold = 16384
+///
+/// [macros.hadouken]
+/// player = 1
+/// key = "E"
+/// frames = "down*2, down+right*2, right+a, -*4"
+/// ```
+///
+/// Keys are named as by SDL, and gamepad buttons as in SDL's game controller
+/// mappings. An empty name unbinds a button, and buttons that aren't listed
+/// keep their default bindings. Each frame of a macro lists the buttons held,
+/// or `-` for none, and may be repeated with `*`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(from = "Config", into = "Config")]
+pub struct Bindings {
+    pub players: [PlayerBindings; PLAYER_COUNT],
+    /// The macros, sorted by name.
+    pub macros: Vec<Macro>,
+}
+
+impl Bindings {
+    /// Returns the path of the config file in the user's config directory.
+    pub fn default_path() -> PathBuf {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+
+        match config_dir {
+            Some(dir) => dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME),
+            None => PathBuf::from(CONFIG_FILE_NAME),
+        }
+    }
+
+    /// Loads the bindings from a config file. A missing config file is not an
+    /// error; the default bindings are returned.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            Error::from(FileError::FileOpenFailed {
+                message: format!("{}: {e}", path.display()),
+            })
+        })?;
+
+        Self::parse(&text)
+    }
+
+    /// Writes the bindings to a config file, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let write_failed = |e: std::io::Error| {
+            Error::from(FileError::FileWriteFailed {
+                message: format!("{}: {e}", path.display()),
+            })
+        };
+
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(dir).map_err(write_failed)?;
+        }
+
+        std::fs::write(path, self.to_config()).map_err(write_failed)
+    }
+
+    /// Parses bindings from the text of a config file, on top of the defaults.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        toml::from_str(text).map_err(|e| {
+            // Errors in the file as a whole are reported on the first line.
+            let line = e
+                .span()
+                .map_or(1, |span| text[..span.start].matches('\n').count() + 1);
+            Error::from(ConfigError::InvalidEntry {
+                line,
+                message: e.message().to_string(),
+            })
+        })
+    }
+
+    /// Returns the text of a config file holding these bindings.
+    pub fn to_config(&self) -> String {
+        let config = toml::to_string(self).expect("bindings should always serialize");
+        format!("{CONFIG_HEADER}{config}")
+    }
+
+    /// Interactively binds each input of a player's controller, by prompting
+    /// for them in turn and recording the next key or gamepad button pressed
+    /// in a window. Escape skips an input, and backspace unbinds it. Returns
+    /// `false` if the window was closed before every input was bound.
+    pub fn record(&mut self, player: usize, device: BindingDevice) -> Result<bool, String> {
+        let sdl_context = sdl2::init()?;
+        let video_subsystem = sdl_context.video()?;
+        let title = format!(
+            "Green NES - Binding player {} {}",
+            player + 1,
+            device.get_name()
+        );
+        let _window = video_subsystem
+            .window(&title, 480, 120)
+            .position_centered()
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let mut gamepads = Gamepads::new(sdl_context.game_controller()?);
+        let mut event_pump = sdl_context.event_pump()?;
+        let bindings = &mut self.players[player];
+
+        let input_name = match device {
+            BindingDevice::Keyboard => "key",
+            BindingDevice::Gamepad => "gamepad button",
+        };
+
+        for (index, (name, _)) in INPUTS.iter().enumerate() {
+            println!("Press the {input_name} for {name} (Escape to skip, Backspace to unbind)");
+
+            loop {
+                match event_pump.wait_event() {
+                    Event::Quit { .. } => return Ok(false),
+
+                    Event::KeyDown {
+                        keycode: Some(Keycode::Escape),
+                        ..
+                    } => break,
+                    Event::KeyDown {
+                        keycode: Some(Keycode::Backspace),
+                        ..
+                    } => {
+                        match device {
+                            BindingDevice::Keyboard => bindings.keys[index] = None,
+                            BindingDevice::Gamepad => bindings.gamepad_buttons[index] = None,
+                        }
+                        break;
+                    }
+                    Event::KeyDown {
+                        keycode: Some(key),
+                        repeat: false,
+                        ..
+                    } if device == BindingDevice::Keyboard => {
+                        bindings.keys[index] = Some(key);
+                        break;
+                    }
+
+                    Event::ControllerDeviceAdded { which, .. } => {
+                        gamepads.add(which);
+                    }
+                    Event::ControllerDeviceRemoved { which, .. } => {
+                        gamepads.remove(which);
+                    }
+                    Event::ControllerButtonDown { button, .. }
+                        if device == BindingDevice::Gamepad =>
+                    {
+                        bindings.gamepad_buttons[index] = Some(button);
+                        break;
+                    }
+
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the actions the key is bound to.
+    pub fn find_key(&self, key: Keycode) -> Vec<Action> {
+        let mut actions = Vec::new();
+
+        for (player, bindings) in self.players.iter().enumerate() {
+            for (bound, (_, input)) in bindings.keys.iter().zip(INPUTS) {
+                if *bound == Some(key) {
+                    actions.push(Action::Input { player, input });
+                }
+            }
+        }
+        for (index, m) in self.macros.iter().enumerate() {
+            if m.key == Some(key) {
+                actions.push(Action::Macro { index });
+            }
+        }
+
+        actions
+    }
+
+    /// Returns the actions the button on the given player's gamepad is bound
+    /// to.
+    pub fn find_gamepad_button(&self, player: usize, button: Button) -> Vec<Action> {
+        let mut actions = Vec::new();
+
+        for (bound, (_, input)) in self.players[player].gamepad_buttons.iter().zip(INPUTS) {
+            if *bound == Some(button) {
+                actions.push(Action::Input { player, input });
+            }
+        }
+        for (index, m) in self.macros.iter().enumerate() {
+            if m.player == player && m.gamepad_button == Some(button) {
+                actions.push(Action::Macro { index });
+            }
+        }
+
+        actions
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            players: [
+                PlayerBindings::new([
+                    Some(Keycode::Up),
+                    Some(Keycode::Down),
+                    Some(Keycode::Left),
+                    Some(Keycode::Right),
+                    Some(Keycode::Space),
+                    Some(Keycode::Return),
+                    Some(Keycode::S),
+                    Some(Keycode::A),
+                    Some(Keycode::W),
+                    Some(Keycode::Q),
+                ]),
+                PlayerBindings::new([
+                    Some(Keycode::Kp8),
+                    Some(Keycode::Kp5),
+                    Some(Keycode::Kp4),
+                    Some(Keycode::Kp6),
+                    Some(Keycode::KpPlus),
+                    Some(Keycode::KpEnter),
+                    Some(Keycode::Kp2),
+                    Some(Keycode::Kp1),
+                    None,
+                    None,
+                ]),
+                PlayerBindings::new([
+                    Some(Keycode::T),
+                    Some(Keycode::G),
+                    Some(Keycode::F),
+                    Some(Keycode::H),
+                    Some(Keycode::R),
+                    Some(Keycode::Y),
+                    Some(Keycode::X),
+                    Some(Keycode::Z),
+                    None,
+                    None,
+                ]),
+                PlayerBindings::new([
+                    Some(Keycode::I),
+                    Some(Keycode::K),
+                    Some(Keycode::J),
+                    Some(Keycode::L),
+                    Some(Keycode::U),
+                    Some(Keycode::O),
+                    Some(Keycode::M),
+                    Some(Keycode::N),
+                    None,
+                    None,
+                ]),
+            ],
+            macros: Vec::new(),
+        }
+    }
+}
+
+/// The layout of the config file, which bindings are read from and written
+/// to. Anything left out of the file keeps its default binding.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Config {
+    player1: PlayerConfig,
+    player2: PlayerConfig,
+    player3: PlayerConfig,
+    player4: PlayerConfig,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    macros: BTreeMap<String, MacroConfig>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct PlayerConfig {
+    turbo_rate: Option<TurboRate>,
+    keyboard: KeyboardConfig,
+    gamepad: GamepadConfig,
+}
+
+/// The key bound to each input of a player's controller.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeyboardConfig {
+    up: Option<KeyName>,
+    down: Option<KeyName>,
+    left: Option<KeyName>,
+    right: Option<KeyName>,
+    select: Option<KeyName>,
+    start: Option<KeyName>,
+    b: Option<KeyName>,
+    a: Option<KeyName>,
+    turbo_b: Option<KeyName>,
+    turbo_a: Option<KeyName>,
+}
+
+impl KeyboardConfig {
+    fn new(keys: [Option<Keycode>; INPUT_COUNT]) -> Self {
+        let [up, down, left, right, select, start, b, a, turbo_b, turbo_a] =
+            keys.map(|key| Some(KeyName(key)));
+        Self {
+            up,
+            down,
+            left,
+            right,
+            select,
+            start,
+            b,
+            a,
+            turbo_b,
+            turbo_a,
+        }
+    }
+
+    /// Returns the binding of each input, in the order of `INPUTS`.
+    fn into_inputs(self) -> [Option<KeyName>; INPUT_COUNT] {
+        [
+            self.up,
+            self.down,
+            self.left,
+            self.right,
+            self.select,
+            self.start,
+            self.b,
+            self.a,
+            self.turbo_b,
+            self.turbo_a,
+        ]
+    }
+}
+
+/// The gamepad button bound to each input of a player's controller, and how
+/// far the gamepad's left stick must be pushed.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct GamepadConfig {
+    up: Option<GamepadButtonName>,
+    down: Option<GamepadButtonName>,
+    left: Option<GamepadButtonName>,
+    right: Option<GamepadButtonName>,
+    select: Option<GamepadButtonName>,
+    start: Option<GamepadButtonName>,
+    b: Option<GamepadButtonName>,
+    a: Option<GamepadButtonName>,
+    turbo_b: Option<GamepadButtonName>,
+    turbo_a: Option<GamepadButtonName>,
+    stick_threshold: Option<StickThreshold>,
+}
+
+impl GamepadConfig {
+    fn new(buttons: [Option<Button>; INPUT_COUNT], stick_threshold: i16) -> Self {
+        let [up, down, left, right, select, start, b, a, turbo_b, turbo_a] =
+            buttons.map(|button| Some(GamepadButtonName(button)));
+        Self {
+            up,
+            down,
+            left,
+            right,
+            select,
+            start,
+            b,
+            a,
+            turbo_b,
+            turbo_a,
+            stick_threshold: Some(StickThreshold(stick_threshold)),
+        }
+    }
+
+    /// Returns the binding of each input, in the order of `INPUTS`.
+    fn into_inputs(self) -> [Option<GamepadButtonName>; INPUT_COUNT] {
+        [
+            self.up,
+            self.down,
+            self.left,
+            self.right,
+            self.select,
+            self.start,
+            self.b,
+            self.a,
+            self.turbo_b,
+            self.turbo_a,
+        ]
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct MacroConfig {
+    player: Option<PlayerNumber>,
+    key: Option<KeyName>,
+    gamepad_button: Option<GamepadButtonName>,
+    frames: Option<Frames>,
+}
+
+impl From<Config> for Bindings {
+    fn from(config: Config) -> Self {
+        let mut bindings = Bindings::default();
+
+        let players = [
+            config.player1,
+            config.player2,
+            config.player3,
+            config.player4,
+        ];
+        for (player, config) in bindings.players.iter_mut().zip(players) {
+            if let Some(TurboRate(rate)) = config.turbo_rate {
+                player.turbo_rate = rate;
+            }
+            if let Some(StickThreshold(threshold)) = config.gamepad.stick_threshold {
+                player.stick_threshold = threshold;
+            }
+
+            for (bound, key) in player.keys.iter_mut().zip(config.keyboard.into_inputs()) {
+                if let Some(KeyName(key)) = key {
+                    *bound = key;
+                }
+            }
+            for (bound, button) in player
+                .gamepad_buttons
+                .iter_mut()
+                .zip(config.gamepad.into_inputs())
+            {
+                if let Some(GamepadButtonName(button)) = button {
+                    *bound = button;
+                }
+            }
+        }
+
+        bindings.macros = config
+            .macros
+            .into_iter()
+            .map(|(name, config)| Macro {
+                name,
+                player: config.player.map_or(0, |PlayerNumber(player)| player),
+                key: config.key.and_then(|KeyName(key)| key),
+                gamepad_button: config
+                    .gamepad_button
+                    .and_then(|GamepadButtonName(button)| button),
+                frames: config
+                    .frames
+                    .map(|Frames(frames)| frames)
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        bindings
+    }
+}
+
+impl From<Bindings> for Config {
+    fn from(bindings: Bindings) -> Self {
+        let [player1, player2, player3, player4] = bindings.players.map(|player| PlayerConfig {
+            turbo_rate: Some(TurboRate(player.turbo_rate)),
+            keyboard: KeyboardConfig::new(player.keys),
+            gamepad: GamepadConfig::new(player.gamepad_buttons, player.stick_threshold),
+        });
+
+        let macros = bindings
+            .macros
+            .into_iter()
+            .map(|m| {
+                let config = MacroConfig {
+                    player: Some(PlayerNumber(m.player)),
+                    key: Some(KeyName(m.key)),
+                    gamepad_button: Some(GamepadButtonName(m.gamepad_button)),
+                    frames: Some(Frames(m.frames)),
+                };
+                (m.name, config)
+            })
+            .collect();
+
+        Config {
+            player1,
+            player2,
+            player3,
+            player4,
+            macros,
+        }
+    }
+}
+
+/// A key, named as by SDL. An empty name is no key.
+#[derive(Copy, Clone, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+struct KeyName(Option<Keycode>);
+
+impl TryFrom<String> for KeyName {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        parse_key(&name).map(KeyName)
+    }
+}
+
+impl From<KeyName> for String {
+    fn from(key: KeyName) -> Self {
+        get_key_name(key.0)
+    }
+}
+
+/// A gamepad button, named as in SDL's game controller mappings. An empty name
+/// is no button.
+#[derive(Copy, Clone, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+struct GamepadButtonName(Option<Button>);
+
+impl TryFrom<String> for GamepadButtonName {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        parse_gamepad_button(&name).map(GamepadButtonName)
+    }
+}
+
+impl From<GamepadButtonName> for String {
+    fn from(button: GamepadButtonName) -> Self {
+        get_gamepad_button_name(button.0).to_string()
+    }
+}
+
+/// The frames of a macro, like `down*2, down+right, -`.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+struct Frames(Vec<u8>);
+
+impl TryFrom<String> for Frames {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        parse_frames(&text).map(Frames)
+    }
+}
+
+impl From<Frames> for String {
+    fn from(frames: Frames) -> Self {
+        format_frames(&frames.0)
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Serialize)]
+#[serde(try_from = "u32", into = "u32")]
+struct TurboRate(u32);
+
+impl TryFrom<u32> for TurboRate {
+    type Error = String;
+
+    fn try_from(rate: u32) -> Result<Self, Self::Error> {
+        match rate {
+            1..=MAX_TURBO_RATE if FRAMES_PER_SECOND.is_multiple_of(rate) => Ok(TurboRate(rate)),
+            _ => Err(format!("invalid turbo rate: {rate}")),
+        }
+    }
+}
+
+impl From<TurboRate> for u32 {
+    fn from(rate: TurboRate) -> Self {
+        rate.0
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Serialize)]
+#[serde(try_from = "i16", into = "i16")]
+struct StickThreshold(i16);
+
+impl TryFrom<i16> for StickThreshold {
+    type Error = String;
+
+    fn try_from(threshold: i16) -> Result<Self, Self::Error> {
+        match threshold {
+            1.. => Ok(StickThreshold(threshold)),
+            _ => Err(format!("invalid stick threshold: {threshold}")),
+        }
+    }
+}
+
+impl From<StickThreshold> for i16 {
+    fn from(threshold: StickThreshold) -> Self {
+        threshold.0
+    }
+}
+
+/// A player, numbered from 1 in the config file and from 0 in memory.
+#[derive(Copy, Clone, Deserialize, Serialize)]
+#[serde(try_from = "usize", into = "usize")]
+struct PlayerNumber(usize);
+
+impl TryFrom<usize> for PlayerNumber {
+    type Error = String;
+
+    fn try_from(number: usize) -> Result<Self, Self::Error> {
+        match number {
+            1..=PLAYER_COUNT => Ok(PlayerNumber(number - 1)),
+            _ => Err(format!("invalid player: {number}")),
+        }
+    }
+}
+
+impl From<PlayerNumber> for usize {
+    fn from(player: PlayerNumber) -> Self {
+        player.0 + 1
+    }
+}
+
+/// Parses a key name, where an empty name is no key.
+fn parse_key(name: &str) -> Result<Option<Keycode>, String> {
+    match name {
+        "" => Ok(None),
+        name => Keycode::from_name(name)
+            .map(Some)
+            .ok_or_else(|| format!("unknown key: {name}")),
+    }
+}
+
+fn get_key_name(key: Option<Keycode>) -> String {
+    key.map(|key| key.name()).unwrap_or_default()
+}
+
+/// Parses a gamepad button name, where an empty name is no button.
+fn parse_gamepad_button(name: &str) -> Result<Option<Button>, String> {
+    if name.is_empty() {
+        return Ok(None);
+    }
+
+    GAMEPAD_BUTTON_NAMES
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, button)| Some(*button))
+        .ok_or_else(|| format!("unknown gamepad button: {name}"))
+}
+
+fn get_gamepad_button_name(button: Option<Button>) -> &'static str {
+    GAMEPAD_BUTTON_NAMES
+        .iter()
+        .find(|(_, b)| Some(*b) == button)
+        .map(|(name, _)| *name)
+        .unwrap_or_default()
+}
+
+/// Parses the frames of a macro, like `down*2, down+right, -`.
+fn parse_frames(text: &str) -> Result<Vec<u8>, String> {
+    let mut frames = Vec::new();
+
+    for frame in text.split(FRAME_SEPARATOR) {
+        let (buttons, repeat) = match frame.split_once(FRAME_REPEAT_SEPARATOR) {
+            Some((buttons, repeat)) => {
+                let repeat = repeat
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid frame repeat: {frame}"))?;
+                (buttons.trim(), repeat)
+            }
+            None => (frame.trim(), 1),
+        };
+
+        let mut held = 0;
+        if buttons != EMPTY_FRAME {
+            for name in buttons.split(FRAME_BUTTON_SEPARATOR) {
+                let name = name.trim();
+                let (_, button) = BUTTONS
+                    .iter()
+                    .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| format!("unknown button in frame: {name}"))?;
+                held |= button;
+            }
+        }
+
+        frames.extend(std::iter::repeat_n(held, repeat));
+    }
+
+    Ok(frames)
+}
+
+/// Returns the text of the frames of a macro, with repeated frames combined.
+fn format_frames(frames: &[u8]) -> String {
+    let mut runs: Vec<(u8, usize)> = Vec::new();
+    for &held in frames {
+        match runs.last_mut() {
+            Some((last, count)) if *last == held => *count += 1,
+            _ => runs.push((held, 1)),
+        }
+    }
+
+    let runs: Vec<String> = runs
+        .into_iter()
+        .map(|(held, count)| {
+            let names: Vec<&str> = BUTTONS
+                .iter()
+                .filter(|(_, button)| held & button != 0)
+                .map(|(name, _)| *name)
+                .collect();
+            let buttons = match names.is_empty() {
+                true => EMPTY_FRAME.to_string(),
+                false => names.join(&FRAME_BUTTON_SEPARATOR.to_string()),
+            };
+
+            match count {
+                1 => buttons,
+                _ => format!("{buttons}{FRAME_REPEAT_SEPARATOR}{count}"),
+            }
+        })
+        .collect();
+
+    runs.join(&format!("{FRAME_SEPARATOR} "))
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{controller::Button, keyboard::Keycode};
+
+    use crate::emu::{
+        error::{ConfigError, Error},
+        io::controller::Buttons,
+        nes::bindings::{
+            Action, Bindings, DEFAULT_STICK_THRESHOLD, Input, Macro, format_frames, parse_frames,
+        },
+    };
+
+    #[test]
+    fn gamepad_bindings_are_parsed() {
+        let text = "\
+# comment
+[player2]
+turbo_rate = 30
+
+[player2.gamepad]
+a = \"x\"
+turbo_b = \"\"
+select = \"\"
+stick_threshold = 8000
+
+[macros.jump]
+player = 2
+gamepad_button = \"leftshoulder\"
+frames = \"right*2, right+a\"
+";
+        let bindings = Bindings::parse(text).expect("config should parse");
+
+        let player2 = &bindings.players[1];
+        assert_eq!(
+            bindings.find_gamepad_button(1, Button::X),
+            [Action::Input {
+                player: 1,
+                input: Input::Button(Buttons::A)
+            }]
+        );
+        assert!(bindings.find_gamepad_button(1, Button::Back).is_empty());
+        assert_eq!(
+            bindings.find_gamepad_button(1, Button::LeftShoulder),
+            [Action::Macro { index: 0 }]
+        );
+        assert_eq!(player2.stick_threshold, 8000);
+        assert_eq!(player2.turbo_rate, 30);
+        assert_eq!(bindings.players[0].stick_threshold, DEFAULT_STICK_THRESHOLD);
+        assert_eq!(
+            bindings.macros[0].frames,
+            [Buttons::RIGHT, Buttons::RIGHT, Buttons::RIGHT | Buttons::A]
+        );
+
+        assert!(Bindings::parse("a = \"x\"").is_err());
+        assert!(Bindings::parse("[player5.gamepad]").is_err());
+        assert!(Bindings::parse("[player1.gamepad]\nturbo = \"x\"").is_err());
+        assert!(Bindings::parse("[player1.gamepad]\na = \"nope\"").is_err());
+        assert!(Bindings::parse("[player1]\nturbo_rate = 61").is_err());
+        assert!(Bindings::parse("[player1]\nturbo_rate = 7").is_err());
+    }
+
+    #[test]
+    fn config_round_trips() {
+        let mut bindings = Bindings::default();
+        bindings.players[0].keys[0] = None;
+        bindings.players[2].gamepad_buttons[9] = Some(Button::Paddle1);
+        bindings.players[3].turbo_rate = 20;
+        bindings.macros.push(Macro {
+            name: "hadouken".to_string(),
+            player: 1,
+            key: Some(Keycode::E),
+            gamepad_button: None,
+            frames: vec![Buttons::DOWN, Buttons::DOWN | Buttons::RIGHT, 0, 0],
+        });
+
+        let text = bindings.to_config();
+        assert!(text.contains("[player1.keyboard]\nup = \"\"\n"));
+        assert!(text.contains("[macros.hadouken]\nplayer = 2\n"));
+        assert!(text.contains("frames = \"down, down+right, -*2\"\n"));
+        assert_eq!(
+            Bindings::parse(&text).expect("config should parse"),
+            bindings
+        );
+
+        let Err(Error::ConfigError {
+            err: ConfigError::InvalidEntry { line, .. },
+        }) = Bindings::parse("[player1]\n\n[player1.keyboard]\nup = \"Nope\"")
+        else {
+            panic!("unknown key should fail to parse");
+        };
+        assert_eq!(line, 4);
+    }
+
+    #[test]
+    fn macro_frames_round_trip() {
+        let frames = parse_frames("down*2, down+right, -*3, a").expect("frames should parse");
+        assert_eq!(frames.len(), 7);
+        assert_eq!(format_frames(&frames), "down*2, down+right, -*3, a");
+        assert!(parse_frames("jump").is_err());
+    }
+}
@@ -143,6 +143,131 @@ pub fn get_debug_text(nes: &NES) -> String {
     }
 }
 
+/// Returns the line for the instruction at the program counter in the format
+/// of nestest.log, with the state of the console before the instruction runs.
+///
+/// # Examples
+///
+/// ```text
+/// C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+/// ```
+///
+pub fn get_trace_line(nes: &NES) -> String {
+    let registers = nes.cpu.get_registers();
+    let pc = concat_u8!(registers.pc.0, registers.pc.1);
+    let opcode = nes.buses.peek(pc);
+
+    let len = match get_addressing_mode(opcode) {
+        AddressingMode::Accumulator | AddressingMode::Implied => 1,
+        AddressingMode::Absolute
+        | AddressingMode::Indirect
+        | AddressingMode::AbsoluteIndexedX
+        | AddressingMode::AbsoluteIndexedY => 3,
+        _ => 2,
+    };
+    let bytes: Vec<String> = (0..len)
+        .map(|offset| format!("{:02X}", nes.buses.peek(pc.wrapping_add(offset))))
+        .collect();
+
+    // Unofficial opcodes are marked with a star.
+    let marker = match get_label(opcode) {
+        "NOP" => opcode != 0xEA,
+        label => matches!(
+            label,
+            "SLO" | "RLA" | "SRE" | "RRA" | "SAX" | "LAX" | "DCP" | "ISC" | "USBC" | "JAM"
+        ),
+    };
+    let marker = if marker { "*" } else { " " };
+
+    let ppu = nes.buses.get_ppu();
+    format!(
+        "{pc:04X}  {:8} {marker}{:31} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:3},{:3} CYC:{}",
+        bytes.join(" "),
+        get_trace_text(nes, pc, opcode),
+        registers.a,
+        registers.x_index,
+        registers.y_index,
+        u8::from(registers.psr),
+        registers.sp,
+        ppu.get_scanline_index(),
+        ppu.get_cycle_count(),
+        nes.cpu.get_cycle_count(),
+    )
+}
+
+/// Returns the disassembly of an instruction in the format of nestest.log,
+/// along with the addresses it accesses and the values they hold.
+fn get_trace_text(nes: &NES, pc: u16, opcode: u8) -> String {
+    let label = match get_label(opcode) {
+        "ISC" => "ISB",
+        "USBC" => "SBC",
+        label => label,
+    };
+
+    let peek = |addr: u16| nes.buses.peek(addr);
+    let op = peek(pc.wrapping_add(1));
+    let addr = concat_u8!(peek(pc.wrapping_add(2)), op);
+    let registers = nes.cpu.get_registers();
+
+    match get_addressing_mode(opcode) {
+        AddressingMode::Accumulator => format!("{label} A"),
+        AddressingMode::Implied => label.to_string(),
+        AddressingMode::Immediate => format!("{label} #${op:02X}"),
+        AddressingMode::Relative => {
+            let target = pc.wrapping_add(2).wrapping_add_signed(op as i8 as i16);
+            format!("{label} ${target:04X}")
+        }
+        AddressingMode::ZeroPage => format!("{label} ${op:02X} = {:02X}", peek(op as u16)),
+        AddressingMode::ZeroPageIndexedX => {
+            let zero_page_addr = op.wrapping_add(registers.x_index);
+            let val = peek(zero_page_addr as u16);
+            format!("{label} ${op:02X},X @ {zero_page_addr:02X} = {val:02X}")
+        }
+        AddressingMode::ZeroPageIndexedY => {
+            let zero_page_addr = op.wrapping_add(registers.y_index);
+            let val = peek(zero_page_addr as u16);
+            format!("{label} ${op:02X},Y @ {zero_page_addr:02X} = {val:02X}")
+        }
+        // Jumps show only their target.
+        AddressingMode::Absolute if matches!(label, "JMP" | "JSR") => {
+            format!("{label} ${addr:04X}")
+        }
+        AddressingMode::Absolute => format!("{label} ${addr:04X} = {:02X}", peek(addr)),
+        AddressingMode::AbsoluteIndexedX => {
+            let indexed_addr = addr.wrapping_add(registers.x_index as u16);
+            let val = peek(indexed_addr);
+            format!("{label} ${addr:04X},X @ {indexed_addr:04X} = {val:02X}")
+        }
+        AddressingMode::AbsoluteIndexedY => {
+            let indexed_addr = addr.wrapping_add(registers.y_index as u16);
+            let val = peek(indexed_addr);
+            format!("{label} ${addr:04X},Y @ {indexed_addr:04X} = {val:02X}")
+        }
+        AddressingMode::Indirect => {
+            // The pointer's high byte is read without carrying into its page.
+            let (ptr_high_byte, ptr_low_byte) = split_u16!(addr);
+            let ptr_high_byte_location = concat_u8!(ptr_high_byte, ptr_low_byte.wrapping_add(1));
+            let target = concat_u8!(peek(ptr_high_byte_location), peek(addr));
+            format!("{label} (${addr:04X}) = {target:04X}")
+        }
+        AddressingMode::IndirectIndexedX => {
+            let zero_page_addr = op.wrapping_add(registers.x_index);
+            let ptr = concat_u8!(
+                peek(zero_page_addr.wrapping_add(1) as u16),
+                peek(zero_page_addr as u16)
+            );
+            let val = peek(ptr);
+            format!("{label} (${op:02X},X) @ {zero_page_addr:02X} = {ptr:04X} = {val:02X}")
+        }
+        AddressingMode::IndirectIndexedY => {
+            let ptr = concat_u8!(peek(op.wrapping_add(1) as u16), peek(op as u16));
+            let indexed_addr = ptr.wrapping_add(registers.y_index as u16);
+            let val = peek(indexed_addr);
+            format!("{label} (${op:02X}),Y = {ptr:04X} @ {indexed_addr:04X} = {val:02X}")
+        }
+    }
+}
+
 /// Creates the debug text for the Relative addressing mode.
 ///
 /// # Arguments
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use sdl2::{
+    GameControllerSubsystem,
+    controller::{Axis, GameController},
+};
+
+use crate::emu::{io::controller::Buttons, nes::bindings::PLAYER_COUNT};
+
+/// A connected gamepad and the player it controls.
+struct Gamepad {
+    /// The open handle to the gamepad, which closes it when dropped.
+    controller: GameController,
+    player: usize,
+    /// The direction the left stick is pushed on each axis: -1, 0 or 1.
+    stick: (i8, i8),
+}
+
+/// The gamepads connected through SDL's game controller API. Gamepads are
+/// opened as they're plugged in, including those already connected at start,
+/// and each is given to the first player without one.
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    /// The connected gamepads, keyed by their joystick instance ID.
+    connected: HashMap<u32, Gamepad>,
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GameControllerSubsystem) -> Self {
+        Self {
+            subsystem,
+            connected: HashMap::new(),
+        }
+    }
+
+    /// Opens a newly connected gamepad, returning the player it was given to.
+    /// Returns `None` if every player already has a gamepad.
+    pub fn add(&mut self, joystick_index: u32) -> Option<usize> {
+        let player = (0..PLAYER_COUNT)
+            .find(|player| self.connected.values().all(|g| g.player != *player))?;
+
+        let controller = match self.subsystem.open(joystick_index) {
+            Ok(controller) => controller,
+            Err(err) => {
+                eprintln!("Opening gamepad failed: {err}");
+                return None;
+            }
+        };
+
+        let gamepad = Gamepad {
+            controller,
+            player,
+            stick: (0, 0),
+        };
+        self.connected
+            .insert(gamepad.controller.instance_id(), gamepad);
+
+        Some(player)
+    }
+
+    /// Closes a disconnected gamepad, returning the player it was given to.
+    pub fn remove(&mut self, instance_id: u32) -> Option<usize> {
+        self.connected
+            .remove(&instance_id)
+            .map(|gamepad| gamepad.player)
+    }
+
+    /// Returns the player the gamepad was given to.
+    pub fn get_player(&self, instance_id: u32) -> Option<usize> {
+        self.connected
+            .get(&instance_id)
+            .map(|gamepad| gamepad.player)
+    }
+
+    /// Moves one axis of a gamepad's left stick. Returns the D-pad buttons to
+    /// press or release, which change when the stick crosses the threshold.
+    pub fn move_stick(
+        &mut self,
+        instance_id: u32,
+        axis: Axis,
+        value: i16,
+        threshold: i16,
+    ) -> Vec<(u8, bool)> {
+        let Some(gamepad) = self.connected.get_mut(&instance_id) else {
+            return Vec::new();
+        };
+
+        let (direction, negative, positive) = match axis {
+            Axis::LeftX => (&mut gamepad.stick.0, Buttons::LEFT, Buttons::RIGHT),
+            Axis::LeftY => (&mut gamepad.stick.1, Buttons::UP, Buttons::DOWN),
+            // ignore; only the left stick acts as a D-pad
+            _ => return Vec::new(),
+        };
+
+        let new_direction = match value {
+            value if value <= -threshold => -1,
+            value if value >= threshold => 1,
+            _ => 0,
+        };
+        if new_direction == *direction {
+            return Vec::new();
+        }
+
+        let mut changes = Vec::new();
+        match *direction {
+            -1 => changes.push((negative, false)),
+            1 => changes.push((positive, false)),
+            _ => {}
+        }
+        match new_direction {
+            -1 => changes.push((negative, true)),
+            1 => changes.push((positive, true)),
+            _ => {}
+        }
+        *direction = new_direction;
+
+        changes
+    }
+}
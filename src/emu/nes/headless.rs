@@ -0,0 +1,102 @@
+use crate::emu::ppu::frame::Frame;
+
+/// A condition that stops [`crate::emu::nes::NES::run_headless`]. Runs also
+/// always stop when the CPU executes a JAM instruction, as it can't continue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StopCondition {
+    /// Stops once the given number of frames have been run.
+    Frames(u64),
+    /// Stops once the given number of CPU cycles have been run.
+    Cycles(u64),
+    /// Stops when the byte at the address holds the value, checked between
+    /// instructions.
+    Memory { addr: u16, value: u8 },
+    /// Stops when the CPU is about to execute the instruction at the address.
+    Pc(u16),
+}
+
+/// Why a headless run stopped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Condition(StopCondition),
+    /// The CPU executed a JAM instruction and halted.
+    Jam,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HeadlessOptions {
+    pub stop_conditions: Vec<StopCondition>,
+    /// Whether to hash each frame, for [`HeadlessRun::frame_hashes`].
+    pub hash_frames: bool,
+}
+
+/// The outcome of a headless run.
+#[derive(Clone, Debug)]
+pub struct HeadlessRun {
+    pub reason: StopReason,
+    /// The number of frames run.
+    pub frames: u64,
+    /// The number of CPU cycles run.
+    pub cycles: u64,
+    /// The CRC32 of each frame's pixel data, if the frames were hashed.
+    pub frame_hashes: Vec<u32>,
+}
+
+/// Encodes a frame as a PNG image. The image data is stored without
+/// compression, which keeps the encoder simple at the cost of larger files.
+pub fn encode_png(frame: &Frame) -> Vec<u8> {
+    let pixel_data = frame.get_pixel_data();
+    let row_size = Frame::WIDTH * Frame::BYTES_PER_PIXEL;
+
+    // Each row starts with its filter type, which is none.
+    let mut image_data = Vec::with_capacity((row_size + 1) * Frame::HEIGHT);
+    for row in pixel_data.chunks(row_size) {
+        image_data.push(0);
+        image_data.extend_from_slice(row);
+    }
+
+    // A zlib stream of uncompressed deflate blocks.
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = image_data.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend(adler32(&image_data).to_be_bytes());
+
+    let mut header = Vec::new();
+    header.extend((Frame::WIDTH as u32).to_be_bytes());
+    header.extend((Frame::HEIGHT as u32).to_be_bytes());
+    // 8-bit RGB, with the default compression, filtering and no interlacing.
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_png_chunk(&mut png, b"IHDR", &header);
+    write_png_chunk(&mut png, b"IDAT", &zlib);
+    write_png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_png_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(chunk_type);
+    hasher.update(data);
+    png.extend(hasher.finalize().to_be_bytes());
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % MODULUS;
+        b = (b + a) % MODULUS;
+    }
+    b << 16 | a
+}
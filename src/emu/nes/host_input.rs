@@ -0,0 +1,200 @@
+use crate::emu::nes::bindings::{Bindings, Input, PLAYER_COUNT};
+
+/// The number of frames a second, which turbo rates are relative to.
+pub const FRAMES_PER_SECOND: u32 = 60;
+
+/// A host device that holds inputs. Each is tracked separately, so releasing
+/// an input on one doesn't release it while another still holds it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    Keyboard,
+    GamepadButtons,
+    /// A gamepad's left stick, acting as a D-pad.
+    Stick,
+}
+
+const SOURCE_COUNT: usize = 3;
+
+/// The state of the host inputs bound to each player's controller, which is
+/// turned into the buttons pressed on each controller once per frame. Turbo
+/// buttons and macros are timed by the PPU's frame count, so they play back
+/// the same way every time.
+pub struct HostInput {
+    /// The buttons held by their bindings, for each player and source.
+    held: [[u8; SOURCE_COUNT]; PLAYER_COUNT],
+    /// The turbo buttons held by their bindings, for each player and source.
+    turbo_held: [[u8; SOURCE_COUNT]; PLAYER_COUNT],
+    /// The macros being played, by index, with the frame each started on.
+    playing_macros: Vec<(usize, u64)>,
+}
+
+impl HostInput {
+    pub fn new() -> Self {
+        Self {
+            held: [[0; SOURCE_COUNT]; PLAYER_COUNT],
+            turbo_held: [[0; SOURCE_COUNT]; PLAYER_COUNT],
+            playing_macros: Vec::new(),
+        }
+    }
+
+    /// Holds or releases an input on a player's controller from the given
+    /// source.
+    pub fn set_input_held(&mut self, player: usize, source: InputSource, input: Input, held: bool) {
+        let (buttons, mask) = match input {
+            Input::Button(mask) => (&mut self.held[player][source as usize], mask),
+            Input::Turbo(mask) => (&mut self.turbo_held[player][source as usize], mask),
+        };
+
+        if held {
+            *buttons |= mask;
+        } else {
+            *buttons &= !mask;
+        }
+    }
+
+    /// Releases every input the given source holds on a player's controller.
+    pub fn release_source(&mut self, player: usize, source: InputSource) {
+        self.held[player][source as usize] = 0;
+        self.turbo_held[player][source as usize] = 0;
+    }
+
+    /// Starts playing a macro from the given frame. A macro that's already
+    /// playing starts over.
+    pub fn start_macro(&mut self, index: usize, frame: u64) {
+        self.playing_macros.retain(|(playing, _)| *playing != index);
+        self.playing_macros.push((index, frame));
+    }
+
+    /// Returns the buttons pressed on each player's controller on the given
+    /// frame, and stops macros that have finished.
+    pub fn get_buttons(&mut self, frame: u64, bindings: &Bindings) -> [u8; PLAYER_COUNT] {
+        let mut buttons = self.held.map(|held| held.into_iter().fold(0, |a, b| a | b));
+
+        for (player, buttons) in buttons.iter_mut().enumerate() {
+            if is_turbo_pressed(frame, bindings.players[player].turbo_rate) {
+                *buttons |= self.turbo_held[player].into_iter().fold(0, |a, b| a | b);
+            }
+        }
+
+        self.playing_macros.retain(|&(index, start)| {
+            let Some(m) = bindings.macros.get(index) else {
+                return false;
+            };
+            let Some(&held) = m.frames.get(frame.saturating_sub(start) as usize) else {
+                return false;
+            };
+
+            buttons[m.player] |= held;
+            true
+        });
+
+        buttons
+    }
+}
+
+impl Default for HostInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns `true` if turbo buttons are pressed on the given frame. Each press
+/// lasts half the turbo period, rounded up. The rate divides the frame rate
+/// evenly, as checked when bindings are loaded.
+fn is_turbo_pressed(frame: u64, rate: u32) -> bool {
+    let period = (FRAMES_PER_SECOND / rate.max(1)).max(2) as u64;
+    frame % period < period.div_ceil(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        io::controller::Buttons,
+        nes::{
+            bindings::{Bindings, Input, Macro, PLAYER_COUNT},
+            host_input::{HostInput, InputSource, is_turbo_pressed},
+        },
+    };
+
+    #[test]
+    fn turbo_presses_follow_the_rate() {
+        for (rate, pattern) in [
+            (30, [1, 0, 1, 0, 1, 0, 1, 0]),
+            (20, [1, 1, 0, 1, 1, 0, 1, 1]),
+            (15, [1, 1, 0, 0, 1, 1, 0, 0]),
+        ] {
+            let pressed: Vec<u8> = (0..8)
+                .map(|frame| is_turbo_pressed(frame, rate) as u8)
+                .collect();
+            assert_eq!(pressed, pattern, "turbo rate {rate}");
+        }
+
+        let mut bindings = Bindings::default();
+        bindings.players[1].turbo_rate = 20;
+        let mut host_input = HostInput::new();
+        host_input.set_input_held(1, InputSource::Keyboard, Input::Turbo(Buttons::A), true);
+        host_input.set_input_held(1, InputSource::Keyboard, Input::Button(Buttons::B), true);
+
+        let buttons: Vec<u8> = (0..6)
+            .map(|frame| host_input.get_buttons(frame, &bindings)[1])
+            .collect();
+        let pressed = Buttons::A | Buttons::B;
+        assert_eq!(
+            buttons,
+            [pressed, pressed, Buttons::B, pressed, pressed, Buttons::B]
+        );
+    }
+
+    #[test]
+    fn macros_play_from_their_start_frame() {
+        let mut bindings = Bindings::default();
+        bindings.macros.push(Macro {
+            name: "test".to_string(),
+            player: 2,
+            key: None,
+            gamepad_button: None,
+            frames: vec![Buttons::UP, 0, Buttons::DOWN],
+        });
+        let mut host_input = HostInput::new();
+
+        host_input.start_macro(0, 10);
+        let buttons: Vec<u8> = (10..14)
+            .map(|frame| host_input.get_buttons(frame, &bindings)[2])
+            .collect();
+        assert_eq!(buttons, [Buttons::UP, 0, Buttons::DOWN, 0]);
+        assert!(host_input.playing_macros.is_empty());
+
+        // Starting a macro that's playing starts it over.
+        host_input.start_macro(0, 20);
+        assert_eq!(host_input.get_buttons(20, &bindings)[2], Buttons::UP);
+        host_input.start_macro(0, 21);
+        assert_eq!(host_input.get_buttons(21, &bindings)[2], Buttons::UP);
+        assert_eq!(host_input.get_buttons(23, &bindings)[2], Buttons::DOWN);
+        assert_eq!(host_input.get_buttons(24, &bindings)[2], 0);
+    }
+
+    #[test]
+    fn sources_hold_inputs_separately() {
+        let bindings = Bindings::default();
+        let mut host_input = HostInput::new();
+        let a = Input::Button(Buttons::A);
+
+        host_input.set_input_held(0, InputSource::Keyboard, a, true);
+        host_input.set_input_held(0, InputSource::GamepadButtons, a, true);
+        host_input.set_input_held(0, InputSource::Stick, Input::Button(Buttons::UP), true);
+
+        host_input.set_input_held(0, InputSource::GamepadButtons, a, false);
+        assert_eq!(
+            host_input.get_buttons(0, &bindings)[0],
+            Buttons::A | Buttons::UP
+        );
+
+        // Unplugging the gamepad leaves the keyboard's inputs held.
+        host_input.release_source(0, InputSource::GamepadButtons);
+        host_input.release_source(0, InputSource::Stick);
+        assert_eq!(host_input.get_buttons(0, &bindings)[0], Buttons::A);
+
+        host_input.set_input_held(0, InputSource::Keyboard, a, false);
+        assert_eq!(host_input.get_buttons(0, &bindings), [0; PLAYER_COUNT]);
+    }
+}
@@ -2,33 +2,148 @@ use crate::{
     DebugLevel, concat_u8,
     emu::{
         buses::Buses,
-        cartridge::Cartridge,
+        cartridge::{Cartridge, battery::SaveFile},
         cpu::{CPU, registers::Registers},
+        error::Error,
         io::controller::Buttons,
-        nes::debug::get_debug_text,
+        nes::{
+            bindings::{Action, Bindings, Input},
+            debug::get_debug_text,
+            gamepad::Gamepads,
+            headless::{HeadlessOptions, HeadlessRun, StopCondition, StopReason},
+            host_input::{HostInput, InputSource},
+            movie::{HARD_RESET, MovieFrame, MovieSession, SOFT_RESET},
+            osd::Osd,
+            quick_save::{QuickSave, QuickSaves, SLOT_KEYS, format_timestamp},
+            rewind::Rewind,
+        },
         ppu::frame::Frame,
+        state::{StateReader, StateWriter},
     },
 };
 use sdl2::{
-    event::Event,
-    keyboard::Keycode,
+    event::{Event, WindowEvent},
+    keyboard::{Keycode, Mod},
+    mouse::MouseButton,
     pixels::{Color, PixelFormatEnum},
 };
-use std::collections::HashMap;
 use std::fmt;
 
 pub struct NES {
     pub buses: Buses,
     pub cpu: CPU,
+    /// The file that battery-backed PRG RAM or disk writes are persisted to,
+    /// if any.
+    pub save_file: Option<SaveFile>,
+    /// The host inputs bound to each player's controller.
+    pub bindings: Bindings,
+    /// The quick-save slots bound to F1-F10, if any.
+    pub quick_saves: Option<QuickSaves>,
+    /// The history of states that Backspace rewinds through, if rewinding is
+    /// enabled.
+    pub rewind: Option<Rewind>,
+    /// The number of frames to run ahead of the frame being presented.
+    pub run_ahead: u32,
+    /// The movie being recorded or played, if any.
+    pub movie: Option<MovieSession>,
+    /// The state the console was powered on in, which hard resets return to.
+    power_on_state: Option<Vec<u8>>,
 }
 
+pub mod bindings;
 pub mod debug;
+pub mod gamepad;
+pub mod headless;
+pub mod host_input;
+pub mod movie;
+pub mod osd;
+pub mod quick_save;
+pub mod rewind;
+pub mod test_rom;
+pub mod trace;
+
+/// How often battery-backed PRG RAM is written to disk while running, in
+/// frames (roughly ten seconds).
+const SAVE_INTERVAL_FRAMES: u64 = 600;
 
 impl NES {
     pub fn new(cart: Cartridge) -> Self {
         Self {
             buses: Buses::new(cart),
             cpu: CPU::new(14, Registers::default()),
+            save_file: None,
+            bindings: Bindings::default(),
+            quick_saves: None,
+            rewind: None,
+            run_ahead: 0,
+            movie: None,
+            power_on_state: None,
+        }
+    }
+
+    /// Powers on the console, remembering its state for hard resets.
+    pub fn power_on(&mut self, start_addr: Option<u16>) {
+        self.cpu.poweron(&mut self.buses, start_addr);
+        self.power_on_state = Some(self.save_state());
+    }
+
+    /// Presses the reset button.
+    pub fn soft_reset(&mut self) {
+        self.cpu.reset(&mut self.buses, None);
+    }
+
+    /// Power cycles the console, returning it to the state it was powered on
+    /// in. Battery-backed save data is kept, as it is on a cartridge, and the
+    /// frame count carries on.
+    pub fn hard_reset(&mut self) {
+        let Some(state) = self.power_on_state.take() else {
+            return;
+        };
+
+        let save_data = self.buses.get_cartridge().mapper.borrow().get_save_data();
+        let frame_count = self.buses.ppu.get_frame_count();
+        if let Err(err) = self.load_state(&state) {
+            eprintln!("Power cycling failed: {err}");
+        }
+
+        self.buses
+            .get_cartridge()
+            .mapper
+            .borrow_mut()
+            .set_save_data(&save_data);
+        self.buses.ppu.set_frame_count(frame_count);
+        self.power_on_state = Some(state);
+    }
+
+    /// Starts recording or playing a movie, from its save state if it has one.
+    pub fn start_movie(&mut self, mut movie: MovieSession) -> Result<(), Error> {
+        if let Some(state) = &movie.movie.start_state {
+            self.load_state(state)?;
+        }
+
+        movie.start(self.buses.ppu.get_frame_count());
+        self.movie = Some(movie);
+        Ok(())
+    }
+
+    /// Sets the input for the coming frame, which is played from the movie if
+    /// one is being played, or else is the host's input, and is recorded if a
+    /// movie is being recorded. Resets take effect before the buttons are set.
+    pub fn set_frame_input(&mut self, host_input: MovieFrame) {
+        let frame_count = self.buses.ppu.get_frame_count();
+        let input = match self.movie.as_mut() {
+            Some(movie) => movie.next_frame(frame_count, host_input),
+            None => host_input,
+        };
+
+        if input.commands & HARD_RESET != 0 {
+            self.hard_reset();
+        } else if input.commands & SOFT_RESET != 0 {
+            self.soft_reset();
+        }
+
+        for (player, buttons) in input.buttons.into_iter().enumerate() {
+            self.buses.set_buttons(player, Buttons::from(buttons));
         }
     }
 
@@ -39,8 +154,13 @@ impl NES {
         let width = Frame::WIDTH as u32;
         let height = Frame::HEIGHT as u32;
 
+        let title = match &self.buses.get_cartridge().header.title {
+            Some(game_title) => format!("Green NES - {game_title}"),
+            None => "Green NES".to_string(),
+        };
+
         let window = video_subsystem
-            .window("Green NES", width * 2, height * 2)
+            .window(&title, width * 2, height * 2)
             .position_centered()
             .build()
             .unwrap();
@@ -57,37 +177,35 @@ impl NES {
 
         let mut event_pump = sdl_context.event_pump().unwrap();
 
-        let mut key_map = HashMap::new();
-        key_map.insert(Keycode::Down, Buttons::DOWN);
-        key_map.insert(Keycode::Up, Buttons::UP);
-        key_map.insert(Keycode::Right, Buttons::RIGHT);
-        key_map.insert(Keycode::Left, Buttons::LEFT);
-        key_map.insert(Keycode::Space, Buttons::SELECT);
-        key_map.insert(Keycode::Return, Buttons::START);
-        key_map.insert(Keycode::A, Buttons::A);
-        key_map.insert(Keycode::S, Buttons::B);
+        let mut gamepads = Gamepads::new(sdl_context.game_controller().unwrap());
+        let mut host_input = HostInput::new();
+        let mut osd = Osd::new();
+        let mut rewinding = false;
+        let mut commands = 0;
+
+        // A loaded state may start at the end of a frame, whose input is set
+        // when the loop reaches it. Otherwise the first frame's is set now.
+        if !self.buses.ppu.is_frame_ready() {
+            self.set_frame_input(MovieFrame::default());
+        }
 
         'running: while !self.cpu.is_halted() {
-            if self.cpu.get_cycle_queue().is_empty() && debug_level == DebugLevel::Low {
+            if self.cpu.is_between_instructions() && debug_level == DebugLevel::Low {
                 println!("{self:?}")
             }
 
             self.buses.tick();
 
             if self.buses.ppu.is_frame_ready() {
-                let mut frame = Frame::default();
-                self.buses.ppu.draw_frame(&mut frame);
-
-                texture
-                    .update(
-                        None,
-                        &frame.get_pixel_data(),
-                        Frame::WIDTH * Frame::BYTES_PER_PIXEL,
-                    )
-                    .unwrap();
-
-                canvas.copy(&texture, None, None).unwrap();
-                canvas.present();
+                if self
+                    .buses
+                    .ppu
+                    .get_frame_count()
+                    .is_multiple_of(SAVE_INTERVAL_FRAMES)
+                {
+                    self.write_save_file();
+                    self.write_movie();
+                }
 
                 for event in event_pump.poll_iter() {
                     match event {
@@ -97,38 +215,439 @@ impl NES {
                             ..
                         } => break 'running,
 
-                        Event::KeyDown { keycode, .. } => {
-                            if let Some(key) = key_map.get(&keycode.unwrap_or(Keycode::Ampersand)) {
-                                self.buses
-                                    .controller
-                                    .set_button_pressed(Buttons::from(*key), true);
+                        // The canvas's logical size scales mouse positions to
+                        // frame coordinates.
+                        Event::MouseMotion { x, y, .. } => {
+                            self.buses.set_pointer_position(Some((x, y)))
+                        }
+                        Event::Window {
+                            win_event: WindowEvent::Leave,
+                            ..
+                        } => self.buses.set_pointer_position(None),
+                        Event::MouseButtonDown {
+                            mouse_btn: MouseButton::Left,
+                            ..
+                        } => self.buses.set_pointer_pressed(true),
+                        Event::MouseButtonUp {
+                            mouse_btn: MouseButton::Left,
+                            ..
+                        } => self.buses.set_pointer_pressed(false),
+
+                        Event::KeyDown {
+                            keycode: Some(Keycode::Tab),
+                            repeat: false,
+                            ..
+                        } => self
+                            .buses
+                            .get_cartridge()
+                            .mapper
+                            .borrow_mut()
+                            .switch_disk_side(),
+
+                        Event::KeyDown {
+                            keycode: Some(Keycode::F11),
+                            keymod,
+                            repeat: false,
+                            ..
+                        } => {
+                            commands |= if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                                HARD_RESET
+                            } else {
+                                SOFT_RESET
+                            };
+                        }
+
+                        Event::KeyDown {
+                            keycode: Some(Keycode::Backspace),
+                            ..
+                        } => rewinding = true,
+                        Event::KeyUp {
+                            keycode: Some(Keycode::Backspace),
+                            ..
+                        } => rewinding = false,
+
+                        Event::KeyDown {
+                            keycode: Some(keycode),
+                            keymod,
+                            repeat: false,
+                            ..
+                        } if SLOT_KEYS.contains(&keycode) && !self.buses.has_keyboard() => {
+                            let slot = SLOT_KEYS.iter().position(|key| *key == keycode).unwrap();
+                            let message = if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                                self.quick_save(slot + 1)
+                            } else {
+                                self.quick_load(slot + 1)
+                            };
+                            osd.show(message);
+                        }
+
+                        Event::KeyDown {
+                            keycode: Some(keycode),
+                            repeat,
+                            ..
+                        } => {
+                            self.buses.set_key_pressed(keycode, true);
+                            let actions = self.bindings.find_key(keycode);
+                            self.perform_actions(
+                                &mut host_input,
+                                InputSource::Keyboard,
+                                &actions,
+                                true,
+                                repeat,
+                            );
+                        }
+                        Event::KeyUp {
+                            keycode: Some(keycode),
+                            ..
+                        } => {
+                            self.buses.set_key_pressed(keycode, false);
+                            let actions = self.bindings.find_key(keycode);
+                            self.perform_actions(
+                                &mut host_input,
+                                InputSource::Keyboard,
+                                &actions,
+                                false,
+                                false,
+                            );
+                        }
+
+                        Event::ControllerDeviceAdded { which, .. } => {
+                            gamepads.add(which);
+                        }
+                        Event::ControllerDeviceRemoved { which, .. } => {
+                            if let Some(player) = gamepads.remove(which) {
+                                host_input.release_source(player, InputSource::GamepadButtons);
+                                host_input.release_source(player, InputSource::Stick);
                             }
                         }
-                        Event::KeyUp { keycode, .. } => {
-                            if let Some(key) = key_map.get(&keycode.unwrap_or(Keycode::Ampersand)) {
-                                self.buses
-                                    .controller
-                                    .set_button_pressed(Buttons::from(*key), false);
+                        Event::ControllerButtonDown { which, button, .. } => {
+                            if let Some(player) = gamepads.get_player(which) {
+                                let actions = self.bindings.find_gamepad_button(player, button);
+                                self.perform_actions(
+                                    &mut host_input,
+                                    InputSource::GamepadButtons,
+                                    &actions,
+                                    true,
+                                    false,
+                                );
+                            }
+                        }
+                        Event::ControllerButtonUp { which, button, .. } => {
+                            if let Some(player) = gamepads.get_player(which) {
+                                let actions = self.bindings.find_gamepad_button(player, button);
+                                self.perform_actions(
+                                    &mut host_input,
+                                    InputSource::GamepadButtons,
+                                    &actions,
+                                    false,
+                                    false,
+                                );
+                            }
+                        }
+                        Event::ControllerAxisMotion {
+                            which, axis, value, ..
+                        } => {
+                            let Some(player) = gamepads.get_player(which) else {
+                                continue;
+                            };
+                            let threshold = self.bindings.players[player].stick_threshold;
+                            for (button, pressed) in
+                                gamepads.move_stick(which, axis, value, threshold)
+                            {
+                                host_input.set_input_held(
+                                    player,
+                                    InputSource::Stick,
+                                    Input::Button(button),
+                                    pressed,
+                                );
                             }
                         }
 
                         _ => {}
                     }
                 }
+
+                let frame_count = self.buses.ppu.get_frame_count();
+                let buttons = host_input.get_buttons(frame_count, &self.bindings);
+                self.set_frame_input(MovieFrame { commands, buttons });
+                commands = 0;
+                if self.movie.take_if(|movie| movie.is_finished()).is_some() {
+                    osd.show("Movie finished".to_string());
+                }
+
+                self.step_rewind(rewinding);
+
+                // The input for the coming frame is set before the frame is
+                // presented, so that running ahead can show its effects.
+                let mut frame = Frame::default();
+                self.buses.ppu.draw_frame(&mut frame);
+                if self.run_ahead > 0 {
+                    self.run_ahead(&mut frame);
+                }
+
+                texture
+                    .update(
+                        None,
+                        &frame.get_pixel_data(),
+                        Frame::WIDTH * Frame::BYTES_PER_PIXEL,
+                    )
+                    .unwrap();
+
+                canvas.copy(&texture, None, None).unwrap();
+                osd.draw(&mut canvas);
+                canvas.present();
             }
 
             self.cpu.tick(&mut self.buses);
         }
+
+        self.write_save_file();
+        self.write_movie();
     }
 
-    pub fn run_headless(&mut self, debug_level: DebugLevel) {
+    /// Performs the actions bound to a key or gamepad button that was pressed
+    /// or released. Macros start when their binding is first pressed.
+    fn perform_actions(
+        &self,
+        host_input: &mut HostInput,
+        source: InputSource,
+        actions: &[Action],
+        pressed: bool,
+        repeat: bool,
+    ) {
+        for action in actions {
+            match *action {
+                Action::Input { player, input } => {
+                    host_input.set_input_held(player, source, input, pressed)
+                }
+                Action::Macro { index } if pressed && !repeat => {
+                    host_input.start_macro(index, self.buses.ppu.get_frame_count())
+                }
+                // ignore; releasing a macro's binding doesn't stop it
+                Action::Macro { .. } => {}
+            }
+        }
+    }
+
+    /// Saves a snapshot of the whole console, which can be taken in the middle
+    /// of an instruction.
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut state = StateWriter::new(self.buses.get_cartridge().rom_hash);
+        self.cpu.save_state(&mut state);
+        self.buses.save_state(&mut state);
+        state.into_data()
+    }
+
+    /// Restores a snapshot made by [`NES::save_state`]. States saved from
+    /// another ROM or format version are rejected, and the console is left as
+    /// it was if the state can't be loaded.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), Error> {
+        let rom_hash = self.buses.get_cartridge().rom_hash;
+        let mut state = StateReader::new(data, rom_hash)?;
+
+        let backup = self.save_state();
+        if let Err(err) = self.read_state(&mut state) {
+            let mut state = StateReader::new(&backup, rom_hash)?;
+            self.read_state(&mut state)?;
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    fn read_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        self.cpu.load_state(state)?;
+        self.buses.load_state(state)?;
+        state.finish()
+    }
+
+    /// Runs [`NES::run_ahead`] frames ahead with the current input, drawing the
+    /// last of them to the given frame, then restores the console. This hides
+    /// the frames of latency that games have between reading input and showing
+    /// its effects. Only the last frame is rendered.
+    fn run_ahead(&mut self, frame: &mut Frame) {
+        let state = self.save_state();
+
+        for i in 0..self.run_ahead {
+            self.buses.ppu.set_rendering_skipped(i + 1 < self.run_ahead);
+            self.run_frame();
+        }
+        self.buses.ppu.draw_frame(frame);
+        self.buses.ppu.set_rendering_skipped(false);
+
+        if let Err(err) = self.load_state(&state) {
+            eprintln!("Running ahead failed: {err}");
+        }
+    }
+
+    /// Runs the console until the PPU finishes the next frame, stopping at the
+    /// point where [`NES::run`] presents frames.
+    fn run_frame(&mut self) {
+        self.cpu.tick(&mut self.buses);
         while !self.cpu.is_halted() {
-            if self.cpu.get_cycle_queue().is_empty() && debug_level == DebugLevel::Low {
-                println!("{self:?}")
+            self.buses.tick();
+            if self.buses.ppu.is_frame_ready() {
+                return;
+            }
+            self.cpu.tick(&mut self.buses);
+        }
+    }
+
+    /// Records a state for rewinding at the start of each interval of frames,
+    /// or while rewinding, goes back to the last state recorded, so that its
+    /// frame is presented next and gameplay is shown in reverse.
+    fn step_rewind(&mut self, rewinding: bool) {
+        let Some(interval) = self.rewind.as_ref().map(Rewind::get_interval) else {
+            return;
+        };
+
+        if rewinding {
+            if let Some(state) = self.rewind.as_mut().and_then(Rewind::pop)
+                && let Err(err) = self.load_state(&state)
+            {
+                eprintln!("Rewinding failed: {err}");
+            }
+        } else if self.buses.ppu.get_frame_count().is_multiple_of(interval) {
+            let state = self.save_state();
+            if let Some(rewind) = self.rewind.as_mut() {
+                rewind.push(state);
+            }
+        }
+    }
+
+    /// Saves the console to a quick-save slot, numbered from 1, returning a
+    /// message to show on screen.
+    pub fn quick_save(&self, slot: usize) -> String {
+        let Some(quick_saves) = &self.quick_saves else {
+            return "Quick saves are disabled".to_string();
+        };
+
+        let save = QuickSave::new(self.buses.ppu.get_frame(), self.save_state());
+        let rom_hash = self.buses.get_cartridge().rom_hash;
+        match quick_saves.write(slot, &save, rom_hash) {
+            Ok(()) => format!("Saved state {slot}"),
+            Err(err) => {
+                eprintln!("Saving state {slot} failed: {err}");
+                format!("Saving state {slot} failed")
+            }
+        }
+    }
+
+    /// Loads the console from a quick-save slot, numbered from 1, returning a
+    /// message to show on screen.
+    pub fn quick_load(&mut self, slot: usize) -> String {
+        let Some(quick_saves) = &self.quick_saves else {
+            return "Quick saves are disabled".to_string();
+        };
+
+        let rom_hash = self.buses.get_cartridge().rom_hash;
+        let result = quick_saves
+            .read(slot, rom_hash)
+            .and_then(|save| match save {
+                Some(save) => self.load_state(&save.state).map(|_| Some(save)),
+                None => Ok(None),
+            });
+
+        match result {
+            Ok(Some(save)) => format!("Loaded state {slot} ({})", format_timestamp(save.timestamp)),
+            Ok(None) => format!("State {slot} is empty"),
+            Err(err) => {
+                eprintln!("Loading state {slot} failed: {err}");
+                format!("Loading state {slot} failed")
+            }
+        }
+    }
+
+    /// Writes battery-backed PRG RAM to the save file, if there is one.
+    pub fn write_save_file(&mut self) {
+        if let Some(save_file) = self.save_file.as_mut()
+            && let Err(err) = save_file.write(self.buses.get_cartridge())
+        {
+            eprintln!("Writing save file failed: {err}");
+        }
+    }
+
+    /// Writes the movie being recorded to its file, if there is one.
+    pub fn write_movie(&self) {
+        if let Some(movie) = &self.movie
+            && let Err(err) = movie.save()
+        {
+            eprintln!("Writing movie failed: {err}");
+        }
+    }
+
+    /// Runs the console without a window until one of the stop conditions is
+    /// met, or the CPU halts. Input comes from the movie being played, if any.
+    pub fn run_headless(
+        &mut self,
+        debug_level: DebugLevel,
+        options: &HeadlessOptions,
+    ) -> HeadlessRun {
+        let start_frame = self.buses.ppu.get_frame_count();
+        let start_cycle = self.cpu.get_cycle_count();
+        let mut frame = Frame::default();
+        let mut frame_hashes = Vec::new();
+
+        if !self.buses.ppu.is_frame_ready() {
+            self.set_frame_input(MovieFrame::default());
+        }
+
+        let reason = 'running: loop {
+            if self.cpu.is_halted() {
+                break StopReason::Jam;
+            }
+
+            if self.cpu.is_between_instructions() {
+                if debug_level == DebugLevel::Low {
+                    println!("{self:?}")
+                }
+
+                let pc = self.cpu.get_registers().pc;
+                for &condition in options.stop_conditions.iter() {
+                    let met = match condition {
+                        StopCondition::Memory { addr, value } => self.buses.peek(addr) == value,
+                        StopCondition::Pc(addr) => concat_u8!(pc.0, pc.1) == addr,
+                        // ignore; checked as frames and cycles are run
+                        StopCondition::Frames(_) | StopCondition::Cycles(_) => false,
+                    };
+                    if met {
+                        break 'running StopReason::Condition(condition);
+                    }
+                }
             }
 
             self.buses.tick();
+
+            if self.buses.ppu.is_frame_ready() {
+                self.set_frame_input(MovieFrame::default());
+                self.buses.ppu.draw_frame(&mut frame);
+                if options.hash_frames {
+                    frame_hashes.push(crc32fast::hash(&frame.get_pixel_data()));
+                }
+            }
+
             self.cpu.tick(&mut self.buses);
+
+            let frames = self.buses.ppu.get_frame_count() - start_frame;
+            let cycles = self.cpu.get_cycle_count() - start_cycle;
+            for &condition in options.stop_conditions.iter() {
+                let met = match condition {
+                    StopCondition::Frames(limit) => frames >= limit,
+                    StopCondition::Cycles(limit) => cycles >= limit,
+                    // ignore; checked between instructions
+                    StopCondition::Memory { .. } | StopCondition::Pc(_) => false,
+                };
+                if met {
+                    break 'running StopReason::Condition(condition);
+                }
+            }
+        };
+
+        HeadlessRun {
+            reason,
+            frames: self.buses.ppu.get_frame_count() - start_frame,
+            cycles: self.cpu.get_cycle_count() - start_cycle,
+            frame_hashes,
         }
     }
 }
@@ -184,15 +703,125 @@ mod tests {
         DebugLevel,
         emu::{
             buses::Buses,
+            cartridge::{Cartridge, LoadOptions, ines::INES_TAG, read_cartridge},
             cpu::{CPU, registers::Registers},
-            nes::NES,
+            error::{Error, StateError},
+            nes::{
+                NES,
+                bindings::Bindings,
+                headless::{HeadlessOptions, StopCondition, StopReason},
+                trace::compare_trace,
+            },
+            ppu::frame::Frame,
         },
-        load_cart,
     };
 
+    /// Creates a cartridge whose program increments `$00` in a loop, with the
+    /// given value for the unused final PRG byte to tell cartridges apart.
+    fn create_cartridge(tag: u8) -> Cartridge {
+        let mut data = INES_TAG.to_vec();
+        data.extend([1, 0]);
+        data.resize(16, 0);
+
+        let mut prg = vec![0; 0x4000];
+        // INC $00; JMP $8000
+        prg[..5].copy_from_slice(&[0xE6, 0x00, 0x4C, 0x00, 0x80]);
+        // Reset vector
+        prg[0x3FFC..0x3FFE].copy_from_slice(&[0x00, 0x80]);
+        prg[0x3FFF] = tag;
+        data.extend(prg);
+
+        read_cartridge(&data, &LoadOptions::default()).unwrap()
+    }
+
+    fn run_cycles(nes: &mut NES, cycles: usize) {
+        for _ in 0..cycles {
+            nes.buses.tick();
+            nes.cpu.tick(&mut nes.buses);
+        }
+    }
+
+    #[test]
+    fn save_state_round_trip() {
+        let mut nes = NES::new(create_cartridge(0));
+        nes.cpu.poweron(&mut nes.buses, None);
+        run_cycles(&mut nes, 1000);
+
+        // States can be saved partway through an instruction.
+        run_cycles(&mut nes, 1);
+        assert!(!nes.cpu.is_between_instructions());
+        let cycle_queue = nes.cpu.get_cycle_queue();
+
+        let state = nes.save_state();
+        run_cycles(&mut nes, 1000);
+        let counter = nes.buses.peek(0x0000);
+        let cycle_count = nes.cpu.get_cycle_count();
+
+        nes.load_state(&state).unwrap();
+        assert_eq!(nes.cpu.get_cycle_queue(), cycle_queue);
+        assert_ne!(nes.buses.peek(0x0000), counter);
+        run_cycles(&mut nes, 1000);
+        assert_eq!(nes.buses.peek(0x0000), counter);
+        assert_eq!(nes.cpu.get_cycle_count(), cycle_count);
+
+        let mut other = NES::new(create_cartridge(1));
+        assert!(matches!(
+            other.load_state(&state),
+            Err(Error::StateError {
+                err: StateError::RomMismatch { .. }
+            })
+        ));
+    }
+
+    #[test]
+    fn run_ahead_restores_state() {
+        let mut nes = NES::new(create_cartridge(0));
+        nes.cpu.poweron(&mut nes.buses, None);
+        nes.run_frame();
+        let mut frame = Frame::default();
+        nes.buses.ppu.draw_frame(&mut frame);
+
+        let state = nes.save_state();
+        let frame_count = nes.buses.ppu.get_frame_count();
+        nes.run_ahead = 3;
+        nes.run_ahead(&mut frame);
+
+        assert_eq!(nes.save_state(), state);
+        assert_eq!(nes.buses.ppu.get_frame_count(), frame_count);
+    }
+
+    #[test]
+    fn headless_runs_stop() {
+        let mut nes = NES::new(create_cartridge(0));
+        nes.power_on(None);
+
+        let options = HeadlessOptions {
+            stop_conditions: vec![StopCondition::Frames(3)],
+            hash_frames: true,
+        };
+        let run = nes.run_headless(DebugLevel::None, &options);
+        assert_eq!(run.reason, StopReason::Condition(StopCondition::Frames(3)));
+        assert_eq!(run.frames, 3);
+        assert_eq!(run.frame_hashes.len(), 3);
+
+        let condition = StopCondition::Memory {
+            addr: 0x0000,
+            value: nes.buses.peek(0x0000).wrapping_add(10),
+        };
+        let options = HeadlessOptions {
+            stop_conditions: vec![condition, StopCondition::Cycles(1_000_000)],
+            hash_frames: false,
+        };
+        let run = nes.run_headless(DebugLevel::None, &options);
+        assert_eq!(run.reason, StopReason::Condition(condition));
+        // Each loop of INC and JMP takes 8 cycles.
+        assert!(run.cycles <= 10 * 8);
+    }
+
     #[test]
     fn nestest() {
-        let cart = load_cart("tests/nestest/nestest.nes");
+        let data = std::fs::read("tests/nestest/nestest.nes").expect("nestest.nes should exist");
+        let cart = read_cartridge(&data, &LoadOptions::default()).expect("nestest.nes should load");
 
         let mut nes = NES {
             buses: Buses::new(cart),
@@ -209,9 +838,26 @@ mod tests {
                     ir: 0x00,
                 },
             ),
+            save_file: None,
+            bindings: Bindings::default(),
+            quick_saves: None,
+            rewind: None,
+            run_ahead: 0,
+            movie: None,
+            power_on_state: None,
         };
 
-        nes.run_headless(DebugLevel::None);
+        // The PPU runs alongside the 7 cycles of the reset sequence.
+        for _ in 0..7 {
+            nes.buses.tick();
+        }
+        let log =
+            std::fs::read_to_string("tests/nestest/nestest.log").expect("nestest.log should exist");
+        if let Err(mismatch) = compare_trace(&mut nes, &log) {
+            panic!("{mismatch}");
+        }
+
+        nes.run_headless(DebugLevel::None, &HeadlessOptions::default());
 
         assert_eq!(nes.buses.peek(0x0002), 0x00);
         assert_eq!(nes.buses.peek(0x0003), 0x00);
@@ -0,0 +1,458 @@
+// https://fceux.com/web/help/fm2.html
+
+use crate::emu::{
+    error::{Error, FileError, MovieError},
+    nes::bindings::PLAYER_COUNT,
+};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The version of the FM2 format that is read and written.
+const FM2_VERSION: &str = "3";
+
+/// The command to press the reset button at the start of a frame.
+pub const SOFT_RESET: u8 = 0b_0000_0001;
+/// The command to power cycle the console at the start of a frame.
+pub const HARD_RESET: u8 = 0b_0000_0010;
+
+/// The buttons of a gamepad in the order FM2 lists them, from the highest bit
+/// of [`crate::emu::io::controller::Buttons`] to the lowest.
+const GAMEPAD_BUTTONS: &[u8; 8] = b"RLDUTSBA";
+
+/// The FM2 values for the devices plugged into the ports.
+const PORT_NONE: &str = "0";
+const PORT_GAMEPAD: &str = "1";
+
+/// The commands and buttons for a frame of a movie.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MovieFrame {
+    pub commands: u8,
+    /// Each player's buttons, as for [`crate::emu::io::controller::Buttons`].
+    pub buttons: [u8; PLAYER_COUNT],
+}
+
+/// A movie of the input for each frame in FCEUX's FM2 text format, starting
+/// from power-on or from a save state.
+///
+/// Only gamepads are supported, either one in each port or four through a Four
+/// Score. Embedded save states are in this emulator's own format, so movies
+/// that FCEUX recorded from a save state can't be played.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Movie {
+    pub four_score: bool,
+    pub rom_filename: String,
+    pub guid: String,
+    pub rerecord_count: u32,
+    /// The state the movie starts from, or `None` to start from power-on.
+    pub start_state: Option<Vec<u8>>,
+    pub frames: Vec<MovieFrame>,
+}
+
+impl Movie {
+    /// Creates an empty movie to record gameplay of the ROM at the given path.
+    pub fn new(rom_path: &str, four_score: bool, start_state: Option<Vec<u8>>) -> Self {
+        let rom_filename = Path::new(rom_path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Self {
+            four_score,
+            guid: create_guid(&rom_filename),
+            rom_filename,
+            rerecord_count: 0,
+            start_state,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Parses a movie in the FM2 text format.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut movie = Movie::default();
+        let mut ports = [PORT_GAMEPAD.to_string(), PORT_GAMEPAD.to_string()];
+
+        for (index, line) in text.lines().enumerate() {
+            let invalid = |message: String| {
+                Error::from(MovieError::InvalidLine {
+                    line: index + 1,
+                    message,
+                })
+            };
+
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('|') {
+                let frame = parse_frame(line, movie.four_score).map_err(invalid)?;
+                movie.frames.push(frame);
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "version" if value != FM2_VERSION => {
+                    return Err(invalid(format!("unsupported version {value}")));
+                }
+                "binary" if value != "0" => {
+                    return Err(not_supported("binary input logs aren't supported"));
+                }
+                "palFlag" if value != "0" => {
+                    return Err(not_supported("PAL movies aren't supported"));
+                }
+                "port2" if value != PORT_NONE => {
+                    return Err(not_supported("expansion port devices aren't supported"));
+                }
+                "fourscore" => movie.four_score = value == "1",
+                "port0" => ports[0] = value.to_string(),
+                "port1" => ports[1] = value.to_string(),
+                "romFilename" => movie.rom_filename = value.to_string(),
+                "guid" => movie.guid = value.to_string(),
+                "rerecordCount" => {
+                    movie.rerecord_count = value
+                        .parse()
+                        .map_err(|_| invalid(format!("{value} is not a rerecord count")))?;
+                }
+                "savestate" => {
+                    let state = decode_base64(value.trim_start_matches("base64:"))
+                        .ok_or_else(|| invalid("save state is not valid base64".to_string()))?;
+                    movie.start_state = Some(state);
+                }
+                // ignore; other keys don't affect playback
+                _ => {}
+            }
+        }
+
+        if !movie.four_score
+            && let Some(port) = ports
+                .iter()
+                .find(|port| *port != PORT_NONE && *port != PORT_GAMEPAD)
+        {
+            return Err(not_supported(&format!(
+                "only gamepads are supported, but a port has device {port}"
+            )));
+        }
+
+        Ok(movie)
+    }
+
+    /// Reads a movie from an FM2 file.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            Error::from(FileError::FileOpenFailed {
+                message: format!("{}: {e}", path.display()),
+            })
+        })?;
+
+        Self::parse(&text)
+    }
+
+    /// Writes the movie to an FM2 file.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        std::fs::write(path, self.to_string()).map_err(|e| {
+            Error::from(FileError::FileWriteFailed {
+                message: format!("{}: {e}", path.display()),
+            })
+        })
+    }
+}
+
+impl fmt::Display for Movie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let port = if self.four_score {
+            PORT_NONE
+        } else {
+            PORT_GAMEPAD
+        };
+
+        writeln!(f, "version {FM2_VERSION}")?;
+        writeln!(f, "emuVersion 0")?;
+        writeln!(f, "rerecordCount {}", self.rerecord_count)?;
+        writeln!(f, "palFlag 0")?;
+        writeln!(f, "romFilename {}", self.rom_filename)?;
+        writeln!(f, "guid {}", self.guid)?;
+        writeln!(f, "fourscore {}", self.four_score as u8)?;
+        writeln!(f, "microphone 0")?;
+        writeln!(f, "port0 {port}")?;
+        writeln!(f, "port1 {port}")?;
+        writeln!(f, "port2 {PORT_NONE}")?;
+        if let Some(state) = &self.start_state {
+            writeln!(f, "savestate base64:{}", encode_base64(state))?;
+        }
+
+        let gamepad_count = if self.four_score { PLAYER_COUNT } else { 2 };
+        for frame in self.frames.iter() {
+            write!(f, "|{}|", frame.commands)?;
+            for buttons in &frame.buttons[..gamepad_count] {
+                for (i, name) in GAMEPAD_BUTTONS.iter().enumerate() {
+                    let pressed = buttons & (0b_1000_0000 >> i) != 0;
+                    write!(f, "{}", if pressed { *name as char } else { '.' })?;
+                }
+                write!(f, "|")?;
+            }
+            writeln!(f, "|")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn not_supported(message: &str) -> Error {
+    MovieError::NotSupported {
+        message: message.to_string(),
+    }
+    .into()
+}
+
+/// Parses a line of the input log, such as `|0|R......A|........||`. Ports
+/// without a gamepad have an empty field.
+fn parse_frame(line: &str, four_score: bool) -> Result<MovieFrame, String> {
+    let fields: Vec<&str> = line.split('|').collect();
+    let gamepad_count = if four_score { PLAYER_COUNT } else { 2 };
+    // The line starts and ends with a separator, and has a field for the
+    // expansion port after the gamepads.
+    if fields.len() < gamepad_count + 4 {
+        return Err(format!("expected {} fields", gamepad_count + 2));
+    }
+
+    let commands = fields[1];
+    let mut frame = MovieFrame {
+        commands: commands
+            .parse()
+            .map_err(|_| format!("{commands} is not a command"))?,
+        ..Default::default()
+    };
+
+    for (player, field) in fields[2..2 + gamepad_count].iter().enumerate() {
+        if field.is_empty() {
+            continue;
+        }
+        if field.len() != GAMEPAD_BUTTONS.len() {
+            return Err(format!("{field} is not a gamepad's buttons"));
+        }
+
+        for (i, c) in field.bytes().enumerate() {
+            if c != b'.' && c != b' ' {
+                frame.buttons[player] |= 0b_1000_0000 >> i;
+            }
+        }
+    }
+
+    Ok(frame)
+}
+
+/// Creates a GUID for a new movie from the current time and the ROM's name.
+fn create_guid(rom_filename: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+
+    let mut sha1 = sha1_smol::Sha1::new();
+    sha1.update(&nanos.to_le_bytes());
+    sha1.update(rom_filename.as_bytes());
+    let hex: String = sha1.digest().bytes()[..16]
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect();
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_base64(data: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (value >> (18 - i * 6)) & 0b_0011_1111;
+                text.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut value = 0u32;
+    let mut bits = 0;
+    for c in text.trim_end_matches('=').bytes() {
+        let index = BASE64_ALPHABET.iter().position(|&b| b == c)?;
+        value = value << 6 | index as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            data.push((value >> bits) as u8);
+        }
+    }
+    Some(data)
+}
+
+/// Whether a movie is being recorded or played.
+enum MovieMode {
+    Recording { path: PathBuf },
+    Playing { finished: bool },
+}
+
+/// A movie being recorded or played back. The movie's frames are matched to
+/// the PPU's frame count, so that loading a state moves to the frame it was
+/// saved on. Recording over earlier frames, after loading a state or
+/// rewinding, counts as a rerecord.
+pub struct MovieSession {
+    pub movie: Movie,
+    mode: MovieMode,
+    /// The PPU's frame count at the start of the movie.
+    start_frame: u64,
+}
+
+impl MovieSession {
+    /// Records a movie, to be saved to the given path.
+    pub fn record(movie: Movie, path: PathBuf) -> Self {
+        Self {
+            movie,
+            mode: MovieMode::Recording { path },
+            start_frame: 0,
+        }
+    }
+
+    pub fn play(movie: Movie) -> Self {
+        Self {
+            movie,
+            mode: MovieMode::Playing { finished: false },
+            start_frame: 0,
+        }
+    }
+
+    /// Starts the movie on the given frame.
+    pub fn start(&mut self, frame_count: u64) {
+        self.start_frame = frame_count;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, MovieMode::Recording { .. })
+    }
+
+    /// Returns the input for the frame with the given count. While recording,
+    /// this is the host's input, which is recorded. While playing, this is the
+    /// movie's input, or the host's once the movie has finished.
+    pub fn next_frame(&mut self, frame_count: u64, host_input: MovieFrame) -> MovieFrame {
+        let index = frame_count.saturating_sub(self.start_frame) as usize;
+        let frames = &mut self.movie.frames;
+
+        match &mut self.mode {
+            MovieMode::Recording { .. } => {
+                if index < frames.len() {
+                    frames.truncate(index);
+                    self.movie.rerecord_count += 1;
+                }
+                frames.resize(index, MovieFrame::default());
+                frames.push(host_input);
+                host_input
+            }
+            MovieMode::Playing { finished } => match frames.get(index) {
+                Some(frame) => {
+                    *finished = false;
+                    *frame
+                }
+                None => {
+                    *finished = true;
+                    host_input
+                }
+            },
+        }
+    }
+
+    /// Returns `true` if the movie being played has run out of frames.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.mode, MovieMode::Playing { finished: true })
+    }
+
+    /// Saves the movie being recorded.
+    pub fn save(&self) -> Result<(), Error> {
+        match &self.mode {
+            MovieMode::Recording { path } => self.movie.save(path),
+            MovieMode::Playing { .. } => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        io::controller::Buttons,
+        nes::movie::{HARD_RESET, Movie, MovieFrame, SOFT_RESET},
+    };
+
+    #[test]
+    fn fm2_round_trip() {
+        let text = "version 3\n\
+            emuVersion 22020\n\
+            rerecordCount 7\n\
+            palFlag 0\n\
+            romFilename game\n\
+            guid 452DE2C3-EF43-2FA9-77AC-0677FC51543B\n\
+            fourscore 0\n\
+            microphone 0\n\
+            port0 1\n\
+            port1 1\n\
+            port2 0\n\
+            comment author someone\n\
+            |1|........|........||\n\
+            |0|R......A|...U.S..||\n\
+            |2|........|........||\n";
+
+        let movie = Movie::parse(text).unwrap();
+        assert_eq!(movie.rerecord_count, 7);
+        assert_eq!(
+            movie.frames,
+            [
+                MovieFrame {
+                    commands: SOFT_RESET,
+                    buttons: [0; 4],
+                },
+                MovieFrame {
+                    commands: 0,
+                    buttons: [
+                        Buttons::RIGHT | Buttons::A,
+                        Buttons::UP | Buttons::SELECT,
+                        0,
+                        0
+                    ],
+                },
+                MovieFrame {
+                    commands: HARD_RESET,
+                    buttons: [0; 4],
+                },
+            ]
+        );
+
+        let mut movie = movie;
+        movie.four_score = true;
+        movie.start_state = Some(vec![0, 1, 2, 0xFE, 0xFF]);
+        assert_eq!(Movie::parse(&movie.to_string()).unwrap(), movie);
+
+        assert!(Movie::parse("port1 2\n").is_err());
+    }
+}
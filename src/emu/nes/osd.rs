@@ -0,0 +1,133 @@
+use sdl2::{
+    pixels::Color,
+    rect::Rect,
+    render::{BlendMode, Canvas},
+    video::Window,
+};
+use std::time::{Duration, Instant};
+
+/// How long a message stays on screen.
+const MESSAGE_DURATION: Duration = Duration::from_secs(2);
+
+const GLYPH_WIDTH: i32 = 5;
+const GLYPH_HEIGHT: i32 = 7;
+/// The gap between glyphs, and the padding around the message.
+const GLYPH_SPACING: i32 = 1;
+const MESSAGE_X: i32 = 4;
+const MESSAGE_Y: i32 = 4;
+
+const BACKGROUND_COLOR: Color = Color::RGBA(0, 0, 0, 192);
+const TEXT_COLOR: Color = Color::WHITE;
+
+/// An on-screen display that shows a short message over the frame, such as a
+/// confirmation that a state was saved.
+#[derive(Default)]
+pub struct Osd {
+    message: Option<(String, Instant)>,
+}
+
+impl Osd {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows a message, replacing the one on screen.
+    pub fn show(&mut self, message: String) {
+        self.message = Some((message.to_uppercase(), Instant::now()));
+    }
+
+    /// Draws the message over the canvas, in the canvas's logical coordinates,
+    /// until it expires.
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>) {
+        let Some((message, shown_at)) = &self.message else {
+            return;
+        };
+        if shown_at.elapsed() >= MESSAGE_DURATION {
+            self.message = None;
+            return;
+        }
+
+        let columns = message.chars().count() as i32;
+        let background = Rect::new(
+            MESSAGE_X,
+            MESSAGE_Y,
+            (columns * (GLYPH_WIDTH + GLYPH_SPACING) + GLYPH_SPACING) as u32,
+            (GLYPH_HEIGHT + GLYPH_SPACING * 2) as u32,
+        );
+
+        let mut pixels = Vec::new();
+        for (column, c) in message.chars().enumerate() {
+            let glyph_x = MESSAGE_X + GLYPH_SPACING + column as i32 * (GLYPH_WIDTH + GLYPH_SPACING);
+            let glyph_y = MESSAGE_Y + GLYPH_SPACING;
+
+            for (y, row) in get_glyph(c).iter().enumerate() {
+                for x in 0..GLYPH_WIDTH {
+                    if row & (0b_0001_0000 >> x) != 0 {
+                        pixels.push(Rect::new(glyph_x + x, glyph_y + y as i32, 1, 1));
+                    }
+                }
+            }
+        }
+
+        let blend_mode = canvas.blend_mode();
+        let draw_color = canvas.draw_color();
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(BACKGROUND_COLOR);
+        canvas.fill_rect(background).unwrap();
+        canvas.set_draw_color(TEXT_COLOR);
+        canvas.fill_rects(&pixels).unwrap();
+        canvas.set_blend_mode(blend_mode);
+        canvas.set_draw_color(draw_color);
+    }
+}
+
+/// Gets the rows of a character's glyph, from top to bottom, with the leftmost
+/// pixel in bit 4. Characters without a glyph are drawn as `?`.
+fn get_glyph(c: char) -> [u8; GLYPH_HEIGHT as usize] {
+    match c {
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
@@ -0,0 +1,219 @@
+use crate::emu::{
+    error::{Error, FileError},
+    ppu::frame::Frame,
+    state::{StateReader, StateWriter},
+};
+use sdl2::keyboard::Keycode;
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of quick-save slots.
+pub const SLOT_COUNT: usize = 10;
+
+/// The keys that load each slot, or save it while Shift is held. They're left
+/// to the Family BASIC keyboard while it's plugged in, as it has keys F1-F8.
+pub const SLOT_KEYS: [Keycode; SLOT_COUNT] = [
+    Keycode::F1,
+    Keycode::F2,
+    Keycode::F3,
+    Keycode::F4,
+    Keycode::F5,
+    Keycode::F6,
+    Keycode::F7,
+    Keycode::F8,
+    Keycode::F9,
+    Keycode::F10,
+];
+
+const STATE_DIR_EXTENSION: &str = "states";
+const STATE_FILE_EXTENSION: &str = "state";
+const TEMP_FILE_EXTENSION: &str = "state.tmp";
+
+/// A save state kept in a quick-save slot, along with the frame on screen and
+/// the time when it was saved.
+pub struct QuickSave {
+    /// When the state was saved, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The frame on screen, as returned by [`Frame::get_pixel_data`].
+    thumbnail: Vec<u8>,
+    pub state: Vec<u8>,
+}
+
+impl QuickSave {
+    /// Creates a quick save of the given state, timestamped with the current
+    /// time.
+    pub fn new(thumbnail: &Frame, state: Vec<u8>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            timestamp,
+            thumbnail: thumbnail.get_pixel_data().to_vec(),
+            state,
+        }
+    }
+
+    pub fn get_thumbnail(&self) -> Frame {
+        Frame::from_pixel_data(self.thumbnail.as_slice().try_into().unwrap())
+    }
+}
+
+/// The directory of numbered `.state` files holding a ROM's quick saves.
+pub struct QuickSaves {
+    dir: PathBuf,
+}
+
+impl QuickSaves {
+    /// Creates the quick saves for the ROM at the given path. They're kept in
+    /// a `.states` directory named after the ROM and placed in `save_dir` if
+    /// given, or next to the ROM otherwise.
+    pub fn new(rom_path: &str, save_dir: Option<&str>) -> Self {
+        let rom_path = Path::new(rom_path);
+        let dir_name = rom_path
+            .with_extension(STATE_DIR_EXTENSION)
+            .file_name()
+            .map(|name| name.to_owned())
+            .unwrap_or_default();
+
+        let dir = match save_dir {
+            Some(dir) => Path::new(dir).join(dir_name),
+            None => rom_path.with_extension(STATE_DIR_EXTENSION),
+        };
+
+        Self { dir }
+    }
+
+    /// Gets the path of the file for a slot, numbered from 1.
+    pub fn get_path(&self, slot: usize) -> PathBuf {
+        self.dir
+            .join(format!("slot{slot}"))
+            .with_extension(STATE_FILE_EXTENSION)
+    }
+
+    /// Writes a quick save to a slot, replacing whatever was in it.
+    ///
+    /// The data is first written to a temporary file which then replaces the
+    /// slot's file, so an interrupted write never leaves a truncated state.
+    pub fn write(&self, slot: usize, save: &QuickSave, rom_hash: u32) -> Result<(), Error> {
+        let path = self.get_path(slot);
+        let write_failed = |e: std::io::Error| {
+            Error::from(FileError::FileWriteFailed {
+                message: format!("{}: {e}", path.display()),
+            })
+        };
+
+        let mut data = StateWriter::new(rom_hash);
+        data.write_u64(save.timestamp);
+        data.write_bytes(&save.thumbnail);
+        data.write_block(&save.state);
+
+        std::fs::create_dir_all(&self.dir).map_err(write_failed)?;
+        let temp_path = path.with_extension(TEMP_FILE_EXTENSION);
+        std::fs::write(&temp_path, data.into_data()).map_err(write_failed)?;
+        std::fs::rename(&temp_path, &path).map_err(write_failed)?;
+
+        Ok(())
+    }
+
+    /// Reads the quick save in a slot, or `None` if the slot is empty.
+    pub fn read(&self, slot: usize, rom_hash: u32) -> Result<Option<QuickSave>, Error> {
+        let path = self.get_path(slot);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = std::fs::read(&path).map_err(|e| {
+            Error::from(FileError::FileOpenFailed {
+                message: format!("{}: {e}", path.display()),
+            })
+        })?;
+
+        let mut data = StateReader::new(&data, rom_hash)?;
+        let timestamp = data.read_u64()?;
+        let mut thumbnail = vec![0; Frame::WIDTH * Frame::HEIGHT * Frame::BYTES_PER_PIXEL];
+        data.read_bytes(&mut thumbnail)?;
+        let state = data.read_block_data()?.to_vec();
+        data.finish()?;
+
+        Ok(Some(QuickSave {
+            timestamp,
+            thumbnail,
+            state,
+        }))
+    }
+}
+
+/// Formats seconds since the Unix epoch as a UTC date and time, such as
+/// `2026-10-19 14:03 UTC`.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let minutes = timestamp % 86400 / 60;
+
+    // Converts days since the epoch to a civil date, with years starting in
+    // March so that leap days fall at the end of the year.
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        nes::quick_save::{QuickSave, QuickSaves, format_timestamp},
+        ppu::frame::Frame,
+    };
+    use sdl2::{pixels::Color, rect::Point};
+
+    #[test]
+    fn timestamps_are_formatted_as_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00 UTC");
+        assert_eq!(format_timestamp(1_792_418_580), "2026-10-19 14:03 UTC");
+    }
+
+    #[test]
+    fn slots_round_trip() {
+        let dir = std::env::temp_dir().join(format!("green_nes_quick_save_{}", std::process::id()));
+        let rom_path = dir.join("game.nes");
+        let quick_saves = QuickSaves::new(rom_path.to_str().unwrap(), None);
+        assert_eq!(quick_saves.get_path(3), dir.join("game.states/slot3.state"));
+
+        let mut thumbnail = Frame::default();
+        thumbnail.set_pixel(Point::new(10, 20), Color::RGB(1, 2, 3));
+        let save = QuickSave::new(&thumbnail, vec![4, 5, 6]);
+
+        assert!(quick_saves.read(3, 0xABCD).unwrap().is_none());
+        quick_saves.write(3, &save, 0xABCD).unwrap();
+        let loaded = quick_saves.read(3, 0xABCD).unwrap().unwrap();
+        assert!(quick_saves.read(3, 0xDCBA).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.timestamp, save.timestamp);
+        assert_eq!(loaded.state, save.state);
+        assert_eq!(
+            loaded.get_thumbnail().get_pixel(Point::new(10, 20)),
+            Color::RGB(1, 2, 3)
+        );
+    }
+}
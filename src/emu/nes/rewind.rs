@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+
+/// How often a state is recorded for rewinding, in frames.
+pub const DEFAULT_REWIND_INTERVAL: u64 = 1;
+/// How much memory the recorded states may take up, in bytes.
+pub const DEFAULT_REWIND_MEMORY_BUDGET: usize = 64 * 1024 * 1024;
+
+/// A history of save states that gameplay can be rewound through.
+///
+/// Only the newest state is kept in full. Each older state is kept as a delta
+/// against the state after it, which is mostly zeros as little changes from one
+/// frame to the next, with runs of zeros compressed away. When the deltas go
+/// over the memory budget, the oldest are dropped.
+pub struct Rewind {
+    interval: u64,
+    memory_budget: usize,
+    latest: Option<Vec<u8>>,
+    /// Deltas from each state to the one before it, oldest first.
+    deltas: VecDeque<Vec<u8>>,
+    /// The total size of the deltas, in bytes.
+    deltas_size: usize,
+}
+
+impl Rewind {
+    /// Creates a history that records a state every `interval` frames, keeping
+    /// states within `memory_budget` bytes.
+    pub fn new(interval: u64, memory_budget: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            memory_budget,
+            latest: None,
+            deltas: VecDeque::new(),
+            deltas_size: 0,
+        }
+    }
+
+    pub fn get_interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Returns the number of states that can be rewound through.
+    pub fn len(&self) -> usize {
+        match self.latest {
+            Some(_) => self.deltas.len() + 1,
+            None => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latest.is_none()
+    }
+
+    pub fn get_memory_used(&self) -> usize {
+        self.latest.as_ref().map_or(0, Vec::len) + self.deltas_size
+    }
+
+    /// Records a new state.
+    pub fn push(&mut self, state: Vec<u8>) {
+        if let Some(latest) = self.latest.take() {
+            let delta = encode_delta(&state, &latest);
+            self.deltas_size += delta.len();
+            self.deltas.push_back(delta);
+        }
+        self.latest = Some(state);
+
+        while self.get_memory_used() > self.memory_budget
+            && let Some(delta) = self.deltas.pop_front()
+        {
+            self.deltas_size -= delta.len();
+        }
+    }
+
+    /// Takes the newest state, which is removed from the history unless it's
+    /// the only one left, so that rewinding stops at the oldest state.
+    pub fn pop(&mut self) -> Option<Vec<u8>> {
+        let latest = self.latest.as_ref()?;
+        let Some(delta) = self.deltas.pop_back() else {
+            return Some(latest.clone());
+        };
+
+        self.deltas_size -= delta.len();
+        let previous = apply_delta(latest, &delta);
+        self.latest.replace(previous)
+    }
+}
+
+/// Encodes the bytes that differ between a base state and a target state.
+///
+/// The delta starts with the target's length, followed by pairs of a run of
+/// unchanged bytes and a run of changed bytes, XORed with the base. Lengths are
+/// written as LEB128 variable-length integers.
+fn encode_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut delta = Vec::new();
+    write_varint(&mut delta, target.len());
+
+    let xored: Vec<u8> = target
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| byte ^ base.get(i).copied().unwrap_or_default())
+        .collect();
+
+    let mut position = 0;
+    while position < xored.len() {
+        let unchanged = xored[position..]
+            .iter()
+            .take_while(|byte| **byte == 0)
+            .count();
+        position += unchanged;
+
+        let changed = xored[position..]
+            .iter()
+            .take_while(|byte| **byte != 0)
+            .count();
+        write_varint(&mut delta, unchanged);
+        write_varint(&mut delta, changed);
+        delta.extend_from_slice(&xored[position..position + changed]);
+        position += changed;
+    }
+
+    delta
+}
+
+/// Rebuilds the target state from a base state and a delta made by
+/// [`encode_delta`].
+fn apply_delta(base: &[u8], delta: &[u8]) -> Vec<u8> {
+    let mut position = 0;
+    let len = read_varint(delta, &mut position);
+    let mut target = base.to_vec();
+    target.resize(len, 0);
+
+    let mut index = 0;
+    while position < delta.len() {
+        index += read_varint(delta, &mut position);
+        let changed = read_varint(delta, &mut position);
+        for byte in &delta[position..position + changed] {
+            target[index] ^= byte;
+            index += 1;
+        }
+        position += changed;
+    }
+
+    target
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        data.push(value as u8 | 0b_1000_0000);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn read_varint(data: &[u8], position: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = data[*position];
+        *position += 1;
+        value |= ((byte & 0b_0111_1111) as usize) << shift;
+        if byte & 0b_1000_0000 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::nes::rewind::Rewind;
+
+    #[test]
+    fn states_are_rewound_newest_first() {
+        let states: Vec<Vec<u8>> = (0..5u8)
+            .map(|i| {
+                let mut state = vec![0xAA; 1000];
+                state[10] = i;
+                state[500..500 + i as usize].fill(i);
+                state.resize(1000 + i as usize * 200, 0xAA);
+                state
+            })
+            .collect();
+
+        let mut rewind = Rewind::new(1, usize::MAX);
+        for state in states.iter() {
+            rewind.push(state.clone());
+        }
+        // Only the newest state is kept in full.
+        assert!(rewind.get_memory_used() < 2000);
+
+        for state in states.iter().rev() {
+            assert_eq!(rewind.pop().as_ref(), Some(state));
+        }
+        // The oldest state is kept once reached.
+        assert_eq!(rewind.pop().as_ref(), Some(&states[0]));
+        assert_eq!(rewind.len(), 1);
+    }
+
+    #[test]
+    fn oldest_states_are_dropped_over_budget() {
+        let mut rewind = Rewind::new(1, 1100);
+        let create_state = |i| {
+            let mut state = vec![0; 1000];
+            state[0] = i;
+            state
+        };
+        for i in 0..100 {
+            rewind.push(create_state(i));
+        }
+
+        assert!(rewind.get_memory_used() <= 1100);
+        assert!(rewind.len() > 1 && rewind.len() < 100);
+        assert_eq!(rewind.pop(), Some(create_state(99)));
+        assert_eq!(rewind.pop(), Some(create_state(98)));
+    }
+}
@@ -0,0 +1,426 @@
+use crate::{
+    DebugLevel,
+    emu::nes::{
+        NES,
+        headless::{HeadlessOptions, StopCondition, StopReason},
+    },
+};
+
+/// The address test ROMs write their status to.
+const STATUS_ADDR: u16 = 0x6000;
+/// The address of the signature that marks the status and message as valid.
+const SIGNATURE_ADDR: u16 = 0x6001;
+const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+/// The address of the NUL-terminated message.
+const MESSAGE_ADDR: u16 = 0x6004;
+/// The end of PRG RAM, which the message can't run past.
+const MESSAGE_END_ADDR: u16 = 0x7FFF;
+
+/// The status while the test is running.
+const STATUS_RUNNING: u8 = 0x80;
+/// The status while the test waits for the reset button to be pressed.
+const STATUS_RESET_REQUESTED: u8 = 0x81;
+/// How long to wait before pressing reset when it's requested, in frames. The
+/// ROMs ask for at least 100 ms.
+const RESET_DELAY_FRAMES: u64 = 10;
+
+/// How long a test ROM may run before it's given up on, in seconds.
+pub const DEFAULT_TEST_ROM_TIMEOUT: u64 = 120;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TestRomStatus {
+    /// The test finished with a result code, which is 0 if it passed.
+    Finished(u8),
+    /// The test didn't finish before the timeout.
+    TimedOut,
+    /// The CPU executed a JAM instruction and halted.
+    Jammed,
+}
+
+/// The outcome of running a test ROM.
+#[derive(Clone, Debug)]
+pub struct TestRomResult {
+    pub status: TestRomStatus,
+    /// The message the test wrote, which may be partial if it didn't finish.
+    pub message: String,
+    /// The number of frames run.
+    pub frames: u64,
+}
+
+/// Runs a test ROM that reports its results in PRG RAM, as blargg's test ROMs
+/// do, until it finishes or `timeout_frames` frames have been run.
+///
+/// While the signature is at `$6001`, the status at `$6000` is `$80` while the
+/// test is running, `$81` while it waits for the reset button to be pressed,
+/// and the result code once it's finished. The message is at `$6004`.
+pub fn run_test_rom(nes: &mut NES, debug_level: DebugLevel, timeout_frames: u64) -> TestRomResult {
+    let options = HeadlessOptions {
+        stop_conditions: vec![StopCondition::Frames(1)],
+        hash_frames: false,
+    };
+
+    let mut frames = 0;
+    let mut reset_at = None;
+    let mut reset_pressed = false;
+    let status = loop {
+        if frames >= timeout_frames {
+            break TestRomStatus::TimedOut;
+        }
+
+        let run = nes.run_headless(debug_level.clone(), &options);
+        frames += run.frames;
+        if run.reason == StopReason::Jam {
+            break TestRomStatus::Jammed;
+        }
+
+        if !has_signature(nes) {
+            continue;
+        }
+
+        match nes.buses.peek(STATUS_ADDR) {
+            STATUS_RUNNING => reset_pressed = false,
+            STATUS_RESET_REQUESTED => {
+                // The status stays the same until the ROM has started over, so
+                // reset is only pressed once for each request.
+                if reset_pressed {
+                    continue;
+                }
+
+                let reset_frame = *reset_at.get_or_insert(frames + RESET_DELAY_FRAMES);
+                if frames >= reset_frame {
+                    nes.soft_reset();
+                    reset_at = None;
+                    reset_pressed = true;
+                }
+            }
+            code => break TestRomStatus::Finished(code),
+        }
+    };
+
+    let message = match has_signature(nes) {
+        true => read_message(nes),
+        false => String::new(),
+    };
+
+    TestRomResult {
+        status,
+        message,
+        frames,
+    }
+}
+
+fn has_signature(nes: &NES) -> bool {
+    (0..SIGNATURE.len() as u16)
+        .map(|offset| nes.buses.peek(SIGNATURE_ADDR + offset))
+        .eq(SIGNATURE)
+}
+
+fn read_message(nes: &NES) -> String {
+    let bytes: Vec<u8> = (MESSAGE_ADDR..=MESSAGE_END_ADDR)
+        .map(|addr| nes.buses.peek(addr))
+        .take_while(|byte| *byte != 0)
+        .collect();
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        DebugLevel,
+        emu::{
+            cartridge::{LoadOptions, ines::INES_TAG, read_cartridge},
+            nes::{
+                NES,
+                test_rom::{
+                    DEFAULT_TEST_ROM_TIMEOUT, RESET_DELAY_FRAMES, TestRomStatus, run_test_rom,
+                },
+            },
+        },
+    };
+
+    /// Runs the test ROM at the given path, from blargg's test ROMs in
+    /// https://github.com/christopherpow/nes-test-roms, and checks that it
+    /// passes.
+    fn assert_test_rom_passes(path: &str) {
+        let data = std::fs::read(path).unwrap_or_else(|_| panic!("{path} should exist"));
+        let cart = read_cartridge(&data, &LoadOptions::default())
+            .unwrap_or_else(|err| panic!("{path} should load: {err}"));
+
+        let mut nes = NES::new(cart);
+        nes.power_on(None);
+        let result = run_test_rom(&mut nes, DebugLevel::None, DEFAULT_TEST_ROM_TIMEOUT * 60);
+
+        assert_eq!(
+            result.status,
+            TestRomStatus::Finished(0),
+            "{path} failed after {} frames:\n{}",
+            result.frames,
+            result.message.trim_end()
+        );
+    }
+
+    /// Declares a test that runs a ROM from `tests/`, which is downloaded by
+    /// `download-tests.sh`.
+    macro_rules! test_rom {
+        ($(#[$attr:meta])* $name:ident, $path:literal) => {
+            $(#[$attr])*
+            #[test]
+            fn $name() {
+                assert_test_rom_passes($path);
+            }
+        };
+    }
+
+    test_rom!(cpu_instrs_01_basics, "tests/cpu_instrs/01-basics.nes");
+    test_rom!(cpu_instrs_02_implied, "tests/cpu_instrs/02-implied.nes");
+    test_rom!(cpu_instrs_03_immediate, "tests/cpu_instrs/03-immediate.nes");
+    test_rom!(cpu_instrs_04_zero_page, "tests/cpu_instrs/04-zero_page.nes");
+    test_rom!(cpu_instrs_05_zp_xy, "tests/cpu_instrs/05-zp_xy.nes");
+    test_rom!(cpu_instrs_06_absolute, "tests/cpu_instrs/06-absolute.nes");
+    test_rom!(cpu_instrs_07_abs_xy, "tests/cpu_instrs/07-abs_xy.nes");
+    test_rom!(cpu_instrs_08_ind_x, "tests/cpu_instrs/08-ind_x.nes");
+    test_rom!(cpu_instrs_09_ind_y, "tests/cpu_instrs/09-ind_y.nes");
+    test_rom!(cpu_instrs_10_branches, "tests/cpu_instrs/10-branches.nes");
+    test_rom!(cpu_instrs_11_stack, "tests/cpu_instrs/11-stack.nes");
+    test_rom!(cpu_instrs_12_jmp_jsr, "tests/cpu_instrs/12-jmp_jsr.nes");
+    test_rom!(cpu_instrs_13_rts, "tests/cpu_instrs/13-rts.nes");
+    test_rom!(cpu_instrs_14_rti, "tests/cpu_instrs/14-rti.nes");
+    test_rom!(cpu_instrs_15_brk, "tests/cpu_instrs/15-brk.nes");
+    test_rom!(cpu_instrs_16_special, "tests/cpu_instrs/16-special.nes");
+
+    test_rom!(
+        #[ignore = "timing is measured with the APU, which isn't emulated yet"]
+        instr_timing_1_instr_timing,
+        "tests/instr_timing/1-instr_timing.nes"
+    );
+    test_rom!(
+        #[ignore = "timing is measured with the APU, which isn't emulated yet"]
+        instr_timing_2_branch_timing,
+        "tests/instr_timing/2-branch_timing.nes"
+    );
+
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_01_vbl_basics,
+        "tests/ppu_vbl_nmi/01-vbl_basics.nes"
+    );
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_02_vbl_set_time,
+        "tests/ppu_vbl_nmi/02-vbl_set_time.nes"
+    );
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_03_vbl_clear_time,
+        "tests/ppu_vbl_nmi/03-vbl_clear_time.nes"
+    );
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_04_nmi_control,
+        "tests/ppu_vbl_nmi/04-nmi_control.nes"
+    );
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_05_nmi_timing,
+        "tests/ppu_vbl_nmi/05-nmi_timing.nes"
+    );
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_06_suppression,
+        "tests/ppu_vbl_nmi/06-suppression.nes"
+    );
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_07_nmi_on_timing,
+        "tests/ppu_vbl_nmi/07-nmi_on_timing.nes"
+    );
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_08_nmi_off_timing,
+        "tests/ppu_vbl_nmi/08-nmi_off_timing.nes"
+    );
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_09_even_odd_frames,
+        "tests/ppu_vbl_nmi/09-even_odd_frames.nes"
+    );
+    test_rom!(
+        #[ignore = "the PPU isn't dot-accurate yet"]
+        ppu_vbl_nmi_10_even_odd_timing,
+        "tests/ppu_vbl_nmi/10-even_odd_timing.nes"
+    );
+
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_01_basics,
+        "tests/ppu_sprite_hit/01-basics.nes"
+    );
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_02_alignment,
+        "tests/ppu_sprite_hit/02-alignment.nes"
+    );
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_03_corners,
+        "tests/ppu_sprite_hit/03-corners.nes"
+    );
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_04_flip,
+        "tests/ppu_sprite_hit/04-flip.nes"
+    );
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_05_left_clip,
+        "tests/ppu_sprite_hit/05-left_clip.nes"
+    );
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_06_right_edge,
+        "tests/ppu_sprite_hit/06-right_edge.nes"
+    );
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_07_screen_bottom,
+        "tests/ppu_sprite_hit/07-screen_bottom.nes"
+    );
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_08_double_height,
+        "tests/ppu_sprite_hit/08-double_height.nes"
+    );
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_09_timing,
+        "tests/ppu_sprite_hit/09-timing.nes"
+    );
+    test_rom!(
+        #[ignore = "sprite 0 hit isn't emulated yet"]
+        ppu_sprite_hit_10_timing_order,
+        "tests/ppu_sprite_hit/10-timing_order.nes"
+    );
+
+    test_rom!(
+        #[ignore = "the APU isn't emulated yet"]
+        apu_test_1_len_ctr,
+        "tests/apu_test/1-len_ctr.nes"
+    );
+    test_rom!(
+        #[ignore = "the APU isn't emulated yet"]
+        apu_test_2_len_table,
+        "tests/apu_test/2-len_table.nes"
+    );
+    test_rom!(
+        #[ignore = "the APU isn't emulated yet"]
+        apu_test_3_irq_flag,
+        "tests/apu_test/3-irq_flag.nes"
+    );
+    test_rom!(
+        #[ignore = "the APU isn't emulated yet"]
+        apu_test_4_jitter,
+        "tests/apu_test/4-jitter.nes"
+    );
+    test_rom!(
+        #[ignore = "the APU isn't emulated yet"]
+        apu_test_5_len_timing,
+        "tests/apu_test/5-len_timing.nes"
+    );
+    test_rom!(
+        #[ignore = "the APU isn't emulated yet"]
+        apu_test_6_irq_flag_timing,
+        "tests/apu_test/6-irq_flag_timing.nes"
+    );
+    test_rom!(
+        #[ignore = "the APU isn't emulated yet"]
+        apu_test_7_dmc_basics,
+        "tests/apu_test/7-dmc_basics.nes"
+    );
+    test_rom!(
+        #[ignore = "the APU isn't emulated yet"]
+        apu_test_8_dmc_rates,
+        "tests/apu_test/8-dmc_rates.nes"
+    );
+
+    test_rom!(
+        #[ignore = "the MMC3 mapper isn't supported yet"]
+        mmc3_test_1_clocking,
+        "tests/mmc3_test/1-clocking.nes"
+    );
+    test_rom!(
+        #[ignore = "the MMC3 mapper isn't supported yet"]
+        mmc3_test_2_details,
+        "tests/mmc3_test/2-details.nes"
+    );
+    test_rom!(
+        #[ignore = "the MMC3 mapper isn't supported yet"]
+        mmc3_test_3_a12_clocking,
+        "tests/mmc3_test/3-A12_clocking.nes"
+    );
+    test_rom!(
+        #[ignore = "the MMC3 mapper isn't supported yet"]
+        mmc3_test_4_scanline_timing,
+        "tests/mmc3_test/4-scanline_timing.nes"
+    );
+    test_rom!(
+        #[ignore = "the MMC3 mapper isn't supported yet"]
+        mmc3_test_5_mmc3,
+        "tests/mmc3_test/5-MMC3.nes"
+    );
+    test_rom!(
+        #[ignore = "the MMC3 mapper isn't supported yet"]
+        mmc3_test_6_mmc3_alt,
+        "tests/mmc3_test/6-MMC3_alt.nes"
+    );
+
+    #[test]
+    fn reset_requests_and_results_are_handled() {
+        #[rustfmt::skip]
+        let program = [
+            0xAD, 0x10, 0x60,       // LDA $6010
+            0xD0, 0x1F,             // BNE finish
+            0xEE, 0x10, 0x60,       // INC $6010
+            0xA9, 0x80,             // LDA #$80
+            0x8D, 0x00, 0x60,       // STA $6000
+            0xA9, 0xDE,             // LDA #$DE
+            0x8D, 0x01, 0x60,       // STA $6001
+            0xA9, 0xB0,             // LDA #$B0
+            0x8D, 0x02, 0x60,       // STA $6002
+            0xA9, 0x61,             // LDA #$61
+            0x8D, 0x03, 0x60,       // STA $6003
+            0xA9, 0x81,             // LDA #$81
+            0x8D, 0x00, 0x60,       // STA $6000
+            0x4C, 0x21, 0xC0,       // JMP *
+            // finish:
+            0xA9, b'o',             // LDA #'o'
+            0x8D, 0x04, 0x60,       // STA $6004
+            0xA9, b'k',             // LDA #'k'
+            0x8D, 0x05, 0x60,       // STA $6005
+            0xA9, 0x00,             // LDA #0
+            0x8D, 0x06, 0x60,       // STA $6006
+            0xA9, 0x05,             // LDA #5
+            0x8D, 0x00, 0x60,       // STA $6000
+            0x4C, 0x38, 0xC0,       // JMP *
+        ];
+
+        // NROM with 16 KiB of PRG ROM at $C000 and 8 KiB of PRG RAM.
+        let mut rom = INES_TAG.to_vec();
+        rom.extend([1, 0, 0, 0b_0000_1000, 0, 0, 7, 7]);
+        rom.resize(16, 0);
+        rom.extend(program);
+        rom.resize(16 + 0x4000, 0xEA);
+        rom[16 + 0x3FFA..].copy_from_slice(&[0x00, 0xC0, 0x00, 0xC0, 0x00, 0xC0]);
+
+        let cart = read_cartridge(&rom, &LoadOptions::default()).expect("ROM should load");
+        let mut nes = NES::new(cart);
+        nes.power_on(None);
+        let result = run_test_rom(&mut nes, DebugLevel::None, 60);
+
+        // The ROM only finishes once it has been reset.
+        assert_eq!(result.status, TestRomStatus::Finished(5));
+        assert_eq!(result.message, "ok");
+        assert!(result.frames >= RESET_DELAY_FRAMES);
+        assert!(result.frames < 60);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::emu::nes::{NES, debug::get_trace_line};
+use std::fmt;
+
+/// The number of lines shown on either side of a mismatch.
+const CONTEXT_LINES: usize = 5;
+
+/// The first line where a trace differs from its reference.
+#[derive(Clone, Debug)]
+pub struct TraceMismatch {
+    /// The number of the line, counting from 1.
+    pub line: usize,
+    pub expected: String,
+    pub actual: String,
+    /// The reference lines before the mismatch, which matched.
+    pub before: Vec<String>,
+    /// The reference lines after the mismatch.
+    pub after: Vec<String>,
+}
+
+impl fmt::Display for TraceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "trace differs from the reference at line {}:", self.line)?;
+        for line in self.before.iter() {
+            writeln!(f, "  {line}")?;
+        }
+
+        let column = self
+            .expected
+            .chars()
+            .zip(self.actual.chars())
+            .take_while(|(expected, actual)| expected == actual)
+            .count();
+        writeln!(f, "- {}", self.expected)?;
+        writeln!(f, "+ {}", self.actual)?;
+        write!(f, "  {:column$}^", "")?;
+
+        for line in self.after.iter() {
+            write!(f, "\n  {line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the console one instruction at a time, comparing the trace line of
+/// each instruction with the next line of a reference trace in the format of
+/// nestest.log. Returns the number of lines that matched, which is all of
+/// them, or the first mismatch.
+pub fn compare_trace(nes: &mut NES, reference: &str) -> Result<usize, TraceMismatch> {
+    let expected: Vec<&str> = reference.lines().map(str::trim_end).collect();
+
+    for (index, expected_line) in expected.iter().enumerate() {
+        let actual = match nes.cpu.is_halted() {
+            true => "CPU jammed".to_string(),
+            false => get_trace_line(nes),
+        };
+
+        if actual != *expected_line {
+            let to_strings = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect();
+            let after_end = (index + 1 + CONTEXT_LINES).min(expected.len());
+
+            return Err(TraceMismatch {
+                line: index + 1,
+                expected: expected_line.to_string(),
+                actual,
+                before: to_strings(&expected[index.saturating_sub(CONTEXT_LINES)..index]),
+                after: to_strings(&expected[index + 1..after_end]),
+            });
+        }
+
+        step_instruction(nes);
+    }
+
+    Ok(expected.len())
+}
+
+/// Runs the console until the CPU finishes the current instruction.
+fn step_instruction(nes: &mut NES) {
+    loop {
+        nes.buses.tick();
+        nes.cpu.tick(&mut nes.buses);
+
+        if nes.cpu.is_between_instructions() || nes.cpu.is_halted() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        cartridge::{LoadOptions, ines::INES_TAG, read_cartridge},
+        cpu::{CPU, registers::Registers},
+        nes::{NES, trace::compare_trace},
+    };
+
+    #[test]
+    fn traces_are_compared_line_by_line() {
+        let mut data = INES_TAG.to_vec();
+        data.extend([1, 0]);
+        data.resize(16, 0);
+        let mut prg = vec![0; 0x4000];
+        // LDX #$05; INC $10,X; JMP $8000
+        prg[..7].copy_from_slice(&[0xA2, 0x05, 0xF6, 0x10, 0x4C, 0x00, 0x80]);
+        data.extend(prg);
+
+        let create_nes = || {
+            let cart = read_cartridge(&data, &LoadOptions::default()).unwrap();
+            let mut nes = NES::new(cart);
+            nes.cpu = CPU::new(
+                14,
+                Registers {
+                    pc: (0x80, 0x00),
+                    sp: 0xFD,
+                    psr: 0b100100.into(),
+                    ..Registers::default()
+                },
+            );
+            nes
+        };
+
+        let reference = "\
+8000  A2 05     LDX #$05                        A:00 X:00 Y:00 P:24 SP:FD PPU:  0,  0 CYC:7
+8002  F6 10     INC $10,X @ 15 = 00             A:00 X:05 Y:00 P:24 SP:FD PPU:  0,  6 CYC:9
+8004  4C 00 80  JMP $8000                       A:00 X:05 Y:00 P:24 SP:FD PPU:  0, 24 CYC:15
+8000  A2 05     LDX #$05                        A:00 X:05 Y:00 P:24 SP:FD PPU:  0, 33 CYC:18
+";
+        assert_eq!(compare_trace(&mut create_nes(), reference).unwrap(), 4);
+
+        let reference = reference.replace("@ 15 = 00", "@ 15 = 01");
+        let mismatch = compare_trace(&mut create_nes(), &reference).unwrap_err();
+        assert_eq!(mismatch.line, 2);
+        assert!(mismatch.actual.contains("@ 15 = 00"));
+        assert_eq!((mismatch.before.len(), mismatch.after.len()), (1, 2));
+    }
+}
@@ -1,6 +1,8 @@
 use crate::emu::{
     cartridge::{Cartridge, NametableMirroring},
+    error::Error,
     ppu::{mappings::*, nametable::Nametable},
+    state::{StateReader, StateWriter},
 };
 
 pub struct Buses {
@@ -57,6 +59,23 @@ impl Buses {
             }
         }
     }
+
+    /// Writes the nametables and palette RAM to a save state. Pattern tables
+    /// belong to the cartridge, so they are saved with the mapper.
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&*self.nametable_a);
+        state.write_bytes(&*self.nametable_b);
+        state.write_bytes(&self.palette_ram);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        for nametable in [&mut self.nametable_a, &mut self.nametable_b] {
+            let mut data = **nametable;
+            state.read_bytes(&mut data)?;
+            *nametable = Nametable::new(data);
+        }
+        state.read_bytes(&mut self.palette_ram)
+    }
 }
 
 fn do_palette_read(buses: &Buses, addr: u16) -> u8 {
@@ -71,6 +71,19 @@ impl Frame {
         self.pixels[location.y as usize][location.x as usize] = pixel;
     }
 
+    /// Creates a frame from pixel data returned by [`Frame::get_pixel_data`].
+    pub fn from_pixel_data(
+        data: &[u8; FRAME_WIDTH as usize * FRAME_HEIGHT as usize * FRAME_BYTES_PER_PIXEL as usize],
+    ) -> Self {
+        let mut pixels = data.chunks_exact(FRAME_BYTES_PER_PIXEL as usize);
+        Frame::new(array::from_fn(|_| {
+            array::from_fn(|_| {
+                let pixel = pixels.next().unwrap();
+                (pixel[0], pixel[1], pixel[2])
+            })
+        }))
+    }
+
     /// Returns the pixel data for a frame as a flattened array of bytes.
     pub fn get_pixel_data(
         &self,
@@ -1,5 +1,6 @@
 use crate::emu::{
     cartridge::Cartridge,
+    error::{Error, StateError},
     ppu::{
         buses::Buses,
         frame::{
@@ -12,6 +13,7 @@ use crate::emu::{
         },
         registers::{REGISTERS_AT_POWERON, Registers},
     },
+    state::{StateReader, StateWriter},
 };
 
 pub mod buses;
@@ -44,6 +46,9 @@ pub struct PPU {
     frame_ready: bool,
     /// The number of frames generated.
     frame_count: u64,
+    /// Whether frames are counted without being rendered, for frames that
+    /// won't be shown. This isn't part of the console's state.
+    rendering_skipped: bool,
 }
 
 impl PPU {
@@ -59,6 +64,7 @@ impl PPU {
             frame: Frame::default(),
             frame_ready: false,
             frame_count: 1,
+            rendering_skipped: false,
         }
     }
 
@@ -73,7 +79,6 @@ impl PPU {
             if self.scanline_index == VBLANK_LINE_INDEX {
                 self.registers.ppu_status.set_vblank_flag(true);
                 self.update_nmi();
-                self.render_frame();
             } else if self.scanline_index == PRERENDER_LINE_INDEX {
                 self.registers.ppu_status.set_vblank_flag(false);
                 self.update_nmi();
@@ -81,6 +86,15 @@ impl PPU {
                 self.frame_count += 1;
                 self.scanline_index = 0;
                 self.registers.oam_addr.data = 0;
+
+                // The whole frame is rendered as the visible scanlines begin,
+                // so it reflects the updates made during vblank, and devices
+                // like the Zapper can see the frame as it is being output.
+                if self.rendering_skipped {
+                    self.frame_ready = true;
+                } else {
+                    self.render_frame();
+                }
             }
         }
     }
@@ -90,6 +104,11 @@ impl PPU {
         self.frame_ready = false;
     }
 
+    /// Skips rendering frames, while still signalling when they're ready.
+    pub fn set_rendering_skipped(&mut self, skipped: bool) {
+        self.rendering_skipped = skipped;
+    }
+
     pub fn is_frame_ready(&self) -> bool {
         self.frame_ready
     }
@@ -98,10 +117,24 @@ impl PPU {
         self.scanline_index
     }
 
+    /// Returns the dot within the current scanline.
+    pub fn get_cycle_count(&self) -> u32 {
+        self.cycle_count
+    }
+
+    /// Returns the frame that is being output during the visible scanlines.
+    pub fn get_frame(&self) -> &Frame {
+        &self.frame
+    }
+
     pub fn get_frame_count(&self) -> u64 {
         self.frame_count
     }
 
+    pub fn set_frame_count(&mut self, frame_count: u64) {
+        self.frame_count = frame_count;
+    }
+
     /// Gets the index of the pattern for the pixel at the given (x,y) position.
     pub fn get_pattern_index(&self, x: u16, y: u16) -> u8 {
         let stride = (y / PATTERN_HEIGHT) * PATTERN_COLS_PER_FRAME;
@@ -305,4 +338,90 @@ impl PPU {
             self.registers.oam_addr.data = self.registers.oam_addr.data.wrapping_add(1)
         }
     }
+
+    /// Writes the PPU's state to a save state. The frame isn't saved, as it
+    /// can be rendered again from the PPU's memory.
+    pub fn save_state(&self, state: &mut StateWriter) {
+        let registers = self.registers;
+        state.write_bytes(&[
+            registers.ppu_ctrl.data,
+            registers.ppu_mask.data,
+            registers.ppu_status.data,
+            registers.oam_addr.data,
+            registers.oam_data.data,
+            registers.ppu_scroll.data,
+            registers.ppu_addr.data,
+            registers.ppu_data.data,
+            registers.oam_dma.data,
+        ]);
+        state.write_u16(registers.internal.v);
+        state.write_u16(registers.internal.t);
+        state.write_u8(registers.internal.x);
+        state.write_bool(registers.internal.w);
+
+        self.buses.save_state(state);
+        state.write_bytes(&self.oam);
+        state.write_u8(self.ppu_data_read_buffer);
+        state.write_bool(self.nmi_pin);
+        state.write_u32(self.cycle_count);
+        state.write_u32(self.scanline_index);
+        state.write_bool(self.frame_ready);
+        state.write_u64(self.frame_count);
+    }
+
+    /// Restores the state written by [`PPU::save_state`], and renders the frame
+    /// from the restored memory.
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), Error> {
+        let mut data = [0; 9];
+        state.read_bytes(&mut data)?;
+        let [
+            ppu_ctrl,
+            ppu_mask,
+            ppu_status,
+            oam_addr,
+            oam_data,
+            ppu_scroll,
+            ppu_addr,
+            ppu_data,
+            oam_dma,
+        ] = data;
+
+        let registers = &mut self.registers;
+        registers.ppu_ctrl.data = ppu_ctrl;
+        registers.ppu_mask.data = ppu_mask;
+        registers.ppu_status.data = ppu_status;
+        registers.oam_addr.data = oam_addr;
+        registers.oam_data.data = oam_data;
+        registers.ppu_scroll.data = ppu_scroll;
+        registers.ppu_addr.data = ppu_addr;
+        registers.ppu_data.data = ppu_data;
+        registers.oam_dma.data = oam_dma;
+        registers.internal.v = state.read_u16()?;
+        registers.internal.t = state.read_u16()?;
+        registers.internal.x = state.read_u8()?;
+        registers.internal.w = state.read_bool()?;
+
+        self.buses.load_state(state)?;
+        state.read_bytes(&mut self.oam)?;
+        self.ppu_data_read_buffer = state.read_u8()?;
+        self.nmi_pin = state.read_bool()?;
+        self.cycle_count = state.read_u32()?;
+        self.scanline_index = state.read_u32()?;
+        if PPU_CYCLES_PER_SCANLINE <= self.cycle_count || PRERENDER_LINE_INDEX < self.scanline_index
+        {
+            return Err(StateError::InvalidState {
+                message: format!(
+                    "PPU dot {} of scanline {} doesn't exist",
+                    self.cycle_count, self.scanline_index
+                ),
+            }
+            .into());
+        }
+        let frame_ready = state.read_bool()?;
+        self.frame_count = state.read_u64()?;
+
+        self.render_frame();
+        self.frame_ready = frame_ready;
+        Ok(())
+    }
 }
@@ -0,0 +1,238 @@
+use crate::emu::error::{Error, StateError};
+
+/// The magic bytes at the start of every save state.
+pub const STATE_TAG: [u8; 4] = *b"GNST";
+
+/// The version of the save state format. It is bumped whenever the layout of a
+/// state changes, so that older states are rejected instead of misread.
+pub const STATE_VERSION: u16 = 7;
+
+/// Serializes the state of the console into the save state format. Values are
+/// written little-endian, one after another, in the order they're read back.
+pub struct StateWriter {
+    data: Vec<u8>,
+}
+
+impl StateWriter {
+    /// Creates a writer for a save state of the ROM with the given hash,
+    /// starting with the state's header.
+    pub fn new(rom_hash: u32) -> Self {
+        let mut state = Self { data: Vec::new() };
+        state.write_bytes(&STATE_TAG);
+        state.write_u16(STATE_VERSION);
+        state.write_u32(rom_hash);
+        state
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.data.extend(value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.data.extend(value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.data.extend(value.to_le_bytes());
+    }
+
+    /// Writes bytes whose length is fixed, so it isn't stored.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Writes bytes prefixed with their length, such as RAM whose size depends
+    /// on the cartridge.
+    pub fn write_block(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.write_bytes(bytes);
+    }
+
+    /// Writes a section prefixed with its length, so that its contents can be
+    /// checked to have been read in full.
+    pub fn write_section(&mut self, write: impl FnOnce(&mut StateWriter)) {
+        let mut section = StateWriter { data: Vec::new() };
+        write(&mut section);
+        self.write_block(&section.data);
+    }
+
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Reads the state of the console back out of the save state format.
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    /// Creates a reader for a save state, checking that its header matches the
+    /// format version and the ROM with the given hash.
+    pub fn new(data: &'a [u8], rom_hash: u32) -> Result<Self, Error> {
+        let mut state = Self { data, position: 0 };
+
+        let mut tag = [0; STATE_TAG.len()];
+        state.read_bytes(&mut tag)?;
+        if tag != STATE_TAG {
+            return Err(StateError::MissingHeader.into());
+        }
+
+        let version = state.read_u16()?;
+        if version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion {
+                expected: STATE_VERSION,
+                actual: version,
+            }
+            .into());
+        }
+
+        let actual = state.read_u32()?;
+        if actual != rom_hash {
+            return Err(StateError::RomMismatch {
+                expected: rom_hash,
+                actual,
+            }
+            .into());
+        }
+
+        Ok(state)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(StateError::Truncated)?;
+
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, Error> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(StateError::InvalidState {
+                message: format!("{value} is not a boolean"),
+            }
+            .into()),
+        }
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, Error> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Fills the buffer with bytes written by [`StateWriter::write_bytes`].
+    pub fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), Error> {
+        bytes.copy_from_slice(self.take(bytes.len())?);
+        Ok(())
+    }
+
+    /// Fills the buffer with bytes written by [`StateWriter::write_block`],
+    /// which must be the same length as the buffer.
+    pub fn read_block(&mut self, bytes: &mut [u8]) -> Result<(), Error> {
+        let len = self.read_u32()? as usize;
+        if len != bytes.len() {
+            return Err(StateError::InvalidState {
+                message: format!("expected a block of {} bytes, found {len}", bytes.len()),
+            }
+            .into());
+        }
+
+        self.read_bytes(bytes)
+    }
+
+    /// Returns bytes written by [`StateWriter::write_block`], whatever their
+    /// length.
+    pub fn read_block_data(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.read_u32()? as usize;
+        self.take(len)
+    }
+
+    /// Reads a section written by [`StateWriter::write_section`], failing if
+    /// its contents aren't read in full.
+    pub fn read_section(
+        &mut self,
+        read: impl FnOnce(&mut StateReader) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let len = self.read_u32()? as usize;
+        let mut section = StateReader {
+            data: self.take(len)?,
+            position: 0,
+        };
+
+        read(&mut section)?;
+        section.finish()
+    }
+
+    /// Fails if any of the state is left unread.
+    pub fn finish(&self) -> Result<(), Error> {
+        match self.data.len() - self.position {
+            0 => Ok(()),
+            size => Err(StateError::TrailingData { size }.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emu::{
+        error::{Error, StateError},
+        state::{StateReader, StateWriter},
+    };
+
+    #[test]
+    fn header_is_checked() {
+        let mut state = StateWriter::new(0x1234_5678);
+        state.write_u16(0xBEEF);
+        let data = state.into_data();
+
+        let mut state = StateReader::new(&data, 0x1234_5678).unwrap();
+        assert_eq!(state.read_u16().unwrap(), 0xBEEF);
+        assert!(state.finish().is_ok());
+
+        assert!(matches!(
+            StateReader::new(&data, 0x8765_4321),
+            Err(Error::StateError {
+                err: StateError::RomMismatch { .. }
+            })
+        ));
+
+        let mut data = data;
+        data[4] += 1;
+        assert!(matches!(
+            StateReader::new(&data, 0x1234_5678),
+            Err(Error::StateError {
+                err: StateError::UnsupportedVersion { .. }
+            })
+        ));
+    }
+}
@@ -0,0 +1,10 @@
+use clap::ValueEnum;
+
+pub mod emu;
+
+#[derive(Clone, PartialEq, ValueEnum)]
+pub enum DebugLevel {
+    None,
+    Low,
+    High,
+}
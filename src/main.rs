@@ -1,14 +1,32 @@
-use std::process;
+use std::{
+    path::{Path, PathBuf},
+    process,
+};
 
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Parser, Subcommand};
 
-use crate::emu::{
-    cartridge::{Cartridge, ines::read_cartridge},
-    nes::NES,
+use green_nes::{
+    DebugLevel,
+    emu::{
+        cartridge::{
+            Cartridge, FileOptions, LoadOptions,
+            battery::SaveFile,
+            database::{Database, import_nes20db},
+            load_cartridge,
+        },
+        io::InputType,
+        nes::{
+            NES,
+            bindings::{BindingDevice, Bindings, PLAYER_COUNT},
+            headless::{HeadlessOptions, StopCondition, StopReason, encode_png},
+            movie::{Movie, MovieSession},
+            quick_save::{QuickSaves, SLOT_COUNT},
+            rewind::{DEFAULT_REWIND_INTERVAL, DEFAULT_REWIND_MEMORY_BUDGET, Rewind},
+            test_rom::{DEFAULT_TEST_ROM_TIMEOUT, TestRomStatus, run_test_rom},
+        },
+    },
 };
 
-pub mod emu;
-
 #[derive(clap::Parser)]
 struct Cli {
     /// Debug level
@@ -22,20 +40,185 @@ struct Cli {
 enum Commands {
     /// Runs a NES program in the emulator.
     Run {
-        /// Path to the NES program.
-        path: String,
-        // An optional starting address, for programs that do not respect the
-        // reset vector at $FFFC–$FFFD.
-        #[arg(value_parser = clap::value_parser!(u16), default_value_t = 0xFFFF)]
-        start_addr: u16,
+        #[command(flatten)]
+        cart: CartArgs,
+        /// Directory for battery-backed save files and quick-save states.
+        /// Defaults to the directory of the NES program.
+        #[arg(long)]
+        save_dir: Option<String>,
+        /// Path to the config file with the input bindings. Defaults to
+        /// `green-nes/config.toml` in the user's config directory.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// How often a state is recorded for rewinding with Backspace, in
+        /// frames.
+        #[arg(long, default_value_t = DEFAULT_REWIND_INTERVAL)]
+        rewind_interval: u64,
+        /// How much memory the states recorded for rewinding may take up, in
+        /// MiB. Set to 0 to disable rewinding.
+        #[arg(long, default_value_t = DEFAULT_REWIND_MEMORY_BUDGET / (1024 * 1024))]
+        rewind_memory: usize,
+        /// Number of frames to run ahead of the frame being shown, which hides
+        /// that many frames of input latency at the cost of running the
+        /// emulator that many more times per frame.
+        #[arg(long, default_value_t = 0)]
+        run_ahead: u32,
+        /// Records the input for each frame to an FM2 movie file, starting
+        /// from power-on. Battery-backed save files aren't loaded, so that the
+        /// movie plays back the same way.
+        #[arg(long, conflicts_with = "play")]
+        record: Option<PathBuf>,
+        /// Quick-save slot to start recording the movie from, rather than
+        /// power-on. The state is embedded in the movie.
+        #[arg(long, requires = "record", value_parser = clap::value_parser!(u8).range(1..=SLOT_COUNT as i64))]
+        record_from_slot: Option<u8>,
+        /// Plays back the input from an FM2 movie file. Battery-backed save
+        /// files aren't loaded.
+        #[arg(long)]
+        play: Option<PathBuf>,
+    },
+    /// Runs a NES program without a window until a stop condition is met or
+    /// the CPU executes a JAM instruction, for automated testing.
+    Headless {
+        #[command(flatten)]
+        cart: CartArgs,
+        /// Stops after this many frames.
+        #[arg(long)]
+        frames: Option<u64>,
+        /// Stops after this many CPU cycles.
+        #[arg(long)]
+        cycles: Option<u64>,
+        /// Stops when a byte of memory holds a value, given in hex as
+        /// `ADDR=VALUE`, e.g. `6000=80`. May be repeated.
+        #[arg(long, value_parser = parse_memory_condition)]
+        until_memory: Vec<StopCondition>,
+        /// Stops when the CPU reaches the instruction at an address, given in
+        /// hex. May be repeated.
+        #[arg(long, value_parser = parse_hex_addr)]
+        until_pc: Vec<u16>,
+        /// Plays back the input from an FM2 movie file.
+        #[arg(long)]
+        play: Option<PathBuf>,
+        /// Writes the last frame to a PNG file.
+        #[arg(long)]
+        screenshot: Option<PathBuf>,
+        /// Writes the contents of the 2 KiB of CPU RAM to a file.
+        #[arg(long)]
+        ram_dump: Option<PathBuf>,
+        /// Writes the CRC32 of each frame's pixels to a file, one per line in
+        /// hex.
+        #[arg(long)]
+        frame_hashes: Option<PathBuf>,
+    },
+    /// Runs a test ROM that reports its result at $6000, such as blargg's,
+    /// without a window. Prints the test's message and exits with its result
+    /// code, which is 0 if it passed, or 1 if it didn't finish.
+    TestRom {
+        #[command(flatten)]
+        cart: CartArgs,
+        /// Gives up on the test after this many seconds of emulated time.
+        #[arg(long, default_value_t = DEFAULT_TEST_ROM_TIMEOUT)]
+        timeout: u64,
+    },
+    /// Converts the NES 2.0 XML database (`nes20db.xml`) into entries for
+    /// the built-in game database, and prints them.
+    ImportDb {
+        /// Path to `nes20db.xml`.
+        path: PathBuf,
     },
+    /// Edits the config file.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+/// The arguments for loading a NES program.
+#[derive(clap::Args)]
+struct CartArgs {
+    /// Path to the NES program, or to a zip archive containing it.
+    path: String,
+    // An optional starting address, for programs that do not respect the
+    // reset vector at $FFFC–$FFFD.
+    #[arg(value_parser = clap::value_parser!(u16), default_value_t = 0xFFFF)]
+    start_addr: u16,
+    /// Disables header corrections from the built-in game database.
+    #[arg(long)]
+    no_db: bool,
+    /// Path to the Famicom Disk System BIOS, for running `.fds` disk
+    /// images. Defaults to `disksys.rom` in the directory of the disk
+    /// image.
+    #[arg(long)]
+    fds_bios: Option<String>,
+    /// IPS, BPS, or UPS patch to apply to the NES program. May be repeated
+    /// to apply several patches in order. Defaults to any patches next to
+    /// the NES program with the same name, e.g. `game.ips`.
+    #[arg(long)]
+    patch: Vec<String>,
+    /// Name of the NES program to run from a zip archive. Defaults to the
+    /// first `.nes`, `.unf`, `.unif`, or `.fds` file in the archive.
+    #[arg(long)]
+    entry: Option<String>,
+    /// Devices plugged into the controller ports. Defaults to the game
+    /// database's entry for the NES program, or standard controllers.
+    #[arg(long, value_enum)]
+    input: Option<InputType>,
+}
+
+impl CartArgs {
+    fn load_cart(&self) -> Cartridge {
+        let file_options = FileOptions {
+            entry: self.entry.clone(),
+            patch_paths: self.patch.clone(),
+            fds_bios_path: self.fds_bios.clone(),
+        };
+        let options = LoadOptions {
+            database: load_database(!self.no_db),
+            fds_bios: None,
+        };
+        load_cart(&self.path, &file_options, options)
+    }
+
+    fn get_start_addr(&self) -> Option<u16> {
+        match self.start_addr {
+            0xFFFF => None,
+            addr => Some(addr),
+        }
+    }
+
+    /// Gets the devices to plug in, which for a movie are the controllers it
+    /// was recorded with.
+    fn get_input(&self, cart: &Cartridge, movie: Option<&Movie>) -> InputType {
+        let input = self
+            .input
+            .or_else(|| InputType::from_expansion_device(cart.header.default_expansion_device))
+            .unwrap_or_default();
+
+        match movie {
+            Some(movie) if movie.four_score && input == InputType::FamicomFourPlayer => input,
+            Some(movie) if movie.four_score => InputType::FourScore,
+            Some(_) => InputType::Standard,
+            None => input,
+        }
+    }
 }
 
-#[derive(Clone, PartialEq, ValueEnum)]
-pub enum DebugLevel {
-    None,
-    Low,
-    High,
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Binds the buttons of a player's controller by pressing keys or gamepad
+    /// buttons for each in turn, and saves them to the config file.
+    Bind {
+        /// The player whose controller to bind.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=PLAYER_COUNT as i64))]
+        player: u8,
+        /// The host device to bind the buttons to.
+        #[arg(long, value_enum, default_value_t = BindingDevice::Keyboard)]
+        device: BindingDevice,
+        /// Path to the config file. Defaults to `green-nes/config.toml` in the
+        /// user's config directory.
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
 }
 
 fn main() {
@@ -43,29 +226,309 @@ fn main() {
     let debug_level = cli.debug;
 
     match cli.command {
-        Commands::Run { path, start_addr } => {
-            let cart = load_cart(&path);
+        Commands::Run {
+            cart: cart_args,
+            save_dir,
+            config,
+            rewind_interval,
+            rewind_memory,
+            run_ahead,
+            record,
+            record_from_slot,
+            play,
+        } => {
+            let path = &cart_args.path;
+            let cart = cart_args.load_cart();
+            let movie = play.as_deref().map(load_movie);
+            let save_file = match record.is_some() || movie.is_some() {
+                true => None,
+                false => load_save_file(&cart, path, save_dir.as_deref()),
+            };
 
-            let start_addr = match start_addr {
-                0xFFFF => None,
-                _ => Some(start_addr),
+            let input = cart_args.get_input(&cart, movie.as_ref());
+            let four_score = match input {
+                InputType::Standard => false,
+                InputType::FourScore | InputType::FamicomFourPlayer => true,
+                _ if record.is_some() => {
+                    eprintln!(
+                        "Recording movie failed: only standard controllers and four player adapters can be recorded"
+                    );
+                    process::exit(1);
+                }
+                _ => false,
             };
 
             let mut nes = NES::new(cart);
-            nes.cpu.poweron(&mut nes.buses, start_addr);
+            nes.buses.ports = input.create_ports();
+            nes.save_file = save_file;
+            nes.run_ahead = run_ahead;
+            nes.quick_saves = Some(QuickSaves::new(path, save_dir.as_deref()));
+            if rewind_memory > 0 {
+                nes.rewind = Some(Rewind::new(rewind_interval, rewind_memory * 1024 * 1024));
+            }
+            nes.bindings = load_bindings(&config.unwrap_or_else(Bindings::default_path));
+            nes.power_on(cart_args.get_start_addr());
+
+            let session = match (record, movie) {
+                (Some(record), _) => {
+                    let start_state = record_from_slot.map(|slot| load_quick_save(&nes, slot));
+                    let movie = Movie::new(path, four_score, start_state);
+                    Some(MovieSession::record(movie, record))
+                }
+                (None, Some(movie)) => Some(MovieSession::play(movie)),
+                (None, None) => None,
+            };
+            if let Some(session) = session
+                && let Err(err) = nes.start_movie(session)
+            {
+                eprintln!("Starting movie failed: {err}");
+                process::exit(1);
+            }
+
             nes.run(debug_level);
         }
+        Commands::Headless {
+            cart: cart_args,
+            frames,
+            cycles,
+            until_memory,
+            until_pc,
+            play,
+            screenshot,
+            ram_dump,
+            frame_hashes,
+        } => {
+            let cart = cart_args.load_cart();
+            let movie = play.as_deref().map(load_movie);
+            let input = cart_args.get_input(&cart, movie.as_ref());
+
+            let mut nes = NES::new(cart);
+            nes.buses.ports = input.create_ports();
+            nes.power_on(cart_args.get_start_addr());
+            if let Some(movie) = movie
+                && let Err(err) = nes.start_movie(MovieSession::play(movie))
+            {
+                eprintln!("Starting movie failed: {err}");
+                process::exit(1);
+            }
+
+            let mut stop_conditions = until_memory;
+            stop_conditions.extend(frames.map(StopCondition::Frames));
+            stop_conditions.extend(cycles.map(StopCondition::Cycles));
+            stop_conditions.extend(until_pc.into_iter().map(StopCondition::Pc));
+            let options = HeadlessOptions {
+                stop_conditions,
+                hash_frames: frame_hashes.is_some(),
+            };
+
+            let run = nes.run_headless(debug_level, &options);
+            let reason = match run.reason {
+                StopReason::Condition(StopCondition::Frames(_)) => {
+                    "frame limit reached".to_string()
+                }
+                StopReason::Condition(StopCondition::Cycles(_)) => {
+                    "cycle limit reached".to_string()
+                }
+                StopReason::Condition(StopCondition::Memory { addr, value }) => {
+                    format!("${addr:04X} holds ${value:02X}")
+                }
+                StopReason::Condition(StopCondition::Pc(addr)) => format!("PC reached ${addr:04X}"),
+                StopReason::Jam => "CPU jammed".to_string(),
+            };
+            println!(
+                "Stopped after {} frames and {} cycles: {reason}",
+                run.frames, run.cycles
+            );
+
+            if let Some(path) = screenshot {
+                write_output(&path, &encode_png(nes.buses.ppu.get_frame()));
+            }
+            if let Some(path) = ram_dump {
+                write_output(&path, nes.buses.get_ram());
+            }
+            if let Some(path) = frame_hashes {
+                let hashes: String = run
+                    .frame_hashes
+                    .iter()
+                    .map(|hash| format!("{hash:08X}\n"))
+                    .collect();
+                write_output(&path, hashes.as_bytes());
+            }
+        }
+        Commands::TestRom {
+            cart: cart_args,
+            timeout,
+        } => {
+            let cart = cart_args.load_cart();
+            let input = cart_args.get_input(&cart, None);
+
+            let mut nes = NES::new(cart);
+            nes.buses.ports = input.create_ports();
+            nes.power_on(cart_args.get_start_addr());
+
+            let result = run_test_rom(&mut nes, debug_level, timeout * 60);
+            let message = result.message.trim_end();
+            if !message.is_empty() {
+                println!("{message}");
+            }
+
+            match result.status {
+                TestRomStatus::Finished(code) => process::exit(code as i32),
+                TestRomStatus::TimedOut => {
+                    eprintln!("Test ROM timed out after {} frames", result.frames);
+                }
+                TestRomStatus::Jammed => {
+                    eprintln!("Test ROM failed: CPU jammed after {} frames", result.frames);
+                }
+            }
+            process::exit(1);
+        }
+        Commands::ImportDb { path } => {
+            let imported = std::fs::read_to_string(&path)
+                .map_err(|e| format!("{}: {e}", path.display()))
+                .and_then(|xml| import_nes20db(&xml).map_err(|e| e.to_string()));
+
+            match imported {
+                Ok(entries) => print!("{entries}"),
+                Err(err) => {
+                    eprintln!("Importing game database failed: {err}");
+                    process::exit(1);
+                }
+            }
+        }
+        Commands::Config {
+            command:
+                ConfigCommands::Bind {
+                    player,
+                    device,
+                    config,
+                },
+        } => {
+            let path = config.unwrap_or_else(Bindings::default_path);
+            let mut bindings = load_bindings(&path);
+
+            match bindings.record(player as usize - 1, device) {
+                Ok(true) => {}
+                Ok(false) => {
+                    eprintln!("Binding cancelled; the config file was not changed");
+                    process::exit(1);
+                }
+                Err(err) => {
+                    eprintln!("Binding failed: {err}");
+                    process::exit(1);
+                }
+            }
+
+            if let Err(err) = bindings.save(&path) {
+                eprintln!("Saving config file failed: {err}");
+                process::exit(1);
+            }
+            println!("Saved bindings to {}", path.display());
+        }
     }
 
     process::exit(0);
 }
 
-fn load_cart(path: &str) -> Cartridge {
-    match read_cartridge(path) {
-        Ok(cart) => cart,
+fn load_database(use_database: bool) -> Option<Database> {
+    if !use_database {
+        return None;
+    }
+
+    match Database::bundled() {
+        Ok(database) => Some(database),
+        Err(err) => {
+            eprintln!("Loading game database failed: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+fn load_bindings(path: &Path) -> Bindings {
+    match Bindings::load(path) {
+        Ok(bindings) => bindings,
+        Err(err) => {
+            eprintln!("Loading config file failed: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+fn write_output(path: &Path, data: &[u8]) {
+    if let Err(err) = std::fs::write(path, data) {
+        eprintln!("Writing {} failed: {err}", path.display());
+        process::exit(1);
+    }
+}
+
+fn parse_hex_addr(text: &str) -> Result<u16, String> {
+    let digits = text.trim_start_matches('$').trim_start_matches("0x");
+    u16::from_str_radix(digits, 16).map_err(|_| format!("{text} is not a hex address"))
+}
+
+fn parse_memory_condition(text: &str) -> Result<StopCondition, String> {
+    let (addr, value) = text
+        .split_once('=')
+        .ok_or_else(|| format!("{text} is not of the form ADDR=VALUE"))?;
+    let digits = value.trim_start_matches('$').trim_start_matches("0x");
+
+    Ok(StopCondition::Memory {
+        addr: parse_hex_addr(addr)?,
+        value: u8::from_str_radix(digits, 16).map_err(|_| format!("{value} is not a hex byte"))?,
+    })
+}
+
+fn load_movie(path: &Path) -> Movie {
+    match Movie::load(path) {
+        Ok(movie) => movie,
+        Err(err) => {
+            eprintln!("Loading movie failed: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+fn load_quick_save(nes: &NES, slot: u8) -> Vec<u8> {
+    let quick_saves = nes.quick_saves.as_ref().expect("quick saves should be set");
+    match quick_saves.read(slot as usize, nes.buses.get_cartridge().rom_hash) {
+        Ok(Some(save)) => save.state,
+        Ok(None) => {
+            eprintln!("Loading quick save failed: state {slot} is empty");
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("Loading quick save failed: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+fn load_cart(path: &str, file_options: &FileOptions, options: LoadOptions) -> Cartridge {
+    match load_cartridge(path, file_options, options) {
+        Ok(cart) => {
+            for warning in cart.warnings.iter() {
+                eprintln!("Warning: {warning}");
+            }
+
+            cart
+        }
         Err(err) => {
             eprintln!("Loading cartridge failed: {err}");
             process::exit(1);
         }
     }
 }
+
+fn load_save_file(cart: &Cartridge, path: &str, save_dir: Option<&str>) -> Option<SaveFile> {
+    if !cart.header.has_battery {
+        return None;
+    }
+
+    let mut save_file = SaveFile::new(path, save_dir);
+    if let Err(err) = save_file.load(cart) {
+        eprintln!("Loading save file failed: {err}");
+        process::exit(1);
+    }
+
+    Some(save_file)
+}